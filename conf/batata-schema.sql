@@ -0,0 +1,84 @@
+/*
+ * Tables Batata uses in addition to the Nacos MySQL schema (mysql-schema.sql of Nacos), create
+ * them in the same database.
+ */
+
+/******************************************/
+/*   table name = config_approval         */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `config_approval` (
+  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT 'id',
+  `data_id` varchar(255) NOT NULL COMMENT 'data_id',
+  `group_id` varchar(128) NOT NULL COMMENT 'group_id',
+  `tenant_id` varchar(128) NOT NULL DEFAULT '' COMMENT 'tenant_id',
+  `config_change` longtext NOT NULL COMMENT 'the submitted publish as json',
+  `base_md5` varchar(32) DEFAULT NULL COMMENT 'md5 of the config when submitted, null when it did not exist',
+  `status` varchar(16) NOT NULL COMMENT 'PENDING, APPROVED or REJECTED',
+  `submitter` varchar(128) NOT NULL COMMENT 'submitter',
+  `submit_time` bigint(20) NOT NULL COMMENT 'submit time in milliseconds',
+  `reviewer` varchar(128) DEFAULT NULL COMMENT 'reviewer',
+  `review_time` bigint(20) DEFAULT NULL COMMENT 'review time in milliseconds',
+  `comment` text COMMENT 'review comment',
+  PRIMARY KEY (`id`),
+  KEY `idx_status` (`status`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='config publish approvals';
+
+/******************************************/
+/*   table name = config_approval_namespace */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `config_approval_namespace` (
+  `namespace` varchar(128) NOT NULL COMMENT 'namespace id, public for the default namespace',
+  `operator` varchar(128) NOT NULL COMMENT 'operator',
+  `gmt_modified` bigint(20) NOT NULL COMMENT 'modify time in milliseconds',
+  PRIMARY KEY (`namespace`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='namespaces whose publishes need approval';
+
+/******************************************/
+/*   table name = user_preference         */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `user_preference` (
+  `username` varchar(50) NOT NULL COMMENT 'username',
+  `preference` text NOT NULL COMMENT 'console preferences and saved filters as json',
+  `gmt_modified` bigint(20) NOT NULL COMMENT 'modify time in milliseconds',
+  PRIMARY KEY (`username`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='console preferences per user';
+
+/******************************************/
+/*   table name = write_quota             */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `write_quota` (
+  `scope` varchar(16) NOT NULL COMMENT 'namespace or user',
+  `target` varchar(128) NOT NULL COMMENT 'namespace id or username, * for the default',
+  `limit_per_minute` int(10) unsigned NOT NULL COMMENT 'max publishes per minute, 0 is unlimited',
+  `gmt_modified` bigint(20) NOT NULL COMMENT 'modify time in milliseconds',
+  PRIMARY KEY (`scope`,`target`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='write quotas set through the admin api';
+
+/******************************************/
+/*   table name = write_lock              */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `write_lock` (
+  `namespace` varchar(128) NOT NULL COMMENT 'namespace id, public for the default namespace',
+  `group_id` varchar(128) NOT NULL COMMENT 'group_id, * for the whole namespace',
+  `reason` text NOT NULL COMMENT 'reason',
+  `operator` varchar(128) NOT NULL COMMENT 'operator',
+  `create_time` bigint(20) NOT NULL COMMENT 'create time in milliseconds',
+  `expire_time` bigint(20) DEFAULT NULL COMMENT 'expire time in milliseconds, null until removed',
+  PRIMARY KEY (`namespace`,`group_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='write locks of namespaces and groups';
+
+/******************************************/
+/*   table name = audit_record            */
+/******************************************/
+CREATE TABLE IF NOT EXISTS `audit_record` (
+  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT 'id',
+  `operator` varchar(128) NOT NULL COMMENT 'operator',
+  `src_ip` varchar(50) NOT NULL COMMENT 'source ip',
+  `resource_type` varchar(32) NOT NULL COMMENT 'config, namespace, user, role, permission, ...',
+  `action` varchar(32) NOT NULL COMMENT 'action',
+  `resource` text NOT NULL COMMENT 'changed resource',
+  `time` bigint(20) NOT NULL COMMENT 'operation time in milliseconds',
+  PRIMARY KEY (`id`),
+  KEY `idx_time` (`time`),
+  KEY `idx_operator` (`operator`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin COMMENT='audit records of console write operations';
@@ -0,0 +1,413 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use serde_json::Value;
+
+const DEFAULT_SERVER: &str = "http://127.0.0.1:8848/nacos";
+const DEFAULT_GROUP: &str = "DEFAULT_GROUP";
+const EXPORT_PAGE_SIZE: u64 = 100;
+
+// Operator command line for a running server. Credentials come from the flags, then the
+// BATATA_* environment variables, then the profile file
+#[derive(Debug, Parser)]
+#[command(name = "batata-cli", version, about = "Batata operator command line")]
+struct Cli {
+    #[arg(long, env = "BATATA_SERVER")]
+    server: Option<String>,
+    #[arg(long, env = "BATATA_USERNAME")]
+    username: Option<String>,
+    #[arg(long, env = "BATATA_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+    /// key=value lines with server, username and password, defaults to ~/.batata/profile
+    #[arg(long, env = "BATATA_PROFILE")]
+    profile: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    #[command(subcommand)]
+    Cluster(ClusterCommand),
+    #[command(subcommand)]
+    User(UserCommand),
+    #[command(subcommand)]
+    Role(RoleCommand),
+}
+
+#[derive(Debug, Args)]
+struct ConfigKey {
+    #[arg(long)]
+    data_id: String,
+    #[arg(long, default_value = DEFAULT_GROUP)]
+    group: String,
+    #[arg(long, default_value = "")]
+    namespace: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the content of a config
+    Get(ConfigKey),
+    /// Publish a config from --content or --file
+    Publish {
+        #[command(flatten)]
+        key: ConfigKey,
+        #[arg(long, conflicts_with = "file")]
+        content: Option<String>,
+        #[arg(long)]
+        file: Option<PathBuf>,
+        #[arg(long, default_value = "text")]
+        r#type: String,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write every config of a namespace to <output>/<group>/<dataId>
+    Export {
+        #[arg(long, default_value = "")]
+        namespace: String,
+        #[arg(long, default_value = "")]
+        group: String,
+        #[arg(long, default_value = ".")]
+        output: PathBuf,
+    },
+    /// Print config changes of a namespace as JSON lines until interrupted
+    Watch {
+        #[arg(long, default_value = "")]
+        namespace: String,
+        /// Group pattern, * matches any characters
+        #[arg(long, default_value = "")]
+        group: String,
+        /// DataId pattern, * matches any characters
+        #[arg(long, default_value = "")]
+        data_id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ClusterCommand {
+    /// List the cluster members and their state
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+enum UserCommand {
+    List {
+        #[arg(long, default_value = "")]
+        username: String,
+    },
+    Create {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        password: String,
+    },
+    Delete {
+        #[arg(long)]
+        username: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RoleCommand {
+    List {
+        #[arg(long, default_value = "")]
+        username: String,
+    },
+    Add {
+        #[arg(long)]
+        role: String,
+        #[arg(long)]
+        username: String,
+    },
+    Remove {
+        #[arg(long)]
+        role: String,
+        #[arg(long)]
+        username: String,
+    },
+}
+
+struct Client {
+    server: String,
+    access_token: Option<String>,
+}
+
+impl Client {
+    fn connect(cli: &Cli) -> anyhow::Result<Self> {
+        let profile = read_profile(cli.profile.clone())?;
+        let setting =
+            |value: &Option<String>, key: &str| value.clone().or_else(|| profile.get(key).cloned());
+        let server = setting(&cli.server, "server")
+            .unwrap_or(DEFAULT_SERVER.to_string())
+            .trim_end_matches('/')
+            .to_string();
+        let mut client = Client {
+            server,
+            access_token: None,
+        };
+
+        // without credentials the server has to run with auth disabled
+        if let (Some(username), Some(password)) = (
+            setting(&cli.username, "username"),
+            setting(&cli.password, "password"),
+        ) {
+            let login: Value = Client::send(
+                client
+                    .request("POST", "/v1/auth/users/login")
+                    .send_form(&[("username", &username), ("password", &password)]),
+            )?;
+
+            client.access_token = login["accessToken"].as_str().map(String::from);
+        }
+
+        Ok(client)
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let req = ureq::request(method, &format!("{}{}", self.server, path));
+
+        match &self.access_token {
+            Some(access_token) => req.set("accessToken", access_token),
+            None => req,
+        }
+    }
+
+    fn send(result: Result<ureq::Response, ureq::Error>) -> anyhow::Result<Value> {
+        Ok(checked(result)?.into_json()?)
+    }
+
+    // Calls the path and hands every line of the response body to the callback as it arrives
+    fn stream(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        mut on_line: impl FnMut(&str),
+    ) -> anyhow::Result<()> {
+        let response = checked(self.request("GET", path).query_pairs(query.to_vec()).call())?;
+
+        for line in BufReader::new(response.into_reader()).lines() {
+            on_line(&line?);
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, path: &str, query: &[(&str, &str)]) -> anyhow::Result<Value> {
+        Self::send(self.request("GET", path).query_pairs(query.to_vec()).call())
+    }
+
+    fn post(&self, path: &str, form: &[(&str, &str)]) -> anyhow::Result<Value> {
+        Self::send(self.request("POST", path).send_form(form))
+    }
+
+    fn delete(&self, path: &str, query: &[(&str, &str)]) -> anyhow::Result<Value> {
+        Self::send(
+            self.request("DELETE", path)
+                .query_pairs(query.to_vec())
+                .call(),
+        )
+    }
+}
+
+fn checked(result: Result<ureq::Response, ureq::Error>) -> anyhow::Result<ureq::Response> {
+    match result {
+        Ok(response) => Ok(response),
+        Err(ureq::Error::Status(status, response)) => anyhow::bail!(
+            "server answered {}: {}",
+            status,
+            response.into_string().unwrap_or_default()
+        ),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn read_profile(path: Option<PathBuf>) -> anyhow::Result<HashMap<String, String>> {
+    let path = match path {
+        Some(path) => path,
+        None => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".batata").join("profile"),
+            None => return Ok(HashMap::new()),
+        },
+    };
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("read profile {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect())
+}
+
+fn print_json(value: &Value) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+
+    Ok(())
+}
+
+fn config_command(client: &Client, command: ConfigCommand) -> anyhow::Result<()> {
+    match command {
+        ConfigCommand::Get(key) => {
+            let config = client.get(
+                "/v1/cs/configs",
+                &[
+                    ("show", "all"),
+                    ("dataId", &key.data_id),
+                    ("group", &key.group),
+                    ("tenant", &key.namespace),
+                ],
+            )?;
+
+            print!("{}", config["content"].as_str().unwrap_or_default());
+
+            Ok(())
+        }
+        ConfigCommand::Publish {
+            key,
+            content,
+            file,
+            r#type,
+            dry_run,
+        } => {
+            let content = match (content, file) {
+                (Some(content), _) => content,
+                (None, Some(file)) => {
+                    fs::read_to_string(&file).with_context(|| format!("read {}", file.display()))?
+                }
+                (None, None) => anyhow::bail!("either --content or --file is required"),
+            };
+            let dry_run = dry_run.to_string();
+
+            print_json(&client.post(
+                "/v1/cs/configs",
+                &[
+                    ("dataId", &key.data_id),
+                    ("group", &key.group),
+                    ("tenant", &key.namespace),
+                    ("content", &content),
+                    ("type", &r#type),
+                    ("dryRun", &dry_run),
+                ],
+            )?)
+        }
+        ConfigCommand::Export {
+            namespace,
+            group,
+            output,
+        } => {
+            let mut page_no = 1u64;
+            let mut exported = 0;
+
+            loop {
+                let page = client.get(
+                    "/v1/cs/configs",
+                    &[
+                        ("search", "blur"),
+                        ("tenant", &namespace),
+                        ("group", &group),
+                        ("pageNo", &page_no.to_string()),
+                        ("pageSize", &EXPORT_PAGE_SIZE.to_string()),
+                    ],
+                )?;
+                let items = page["pageItems"].as_array().cloned().unwrap_or_default();
+
+                for item in &items {
+                    let dir = output.join(item["group"].as_str().unwrap_or(DEFAULT_GROUP));
+
+                    fs::create_dir_all(&dir)?;
+                    fs::write(
+                        dir.join(item["dataId"].as_str().unwrap_or_default()),
+                        item["content"].as_str().unwrap_or_default(),
+                    )?;
+
+                    exported += 1;
+                }
+
+                if page_no >= page["pagesAvailable"].as_u64().unwrap_or_default() {
+                    break;
+                }
+
+                page_no += 1;
+            }
+
+            eprintln!("exported {} configs to {}", exported, output.display());
+
+            Ok(())
+        }
+        // the server streams changes published on the node it is connected to
+        ConfigCommand::Watch {
+            namespace,
+            group,
+            data_id,
+        } => client.stream(
+            "/v1/cs/configs/watch",
+            &[
+                ("tenant", &namespace),
+                ("group", &group),
+                ("dataId", &data_id),
+            ],
+            |line| println!("{}", line),
+        ),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let client = Client::connect(&cli)?;
+
+    match cli.command {
+        Command::Config(command) => config_command(&client, command),
+        Command::Cluster(ClusterCommand::Status) => {
+            print_json(&client.get("/v1/core/cluster/nodes", &[])?)
+        }
+        Command::User(UserCommand::List { username }) => print_json(&client.get(
+            "/v1/auth/users",
+            &[
+                ("search", "blur"),
+                ("username", &username),
+                ("pageNo", "1"),
+                ("pageSize", "100"),
+            ],
+        )?),
+        Command::User(UserCommand::Create { username, password }) => print_json(&client.post(
+            "/v1/auth/users",
+            &[("username", &username), ("password", &password)],
+        )?),
+        Command::User(UserCommand::Delete { username }) => {
+            print_json(&client.delete("/v1/auth/users", &[("username", &username)])?)
+        }
+        Command::Role(RoleCommand::List { username }) => print_json(&client.get(
+            "/v1/auth/roles",
+            &[
+                ("search", "blur"),
+                ("username", &username),
+                ("pageNo", "1"),
+                ("pageSize", "100"),
+            ],
+        )?),
+        Command::Role(RoleCommand::Add { role, username }) => print_json(&client.post(
+            "/v1/auth/roles",
+            &[("role", &role), ("username", &username)],
+        )?),
+        Command::Role(RoleCommand::Remove { role, username }) => print_json(&client.delete(
+            "/v1/auth/roles",
+            &[("role", &role), ("username", &username)],
+        )?),
+    }
+}
@@ -0,0 +1,368 @@
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use actix_web::{get, http::header::ContentType, web, HttpResponse, Responder, Scope};
+use futures_util::future::join_all;
+
+use crate::model::common::AppState;
+
+const MEMBER_SCRAPE_TIMEOUT: Duration = Duration::from_secs(3);
+// samples of histograms and summaries carry the family name with one of these
+const SAMPLE_SUFFIXES: [&str; 4] = ["_bucket", "_sum", "_count", "_created"];
+
+#[derive(Debug, Default)]
+struct MetricFamily {
+    help: Option<String>,
+    metric_type: Option<String>,
+    samples: Vec<String>,
+}
+
+// Metric families of all members in first seen order, every sample labeled with its member.
+// Prometheus wants the samples of a family together, so they cannot just be concatenated
+#[derive(Debug, Default)]
+struct ClusterMetrics {
+    names: Vec<String>,
+    families: HashMap<String, MetricFamily>,
+}
+
+impl ClusterMetrics {
+    fn family(&mut self, name: &str) -> &mut MetricFamily {
+        if !self.families.contains_key(name) {
+            self.names.push(name.to_string());
+        }
+
+        self.families.entry(name.to_string()).or_default()
+    }
+
+    fn add(&mut self, member: &str, text: &str) {
+        let mut current = String::new();
+
+        for line in text.lines().map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut parts = comment.trim_start().splitn(3, ' ');
+
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some("HELP"), Some(name), help) => {
+                        current = name.to_string();
+                        self.family(name).help = Some(help.unwrap_or_default().to_string());
+                    }
+                    (Some("TYPE"), Some(name), metric_type) => {
+                        current = name.to_string();
+                        self.family(name).metric_type =
+                            Some(metric_type.unwrap_or_default().to_string());
+                    }
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+            let (name, rest) = line.split_at(name_end);
+            // histogram and summary samples like x_bucket belong to the family x announced before,
+            // while x_other is a family of its own
+            let family = match name.strip_prefix(current.as_str()) {
+                Some(suffix)
+                    if !current.is_empty()
+                        && (suffix.is_empty() || SAMPLE_SUFFIXES.contains(&suffix)) =>
+                {
+                    current.clone()
+                }
+                _ => name.to_string(),
+            };
+            let sample = match rest.strip_prefix('{') {
+                Some(labels) => format!("{}{{member=\"{}\",{}", name, member, labels),
+                None => format!("{}{{member=\"{}\"}}{}", name, member, rest),
+            };
+
+            self.family(&family).samples.push(sample);
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        for name in &self.names {
+            let family = &self.families[name];
+
+            if let Some(help) = &family.help {
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+            }
+            if let Some(metric_type) = &family.metric_type {
+                let _ = writeln!(out, "# TYPE {} {}", name, metric_type);
+            }
+            for sample in &family.samples {
+                let _ = writeln!(out, "{}", sample);
+            }
+        }
+
+        out
+    }
+}
+
+fn write_metric(out: &mut String, name: &str, metric_type: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, metric_type);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+#[utoipa::path(
+    context_path = "/actuator",
+    operation_id = "actuator_prometheus",
+    tag = "actuator",
+    responses((status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain"))
+)]
+#[get("/prometheus")]
+pub async fn prometheus(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(render(&data))
+}
+
+// Unlike /actuator/prometheus this one requires a token while the open api auth is on, it makes
+// every member do work on each scrape
+#[utoipa::path(
+    context_path = "/metrics",
+    operation_id = "metrics_cluster",
+    tag = "actuator",
+    responses((status = 200, description = "Metrics of every cluster member in the Prometheus text format, each sample labeled with member=ip:port. nacos_cluster_member_up tells which members answered within 3 seconds", body = String, content_type = "text/plain"))
+)]
+#[get("/cluster")]
+pub async fn cluster(data: web::Data<AppState>) -> impl Responder {
+    let local_address = data.member_manager.get_self().address;
+    let scrapes = data
+        .member_manager
+        .peer_urls(&data.context_path)
+        .into_iter()
+        .map(|(address, url)| {
+            let url = format!("{}/actuator/prometheus", url);
+
+            async move {
+                let text = web::block(move || {
+                    ureq::get(&url)
+                        .timeout(MEMBER_SCRAPE_TIMEOUT)
+                        .call()
+                        .map_err(anyhow::Error::from)
+                        .and_then(|response| Ok(response.into_string()?))
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+
+                if let Err(err) = &text {
+                    tracing::warn!("scrape metrics of member {} failed: {}", address, err);
+                }
+
+                (address, text.ok())
+            }
+        });
+    let mut results = join_all(scrapes).await;
+
+    results.insert(0, (local_address, Some(render(&data))));
+
+    let mut metrics = ClusterMetrics::default();
+    let up = metrics.family("nacos_cluster_member_up");
+
+    up.help = Some(String::from(
+        "Whether the member answered the metrics scrape",
+    ));
+    up.metric_type = Some(String::from("gauge"));
+    up.samples = results
+        .iter()
+        .map(|(member, text)| {
+            format!(
+                "nacos_cluster_member_up{{member=\"{}\"}} {}",
+                member,
+                text.is_some() as u8
+            )
+        })
+        .collect();
+
+    for (member, text) in &results {
+        if let Some(text) = text {
+            metrics.add(member, text);
+        }
+    }
+
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(metrics.render())
+}
+
+fn render(data: &AppState) -> String {
+    let stats = data.pool_monitor.stats();
+    let mut out = String::new();
+
+    write_metric(
+        &mut out,
+        "nacos_db_pool_max_connections",
+        "gauge",
+        "Maximum connections of the database pool",
+        stats.max_connections as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_db_pool_connections",
+        "gauge",
+        "Open connections of the database pool",
+        stats.size as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_db_pool_idle_connections",
+        "gauge",
+        "Idle connections of the database pool",
+        stats.idle as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_db_pool_in_use_connections",
+        "gauge",
+        "Checked out connections of the database pool",
+        stats.in_use as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_db_pool_acquire_seconds",
+        "gauge",
+        "Latency of the last sampled connection acquire",
+        stats.acquire_seconds,
+    );
+    write_metric(
+        &mut out,
+        "nacos_db_pool_acquire_failures_total",
+        "counter",
+        "Sampled connection acquires that failed or timed out",
+        stats.acquire_failures as f64,
+    );
+
+    write_metric(
+        &mut out,
+        "nacos_config_watchers",
+        "gauge",
+        "Open config watch streams and WebSockets",
+        data.watch_registry.watcher_count() as f64,
+    );
+
+    let md5_sweep = data.md5_sweeper.stats();
+
+    write_metric(
+        &mut out,
+        "nacos_config_md5_sweep_runs_total",
+        "counter",
+        "Completed sweeps recomputing the md5 of stored configs",
+        md5_sweep.runs as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_mismatches_total",
+        "counter",
+        "Stored configs found with an md5 not matching their content",
+        md5_sweep.mismatches as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_repaired_total",
+        "counter",
+        "Mismatched md5 values rewritten from the content",
+        md5_sweep.repaired as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_undecryptable_total",
+        "counter",
+        "Encrypted configs the sweep could not decrypt to check",
+        md5_sweep.undecryptable as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_sweep_last_checked",
+        "gauge",
+        "Configs checked by the last sweep",
+        md5_sweep.last_checked as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_sweep_last_mismatches",
+        "gauge",
+        "Mismatched configs found by the last sweep",
+        md5_sweep.last_mismatches as f64,
+    );
+    write_metric(
+        &mut out,
+        "nacos_config_md5_sweep_last_run_timestamp_seconds",
+        "gauge",
+        "End of the last sweep, 0 before the first one",
+        (md5_sweep.last_run_time / 1000) as f64,
+    );
+
+    out
+}
+
+pub fn routers() -> Scope {
+    web::scope("/actuator").service(prometheus)
+}
+
+pub fn metrics_routers() -> Scope {
+    web::scope("/metrics").service(cluster)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn families_of_all_members_are_merged_with_member_labels() {
+        let mut metrics = ClusterMetrics::default();
+        let text =
+            "# HELP up_total Requests\n# TYPE up_total counter\nup_total 1\nother{a=\"b\"} 2\n";
+
+        metrics.add("10.0.0.1:8848", text);
+        metrics.add("10.0.0.2:8848", text);
+
+        assert_eq!(
+            metrics.render(),
+            "# HELP up_total Requests\n\
+             # TYPE up_total counter\n\
+             up_total{member=\"10.0.0.1:8848\"} 1\n\
+             up_total{member=\"10.0.0.2:8848\"} 1\n\
+             other{member=\"10.0.0.1:8848\",a=\"b\"} 2\n\
+             other{member=\"10.0.0.2:8848\",a=\"b\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn histogram_samples_stay_in_their_family() {
+        let mut metrics = ClusterMetrics::default();
+
+        metrics.add(
+            "m",
+            "# TYPE latency histogram\n\
+             latency_bucket{le=\"1\"} 3\n\
+             latency_sum 2.5\n\
+             latency_count 3\n",
+        );
+
+        assert_eq!(metrics.names, vec!["latency"]);
+        assert_eq!(metrics.families["latency"].samples.len(), 3);
+    }
+
+    #[test]
+    fn family_name_is_not_matched_as_a_prefix() {
+        let mut metrics = ClusterMetrics::default();
+
+        metrics.add(
+            "m",
+            "# TYPE nacos_db_pool_connections gauge\n\
+             nacos_db_pool_connections 5\n\
+             nacos_db_pool_connections_max 10\n",
+        );
+
+        assert_eq!(
+            metrics.names,
+            vec!["nacos_db_pool_connections", "nacos_db_pool_connections_max"]
+        );
+        assert!(metrics.families["nacos_db_pool_connections_max"]
+            .metric_type
+            .is_none());
+    }
+}
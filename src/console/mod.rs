@@ -1,14 +1,30 @@
+pub mod actuator;
+pub mod openapi;
 pub mod v1 {
+    pub mod activity;
+    pub mod approval;
     pub mod auth;
+    pub mod auth_admin;
+    pub mod cluster;
     pub mod config;
+    pub mod guard;
     pub mod health;
     pub mod history;
+    pub mod history_audit;
+    pub mod ip_filter;
+    pub mod locality;
+    pub mod maintenance;
     pub mod namespace;
+    pub mod peer;
     pub mod permission;
+    pub mod preference;
+    pub mod quota;
     pub mod role;
     pub mod router;
     pub mod server_state;
+    pub mod sync;
     pub mod user;
+    pub mod write_lock;
 }
 pub mod v2 {
     pub mod config;
@@ -17,3 +33,11 @@ pub mod v2 {
     pub mod namespace;
     pub mod router;
 }
+pub mod v3 {
+    pub mod audit;
+    pub mod history;
+    pub mod permission;
+    pub mod reload;
+    pub mod router;
+    pub mod ws;
+}
@@ -0,0 +1,123 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use utoipa::{openapi::Server, OpenApi};
+
+use crate::{
+    console,
+    model::{
+        common::AppState,
+        config::{ConfigAllInfo, PublishReport, SchemaViolation},
+    },
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Batata", description = "Nacos compatible HTTP API"),
+    paths(
+        console::v1::activity::search,
+        console::v1::auth::users_login,
+        console::v1::user::search_page,
+        console::v1::user::search,
+        console::v1::user::create,
+        console::v1::user::update,
+        console::v1::user::delete,
+        console::v1::role::search_page,
+        console::v1::role::search,
+        console::v1::role::create,
+        console::v1::role::delete,
+        console::v1::permission::search_page,
+        console::v1::permission::create,
+        console::v1::permission::delete,
+        console::v1::config::search,
+        console::v1::config::watch,
+        console::v1::config::create_or_update,
+        console::v1::history::search,
+        console::v1::history::get_data_ids,
+        console::v1::history_audit::search,
+        console::v1::namespace::get_all,
+        console::v1::namespace::create,
+        console::v1::namespace::update,
+        console::v1::namespace::delete,
+        console::v1::preference::get,
+        console::v1::preference::update,
+        console::v1::approval::list,
+        console::v1::approval::approve,
+        console::v1::approval::reject,
+        console::v1::approval::get_namespaces,
+        console::v1::approval::set_namespaces,
+        console::v1::server_state::state,
+        console::v1::server_state::announcement,
+        console::v1::server_state::guide,
+        console::v1::health::liveness,
+        console::v1::health::readiness,
+        console::v1::auth_admin::state,
+        console::v1::auth_admin::switch,
+        console::v1::auth_admin::rotate_secret,
+        console::v1::cluster::get_self,
+        console::v1::cluster::list_nodes,
+        console::v1::cluster::get_lookup,
+        console::v1::cluster::switch_lookup,
+        console::v1::cluster::events,
+        console::v1::quota::list,
+        console::v1::quota::create,
+        console::v1::quota::delete,
+        console::v1::ip_filter::list,
+        console::v1::ip_filter::update,
+        console::v1::locality::list,
+        console::v1::locality::update,
+        console::v1::maintenance::status,
+        console::v1::maintenance::enter,
+        console::v1::maintenance::leave,
+        console::v1::write_lock::list,
+        console::v1::write_lock::create,
+        console::v1::write_lock::delete,
+        console::v1::sync::list,
+        console::v1::sync::create,
+        console::v1::sync::delete,
+        console::v1::sync::run,
+        console::v2::config::search,
+        console::v2::health::liveness,
+        console::v2::health::readiness,
+        console::v3::permission::check,
+        console::v3::audit::search,
+        console::v3::history::rollback,
+        console::v3::reload::reload,
+        console::v3::ws::config,
+        console::actuator::prometheus,
+        console::actuator::cluster,
+    ),
+    components(schemas(ConfigAllInfo, PublishReport, SchemaViolation)),
+    tags(
+        (name = "auth", description = "Login, auth switches and token secret rotation"),
+        (name = "user", description = "Console user management"),
+        (name = "role", description = "Console role management"),
+        (name = "permission", description = "Console permission management"),
+        (name = "config", description = "Config server API"),
+        (name = "history", description = "Config history"),
+        (name = "namespace", description = "Console namespace management"),
+        (name = "preference", description = "Console preferences and saved filters of the current user"),
+        (name = "approval", description = "Config publish approval"),
+        (name = "activity", description = "Operator activity feed"),
+        (name = "audit", description = "Persisted audit log of console write operations (admin)"),
+        (name = "server", description = "Console server state and config reload"),
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "cluster", description = "Cluster members, member lookup and cluster events (admin)"),
+        (name = "quota", description = "Write quotas (admin)"),
+        (name = "ip-filter", description = "IP allow and deny lists (admin)"),
+        (name = "locality", description = "Client region and zone labels (admin)"),
+        (name = "maintenance", description = "Draining a node before a restart (admin)"),
+        (name = "write-lock", description = "Namespace and group change freezes (admin)"),
+        (name = "sync", description = "Config sync from other clusters (admin)"),
+        (name = "actuator", description = "Metrics for monitoring systems"),
+    )
+)]
+pub struct ApiDoc;
+
+#[get("/api-docs")]
+pub async fn api_docs(data: web::Data<AppState>) -> impl Responder {
+    let mut openapi = ApiDoc::openapi();
+
+    // paths are documented relative to the context path which is only known at runtime
+    openapi.servers = Some(vec![Server::new(data.context_path.clone())]);
+
+    HttpResponse::Ok().json(openapi)
+}
@@ -0,0 +1,91 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    console::v1::guard,
+    model::common::{AppState, Page, RestResult},
+    service::{activity::Activity, audit::AuditRecord},
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct SearchParam {
+    actor: Option<String>,
+    resource_type: Option<String>,
+    page_no: Option<u64>,
+    page_size: Option<u64>,
+}
+
+// Configs are named namespace:group:dataId like permission resources
+pub fn config_resource(tenant: &str, group: &str, data_id: &str) -> String {
+    format!(
+        "{}:{}:{}",
+        if tenant.is_empty() { "public" } else { tenant },
+        group,
+        data_id
+    )
+}
+
+pub fn record(
+    data: &web::Data<AppState>,
+    req: &HttpRequest,
+    resource_type: &str,
+    action: &str,
+    resource: &str,
+) {
+    let operator = guard::current_username(req).unwrap_or_default();
+
+    data.activity_manager
+        .record(&operator, resource_type, action, resource);
+
+    if data.audit_manager.is_enabled() {
+        data.audit_manager.record(AuditRecord {
+            operator,
+            src_ip: String::from(
+                req.connection_info()
+                    .realip_remote_addr()
+                    .unwrap_or_default(),
+            ),
+            resource_type: resource_type.to_string(),
+            action: action.to_string(),
+            resource: resource.to_string(),
+            time: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/activities",
+    operation_id = "activity_search",
+    tag = "activity",
+    params(SearchParam),
+    responses(
+        (status = 200, description = "Write operations of console users on this node, newest first", body = RestResult<Page<Activity>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn search(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<SearchParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<Page<Activity>>::success(
+        data.activity_manager.search(
+            &params.actor.clone().unwrap_or_default(),
+            &params.resource_type.clone().unwrap_or_default(),
+            params.page_no.unwrap_or(1).max(1),
+            params.page_size.unwrap_or(20).max(1),
+        ),
+    ))
+}
+
+pub fn routers() -> Scope {
+    web::scope("/activities").service(search)
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    console::v1::{
+        activity::{self, config_resource},
+        guard, peer,
+    },
+    model::{
+        auth::GLOBAL_ADMIN_ROLE,
+        common::{AppState, BusinessError, Page, RestResult},
+    },
+    service::{
+        self,
+        approval::{ApprovalRecord, ApprovalStatus},
+        event::ServerEvent,
+        ip_filter::split_list,
+    },
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct ListParam {
+    status: Option<String>,
+    page_no: Option<u64>,
+    page_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = approval::ReviewFormData)]
+struct ReviewFormData {
+    comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = approval::NamespacesFormData)]
+struct NamespacesFormData {
+    namespaces: Option<String>,
+    // set by the member the namespaces were changed on, only reload them
+    local: Option<bool>,
+}
+
+async fn is_approver(data: &web::Data<AppState>, req: &HttpRequest) -> bool {
+    guard::has_any_role(
+        data,
+        req,
+        &[GLOBAL_ADMIN_ROLE, data.approval_manager.approver_role()],
+    )
+    .await
+}
+
+fn not_approver() -> HttpResponse {
+    RestResult::<String>::http_error(
+        &BusinessError::AccessDenied(String::from("only approvers can review config changes"))
+            .into(),
+    )
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/approvals",
+    operation_id = "approval_list",
+    tag = "approval",
+    params(ListParam),
+    responses(
+        (status = 200, description = "Config changes, newest first. Sensitive values are masked unless the user can unmask the namespace", body = RestResult<Page<ApprovalRecord>>),
+        (status = 403, description = "Not an approver", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<ListParam>,
+) -> impl Responder {
+    if !is_approver(&data, &req).await {
+        return not_approver();
+    }
+
+    let status = match &params.status {
+        Some(name) if !name.is_empty() => match ApprovalStatus::from_name(name) {
+            Some(status) => Some(status),
+            None => {
+                return RestResult::<String>::http_error(
+                    &BusinessError::ParameterValidate(format!("unknown status: {}", name)).into(),
+                )
+            }
+        },
+        _ => None,
+    };
+
+    match data
+        .approval_manager
+        .list(
+            status,
+            params.page_no.unwrap_or(1),
+            params.page_size.unwrap_or(100),
+        )
+        .await
+    {
+        Ok(mut page) => {
+            // unmask is decided once per namespace
+            let mut unmask: HashMap<String, bool> = HashMap::new();
+
+            for record in page.page_items.iter_mut() {
+                let tenant = record.change.tenant.clone();
+                let can_unmask = match unmask.get(&tenant) {
+                    Some(can_unmask) => *can_unmask,
+                    None => {
+                        let can_unmask = guard::can_unmask(&data, &req, &tenant).await;
+
+                        unmask.insert(tenant, can_unmask);
+
+                        can_unmask
+                    }
+                };
+
+                if !can_unmask {
+                    record.change.content = data.mask_manager.mask(&record.change.content);
+                }
+            }
+
+            HttpResponse::Ok().json(RestResult::<Page<ApprovalRecord>>::success(page))
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/approvals",
+    operation_id = "approval_approve",
+    tag = "approval",
+    params(("id" = u64, Path, description = "Approval id")),
+    request_body(content = ReviewFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The change was approved and published", body = RestResult<ApprovalRecord>),
+        (status = 403, description = "Not an approver, or the submitter itself", body = RestResult<String>),
+        (status = 404, description = "Approval not found", body = RestResult<String>),
+        (status = 409, description = "Approval already reviewed, or the config changed since it was submitted", body = RestResult<String>),
+        (status = 423, description = "The namespace or group of the change is write locked", body = RestResult<String>)
+    )
+)]
+#[post("/{id}/approve")]
+pub async fn approve(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<u64>,
+    form: web::Form<ReviewFormData>,
+) -> impl Responder {
+    if !is_approver(&data, &req).await {
+        return not_approver();
+    }
+
+    let id = path.into_inner();
+    let reviewer = guard::current_username(&req).unwrap_or_default();
+
+    let record = match data
+        .approval_manager
+        .review(
+            id,
+            &reviewer,
+            ApprovalStatus::Approved,
+            form.comment.clone(),
+        )
+        .await
+    {
+        Ok(record) => record,
+        Err(err) => return RestResult::<String>::http_error(&err),
+    };
+
+    // an approved rollback is recorded in history as one
+    let op_type = match record.change.op_type.as_str() {
+        "" => service::config::OP_TYPE_UPDATE,
+        op_type => op_type,
+    };
+
+    if let Err(err) = service::config::publish_as(
+        &data.database_connection,
+        &data.content_cipher,
+        &data.capacity_policy,
+        &record.change,
+        op_type,
+    )
+    .await
+    {
+        if let Err(reopen_err) = data.approval_manager.reopen(id).await {
+            tracing::error!("reopen approval {} failed: {}", id, reopen_err);
+        }
+
+        return RestResult::<String>::http_error(&err);
+    }
+
+    data.event_bus
+        .publish(ServerEvent::config_changed(&record.change));
+
+    activity::record(
+        &data,
+        &req,
+        "config",
+        "approve",
+        &config_resource(
+            &record.change.tenant,
+            &record.change.group,
+            &record.change.data_id,
+        ),
+    );
+
+    tracing::info!(
+        "config change {} submitted by {} approved by {}",
+        id,
+        record.submitter,
+        reviewer
+    );
+
+    HttpResponse::Ok().json(RestResult::<ApprovalRecord>::success(record))
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/approvals",
+    operation_id = "approval_reject",
+    tag = "approval",
+    params(("id" = u64, Path, description = "Approval id")),
+    request_body(content = ReviewFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The change was rejected", body = RestResult<ApprovalRecord>),
+        (status = 403, description = "Not an approver, or the submitter itself", body = RestResult<String>),
+        (status = 404, description = "Approval not found", body = RestResult<String>),
+        (status = 409, description = "Approval already reviewed", body = RestResult<String>)
+    )
+)]
+#[post("/{id}/reject")]
+pub async fn reject(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<u64>,
+    form: web::Form<ReviewFormData>,
+) -> impl Responder {
+    if !is_approver(&data, &req).await {
+        return not_approver();
+    }
+
+    let id = path.into_inner();
+    let reviewer = guard::current_username(&req).unwrap_or_default();
+
+    return match data
+        .approval_manager
+        .review(
+            id,
+            &reviewer,
+            ApprovalStatus::Rejected,
+            form.comment.clone(),
+        )
+        .await
+    {
+        Ok(record) => {
+            activity::record(
+                &data,
+                &req,
+                "config",
+                "reject",
+                &config_resource(
+                    &record.change.tenant,
+                    &record.change.group,
+                    &record.change.data_id,
+                ),
+            );
+
+            tracing::info!(
+                "config change {} submitted by {} rejected by {}",
+                id,
+                record.submitter,
+                reviewer
+            );
+
+            HttpResponse::Ok().json(RestResult::<ApprovalRecord>::success(record))
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/approvals",
+    operation_id = "approval_get_namespaces",
+    tag = "approval",
+    responses((status = 200, description = "Namespaces whose publishes need approval", body = RestResult<Vec<String>>))
+)]
+#[get("/namespaces")]
+pub async fn get_namespaces(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(RestResult::<Vec<String>>::success(
+        data.approval_manager.protected_namespaces(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/approvals",
+    operation_id = "approval_set_namespaces",
+    tag = "approval",
+    request_body(content = NamespacesFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Stored and applied on every member, an empty list brings back the configured namespaces", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[put("/namespaces")]
+pub async fn set_namespaces(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<NamespacesFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    if form.local.unwrap_or_default() {
+        return match data.approval_manager.load().await {
+            Ok(()) => HttpResponse::Ok().json(RestResult::<bool>::success(true)),
+            Err(err) => RestResult::<String>::http_error(&err),
+        };
+    }
+
+    let namespaces = split_list(&form.namespaces.clone().unwrap_or_default());
+    let operator = guard::current_username(&req).unwrap_or_default();
+
+    if let Err(err) = data
+        .approval_manager
+        .set_protected_namespaces(namespaces.clone(), &operator)
+        .await
+    {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    tracing::info!("config approval required for namespaces {:?}", namespaces);
+
+    activity::record(&data, &req, "approval", "namespaces", &namespaces.join(","));
+
+    let failures = peer::propagate(
+        &data,
+        &req,
+        "PUT",
+        "/v1/console/approvals/namespaces",
+        move |request| request.send_form(&[("local", "true")]).map_err(Box::new),
+    )
+    .await;
+
+    peer::propagated(&failures, true)
+}
+
+pub fn routers() -> Scope {
+    web::scope("/approvals")
+        .service(get_namespaces)
+        .service(set_namespaces)
+        .service(list)
+        .service(approve)
+        .service(reject)
+}
@@ -1,16 +1,17 @@
 use actix_web::{post, web, HttpResponse, Responder, Scope};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
     console::v1,
     model::{
-        auth::{NacosUser, DEFAULT_TOKEN_EXPIRE_SECONDS, GLOBAL_ADMIN_ROLE},
-        common::AppState,
+        auth::{NacosUser, DEFAULT_TOKEN_EXPIRE_SECONDS, DEFAULT_USER, GLOBAL_ADMIN_ROLE},
+        common::{AppState, RestResult},
     },
-    {service, service::auth::encode_jwt_token},
+    {service, service::auth::encode_jwt_token, service::ldap::LdapAuthProvider},
 };
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct LoginResult {
     access_token: String,
@@ -19,12 +20,22 @@ struct LoginResult {
     username: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct LoginFormData {
     username: String,
     password: String,
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "auth_users_login",
+    tag = "auth",
+    request_body(content = LoginFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Access token of the user", body = LoginResult),
+        (status = 403, description = "Wrong username or password", body = String, content_type = "application/json")
+    )
+)]
 #[post("/users/login")]
 pub async fn users_login(
     data: web::Data<AppState>,
@@ -33,56 +44,110 @@ pub async fn users_login(
     let user_option =
         service::user::find_by_username(&data.database_connection, &form.username).await;
 
-    if user_option.is_none() {
-        return HttpResponse::Forbidden().json("user not found!");
+    if let Some(user) = user_option {
+        if bcrypt::verify(&form.password, &user.password).unwrap_or(false) {
+            return login_success(&data, &user.username, &user.password).await;
+        }
     }
 
-    let token_secret_key = data.token_secret_key.as_str();
-
-    let user = user_option.unwrap();
-    let bcrypt_result = bcrypt::verify(&form.password, &user.password).unwrap();
-
-    if bcrypt_result {
-        let token_expire_seconds = data
-            .app_config
-            .get_int("nacos.core.auth.plugin.nacos.token.expire.seconds")
-            .unwrap_or(DEFAULT_TOKEN_EXPIRE_SECONDS);
-
-        let access_token = encode_jwt_token(
-            &NacosUser {
-                username: user.username.clone(),
-                password: user.password.clone(),
-                token: "".to_string(),
-                global_admin: false,
-            },
-            token_secret_key,
-            token_expire_seconds,
-        )
-        .unwrap();
-
-        let global_admin =
-            service::role::find_by_username(&data.database_connection, &user.username)
-                .await
-                .ok()
-                .unwrap()
-                .iter()
-                .any(|role| role.role == GLOBAL_ADMIN_ROLE);
-
-        let login_result = LoginResult {
-            access_token: access_token.clone(),
-            token_ttl: token_expire_seconds,
-            global_admin: global_admin,
-            username: user.username,
-        };
-
-        return HttpResponse::Ok()
-            .append_header(("Authorization", format!("Bearer {}", access_token)))
-            .json(login_result);
+    // the default admin always logs in locally, so a broken directory can't lock everyone out
+    if let Some(ldap) = data
+        .ldap_auth_provider
+        .clone()
+        .filter(|_| form.username != DEFAULT_USER)
+    {
+        return ldap_login(&data, &ldap, &form).await;
     }
 
     return HttpResponse::Forbidden().json("user not found!");
 }
 
+// Directory users get a local account with an unusable password on first login, so the
+// existing roles and permissions apply to them
+async fn ldap_login(
+    data: &web::Data<AppState>,
+    ldap: &LdapAuthProvider,
+    form: &LoginFormData,
+) -> HttpResponse {
+    let groups = match ldap.authenticate(&form.username, &form.password).await {
+        Ok(Some(groups)) => groups,
+        Ok(None) => return HttpResponse::Forbidden().json("user not found!"),
+        Err(err) => {
+            tracing::error!("ldap authentication of {} failed: {}", form.username, err);
+
+            return HttpResponse::Forbidden().json("user not found!");
+        }
+    };
+    let username = ldap.local_username(&form.username);
+
+    let password = match service::user::find_by_username(&data.database_connection, &username).await
+    {
+        Some(user) => user.password,
+        None => {
+            let password = bcrypt::hash(uuid::Uuid::new_v4().to_string(), 10u32)
+                .ok()
+                .unwrap();
+
+            if let Err(err) =
+                service::user::create(&data.database_connection, &username, &password).await
+            {
+                return RestResult::<String>::http_error(&err);
+            }
+
+            tracing::info!("local account created for ldap user {}", username);
+
+            password
+        }
+    };
+
+    if let Err(err) = ldap
+        .sync_roles(&data.database_connection, &username, &groups)
+        .await
+    {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    login_success(data, &username, &password).await
+}
+
+async fn login_success(data: &web::Data<AppState>, username: &str, password: &str) -> HttpResponse {
+    let token_secret_key = data.auth_manager.secret_key();
+    let token_expire_seconds = data
+        .app_config
+        .get_int("nacos.core.auth.plugin.nacos.token.expire.seconds")
+        .unwrap_or(DEFAULT_TOKEN_EXPIRE_SECONDS);
+
+    let access_token = encode_jwt_token(
+        &NacosUser {
+            username: username.to_string(),
+            password: password.to_string(),
+            token: "".to_string(),
+            global_admin: false,
+        },
+        &token_secret_key,
+        token_expire_seconds,
+    )
+    .unwrap();
+
+    let global_admin = service::role::find_by_username(&data.database_connection, username)
+        .await
+        .ok()
+        .unwrap()
+        .iter()
+        .any(|role| role.role == GLOBAL_ADMIN_ROLE);
+
+    let login_result = LoginResult {
+        access_token: access_token.clone(),
+        token_ttl: token_expire_seconds,
+        global_admin: global_admin,
+        username: username.to_string(),
+    };
+
+    return HttpResponse::Ok()
+        .append_header(("Authorization", format!("Bearer {}", access_token)))
+        .json(login_result);
+}
+
 pub fn routers() -> Scope {
     return web::scope("/auth")
         .service(users_login)
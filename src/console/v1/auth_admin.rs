@@ -0,0 +1,208 @@
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    console::v1::{guard, peer},
+    model::{
+        auth::{ApiType, DEFAULT_TOKEN_EXPIRE_SECONDS},
+        common::{AppState, RestResult},
+    },
+    service::{self, auth::AuthSwitchState},
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct SwitchParam {
+    api_type: Option<String>,
+    enabled: bool,
+    // set when a member passes the change on, so it is not passed on again
+    local: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct RotateFormData {
+    secret_key: Option<String>,
+    grace_seconds: Option<i64>,
+    local: Option<bool>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/auth",
+    operation_id = "auth_admin_state",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Auth switches and secret rotation state", body = RestResult<AuthSwitchState>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn state(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<AuthSwitchState>::success(
+        data.auth_manager.state(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/auth",
+    operation_id = "auth_admin_switch",
+    tag = "auth",
+    params(SwitchParam),
+    responses(
+        (status = 200, body = RestResult<bool>),
+        (status = 400, description = "Unknown api type", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[put("/switch")]
+pub async fn switch(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<SwitchParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let api_types: Vec<ApiType> = match &params.api_type {
+        Some(name) => match ApiType::from_name(name) {
+            Some(api_type) => vec![api_type],
+            None => {
+                return HttpResponse::BadRequest().json(RestResult::<bool> {
+                    code: 400,
+                    message: format!("unknown api type: {}", name),
+                    data: false,
+                })
+            }
+        },
+        None => ApiType::ALL.to_vec(),
+    };
+
+    api_types.iter().for_each(|api_type| {
+        tracing::info!("auth of {:?} switched to {}", api_type, params.enabled);
+
+        data.auth_manager.set_enabled(*api_type, params.enabled)
+    });
+
+    if params.local.unwrap_or_default() {
+        return HttpResponse::Ok().json(RestResult::<bool>::success(true));
+    }
+
+    let api_type = params.api_type.clone().unwrap_or_default();
+    let enabled = params.enabled.to_string();
+
+    let failures = peer::propagate(&data, &req, "PUT", "/v1/core/auth/switch", move |request| {
+        request
+            .query("apiType", &api_type)
+            .query("enabled", &enabled)
+            .query("local", "true")
+            .call()
+            .map_err(Box::new)
+    })
+    .await;
+
+    peer::propagated(&failures, failures.is_empty())
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/auth",
+    operation_id = "auth_admin_rotate_secret",
+    tag = "auth",
+    request_body(content = RotateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, body = RestResult<bool>),
+        (status = 400, description = "The secret key is invalid, or other members are only reached without tls", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[post("/secret/rotate")]
+pub async fn rotate_secret(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<RotateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    // the key would travel to the other members in clear text
+    if !form.local.unwrap_or_default()
+        && !service::tls::is_enabled(&data.app_config)
+        && !data.member_manager.peer_urls(&data.context_path).is_empty()
+    {
+        return HttpResponse::BadRequest().json(RestResult::<bool> {
+            code: 400,
+            message: String::from(
+                "the secret key is only sent to other members over tls, enable tls or rotate on \
+                 each member with the same secretKey and local=true",
+            ),
+            data: false,
+        });
+    }
+
+    let secret_key = form
+        .secret_key
+        .clone()
+        .filter(|e| !e.is_empty())
+        .unwrap_or_else(service::auth::generate_secret_key);
+    let grace_seconds = form.grace_seconds.unwrap_or(
+        data.app_config
+            .get_int("nacos.core.auth.plugin.nacos.token.expire.seconds")
+            .unwrap_or(DEFAULT_TOKEN_EXPIRE_SECONDS),
+    );
+
+    if let Err(err) = data
+        .auth_manager
+        .rotate_secret_key(&secret_key, grace_seconds)
+    {
+        return HttpResponse::BadRequest().json(RestResult::<bool> {
+            code: 400,
+            message: err.to_string(),
+            data: false,
+        });
+    }
+
+    tracing::info!(
+        "token secret key rotated, previous key accepted for {}s",
+        grace_seconds
+    );
+
+    if form.local.unwrap_or_default() {
+        return HttpResponse::Ok().json(RestResult::<bool>::success(true));
+    }
+
+    // a generated key is sent along, so every member ends up with the same one
+    let grace_seconds = grace_seconds.to_string();
+
+    let failures = peer::propagate(
+        &data,
+        &req,
+        "POST",
+        "/v1/core/auth/secret/rotate",
+        move |request| {
+            request
+                .send_form(&[
+                    ("secretKey", &secret_key),
+                    ("graceSeconds", &grace_seconds),
+                    ("local", "true"),
+                ])
+                .map_err(Box::new)
+        },
+    )
+    .await;
+
+    peer::propagated(&failures, failures.is_empty())
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/auth")
+        .service(state)
+        .service(switch)
+        .service(rotate_secret)
+}
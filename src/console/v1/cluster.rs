@@ -0,0 +1,152 @@
+use actix_web::{get, put, web, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    model::{
+        cluster::Member,
+        common::{AppState, Page, RestResult},
+    },
+    service::{
+        cluster::LookupHealth,
+        cluster_event::{ClusterEvent, ClusterEventType},
+        member_lookup::LookupType,
+    },
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct NodesParam {
+    keyword: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct LookupParam {
+    r#type: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct EventsParam {
+    r#type: Option<ClusterEventType>,
+    member: Option<String>,
+    page_no: Option<u64>,
+    page_size: Option<u64>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/cluster",
+    operation_id = "cluster_get_self",
+    tag = "cluster",
+    responses((status = 200, description = "The local member", body = RestResult<Member>))
+)]
+#[get("/nodes/self")]
+pub async fn get_self(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(RestResult::<Member>::success(
+        data.member_manager.get_self(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/cluster",
+    operation_id = "cluster_list_nodes",
+    tag = "cluster",
+    params(NodesParam),
+    responses((status = 200, description = "Members whose address contains the keyword", body = RestResult<Vec<Member>>))
+)]
+#[get("/nodes")]
+pub async fn list_nodes(
+    data: web::Data<AppState>,
+    params: web::Query<NodesParam>,
+) -> impl Responder {
+    let keyword = params.keyword.clone().unwrap_or_default();
+    let members: Vec<Member> = data
+        .member_manager
+        .all_members()
+        .into_iter()
+        .filter(|member| keyword.is_empty() || member.address.contains(&keyword))
+        .collect();
+
+    HttpResponse::Ok().json(RestResult::<Vec<Member>>::success(members))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/cluster",
+    operation_id = "cluster_get_lookup",
+    tag = "cluster",
+    responses((status = 200, description = "Health of the current member lookup", body = RestResult<LookupHealth>))
+)]
+#[get("/lookup")]
+pub async fn get_lookup(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(RestResult::<LookupHealth>::success(
+        data.member_manager.lookup_health(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/cluster",
+    operation_id = "cluster_switch_lookup",
+    tag = "cluster",
+    params(LookupParam),
+    responses(
+        (status = 200, body = RestResult<bool>),
+        (status = 400, description = "Unknown lookup type", body = RestResult<bool>)
+    )
+)]
+#[put("/lookup")]
+pub async fn switch_lookup(
+    data: web::Data<AppState>,
+    params: web::Query<LookupParam>,
+) -> impl Responder {
+    let lookup_type = match LookupType::from_name(&params.r#type) {
+        Some(lookup_type) => lookup_type,
+        None => {
+            return HttpResponse::BadRequest().json(RestResult::<bool> {
+                code: 400,
+                message: format!("unknown member lookup type: {}", params.r#type),
+                data: false,
+            })
+        }
+    };
+
+    return match data.member_manager.switch_lookup(lookup_type).await {
+        Ok(()) => HttpResponse::Ok().json(RestResult::<bool>::success(true)),
+        Err(err) => HttpResponse::InternalServerError().json(RestResult::<bool> {
+            code: 500,
+            message: err.to_string(),
+            data: false,
+        }),
+    };
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/cluster",
+    operation_id = "cluster_events",
+    tag = "cluster",
+    params(EventsParam),
+    responses((status = 200, description = "Cluster events seen by this node, newest first", body = RestResult<Page<ClusterEvent>>))
+)]
+#[get("/events")]
+pub async fn events(data: web::Data<AppState>, params: web::Query<EventsParam>) -> impl Responder {
+    let page = data.member_manager.events().search(
+        params.r#type,
+        params.member.as_deref().unwrap_or_default(),
+        params.page_no.unwrap_or(1).max(1),
+        params.page_size.unwrap_or(20).max(1),
+    );
+
+    HttpResponse::Ok().json(RestResult::<Page<ClusterEvent>>::success(page))
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/cluster")
+        .service(get_self)
+        .service(list_nodes)
+        .service(get_lookup)
+        .service(switch_lookup)
+        .service(events)
+}
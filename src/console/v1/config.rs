@@ -1,19 +1,40 @@
-use actix_web::{get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder, Scope};
+use actix_web::{
+    body, get,
+    http::{header::ContentType, StatusCode},
+    post, web, HttpRequest, HttpResponse, Responder, Scope,
+};
+use futures_util::stream;
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use chrono::Utc;
 
 use crate::{
+    console::v1::{
+        activity::{self, config_resource},
+        guard,
+    },
     model::{
-        auth::NacosJwtPayload,
-        common::{AppState, ErrorResult, Page},
-        config::ConfigInfo,
+        common::{AppState, BusinessError, ErrorResult, Page, RestResult},
+        config::{
+            ConfigChange, ConfigInfo, ConfigSortBy, PublishOperation, PublishReport,
+            SchemaViolation,
+        },
+        validation,
+    },
+    service::{
+        self,
+        approval::ApprovalRecord,
+        event::{ConfigSubscription, ServerEvent},
+        idempotency::{
+            IdempotentResult, IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER, REQUEST_ID_HEADER,
+        },
     },
-    service,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchPageParam {
     search: Option<String>,
     show: Option<String>,
@@ -25,12 +46,35 @@ struct SearchPageParam {
     types: Option<String>,
     #[serde(rename = "config_detail")]
     config_detail: Option<String>,
+    sort_by: Option<String>,
+    order: Option<String>,
     tenant: Option<String>,
     page_no: Option<u64>,
     page_size: Option<u64>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct WatchParam {
+    tenant: Option<String>,
+    /// Group pattern where * matches any characters, all groups when empty
+    group: Option<String>,
+    /// DataId pattern where * matches any characters, all dataIds when empty
+    data_id: Option<String>,
+}
+
+impl From<WatchParam> for ConfigSubscription {
+    fn from(value: WatchParam) -> Self {
+        ConfigSubscription {
+            tenant: value.tenant.unwrap_or_default(),
+            group: value.group.unwrap_or_default(),
+            data_id: value.data_id.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct CreateFormParam {
     data_id: String,
@@ -48,19 +92,62 @@ struct CreateFormParam {
     r#type: Option<String>,
     schema: Option<String>,
     encrypted_data_key: Option<String>,
+    dry_run: Option<bool>,
 }
 
+#[utoipa::path(
+    context_path = "/v1/cs/configs",
+    operation_id = "config_search",
+    tag = "config",
+    params(SearchPageParam),
+    responses(
+        (status = 200, description = "Configs matching search=blur, or the full config with show=all. Sensitive values are masked unless the user can unmask the namespace", body = Page<ConfigInfo>),
+        (status = 400, description = "Unknown sortBy, one of id, dataId, group, appName and modifiedTime", body = RestResult<String>),
+        (status = 403, description = "config_detail search without the unmask permission", body = RestResult<String>),
+        (status = 404, description = "Config not found with show=all", body = RestResult<String>)
+    )
+)]
 #[get("")]
 pub async fn search(
-    req: HttpRequest,
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Query<SearchPageParam>,
 ) -> impl Responder {
+    let unmask = guard::can_unmask(&data, &req, &params.tenant.clone().unwrap_or_default()).await;
+
     if params.search.is_some() && params.search.as_ref().unwrap() == "blur" {
+        // a content search tells the masked values apart
+        if !unmask
+            && params
+                .config_detail
+                .as_deref()
+                .is_some_and(|e| !e.is_empty())
+        {
+            return RestResult::<String>::http_error(
+                &BusinessError::AccessDenied(String::from(
+                    "config_detail search needs the unmask permission on the namespace",
+                ))
+                .into(),
+            );
+        }
+
         let search_param = params.0;
+        let sort_by = match search_param.sort_by.as_deref() {
+            None => ConfigSortBy::default(),
+            Some(name) => match ConfigSortBy::from_name(name) {
+                Some(sort_by) => sort_by,
+                None => {
+                    return RestResult::<String>::http_error(
+                        &BusinessError::ParameterValidate(format!("unknown sortBy: {}", name))
+                            .into(),
+                    )
+                }
+            },
+        };
 
         let result = crate::service::config::search_page(
             &data.database_connection,
+            &data.content_cipher,
             search_param.page_no.unwrap_or_default(),
             search_param.page_size.unwrap_or_default(),
             search_param.tenant.unwrap_or_default().as_str(),
@@ -70,47 +157,133 @@ pub async fn search(
             search_param.config_tags.unwrap_or_default().as_str(),
             search_param.types.clone().unwrap_or_default().as_str(),
             search_param.config_detail.unwrap_or_default().as_str(),
+            sort_by,
+            search_param.order.as_deref() == Some("desc"),
         )
         .await;
 
         return match result {
-            Ok(page_result) => HttpResponse::Ok().json(page_result),
-            Err(err) => HttpResponse::InternalServerError().json(ErrorResult {
-                timestamp: Utc::now().to_rfc3339(),
-                status: 403,
-                message: err.to_string(),
-                error: String::from("Forbiden"),
-                path: req.path().to_string(),
-            }),
+            Ok(mut page_result) => {
+                if !unmask {
+                    page_result
+                        .page_items
+                        .iter_mut()
+                        .for_each(|e| e.content = data.mask_manager.mask(&e.content));
+                }
+
+                HttpResponse::Ok().json(page_result)
+            }
+            Err(err) => RestResult::<String>::http_error(&err),
         };
     } else if params.show.is_some() && params.show.as_ref().unwrap() == "all" {
-        let config_all_info = service::config::find_all(
+        let result = service::config::find_all(
             &data.database_connection,
+            &data.content_cipher,
             params.data_id.clone().unwrap_or_default().as_str(),
             params.group.clone().unwrap_or_default().as_str(),
             params.tenant.clone().unwrap_or_default().as_str(),
         )
-        .await
-        .ok();
+        .await;
 
-        return HttpResponse::Ok().json(config_all_info);
+        return match result {
+            Ok(mut config_all_info) => {
+                if !unmask {
+                    config_all_info.content = data.mask_manager.mask(&config_all_info.content);
+                }
+
+                HttpResponse::Ok().json(config_all_info)
+            }
+            Err(err) => RestResult::<String>::http_error(&err),
+        };
     }
 
     return HttpResponse::Ok().json(Page::<ConfigInfo>::default());
 }
 
+#[utoipa::path(
+    context_path = "/v1/cs/configs",
+    operation_id = "config_create_or_update",
+    tag = "config",
+    request_body(content = CreateFormParam, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Config published. A repeated Idempotency-Key or X-Request-Id header within the window replays the original result. With dryRun=true nothing is written and a RestResult of PublishReport describes what the publish would do", body = bool, content_type = "application/json"),
+        (status = 400, description = "A parameter is missing or invalid, or the content breaks a schema bound to the dataId in which case data is a list of SchemaViolation", body = RestResult<String>),
+        (status = 202, description = "The namespace is protected, the publish waits for approval", body = RestResult<ApprovalRecord>),
+        (status = 423, description = "The namespace or group is write locked", body = RestResult<String>),
+        (status = 429, description = "Write quota exhausted. A RestResult body instead means the content exceeds the max size or a new config exceeds the config quota of the namespace or group", body = ErrorResult)
+    )
+)]
 #[post("")]
 pub async fn create_or_update(
     data: web::Data<AppState>,
     req: HttpRequest,
     form: web::Form<CreateFormParam>,
 ) -> impl Responder {
-    let token_data = req
-        .extensions_mut()
-        .get::<NacosJwtPayload>()
-        .unwrap()
-        .clone();
-    let src_user = form.src_user.clone().unwrap_or(token_data.sub.clone());
+    if form.dry_run.unwrap_or_default() {
+        return dry_run(&data, &req, &form).await;
+    }
+
+    let key = match req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .or_else(|| req.headers().get(REQUEST_ID_HEADER))
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+    {
+        Some(key) => format!(
+            "{}:{}",
+            guard::current_username(&req).unwrap_or_default(),
+            key
+        ),
+        None => return publish(&data, &req, &form).await,
+    };
+
+    match data.idempotency_manager.begin(&key) {
+        Ok(Some(result)) => {
+            return HttpResponse::build(
+                StatusCode::from_u16(result.status).unwrap_or(StatusCode::OK),
+            )
+            .insert_header((REPLAYED_HEADER, "true"))
+            .content_type(ContentType::json())
+            .body(result.body)
+        }
+        Ok(None) => {}
+        Err(err) => return RestResult::<String>::http_error(&err),
+    }
+
+    let response = publish(&data, &req, &form).await;
+    let status = response.status();
+
+    if !status.is_success() {
+        data.idempotency_manager.abort(&key);
+
+        return response;
+    }
+
+    return match body::to_bytes(response.into_body()).await {
+        Ok(body) => {
+            data.idempotency_manager.complete(
+                &key,
+                IdempotentResult {
+                    status: status.as_u16(),
+                    body: body.clone(),
+                },
+            );
+
+            HttpResponse::build(status)
+                .content_type(ContentType::json())
+                .body(body)
+        }
+        Err(_) => {
+            data.idempotency_manager.abort(&key);
+
+            HttpResponse::InternalServerError().finish()
+        }
+    };
+}
+
+fn to_change(req: &HttpRequest, form: &CreateFormParam, token_user: &str) -> ConfigChange {
+    let src_user = form.src_user.clone().unwrap_or(token_user.to_string());
     let config_type = form.r#type.clone().unwrap_or(String::from("text"));
     let src_ip = String::from(
         req.connection_info()
@@ -118,31 +291,238 @@ pub async fn create_or_update(
             .unwrap_or_default(),
     );
 
-    let _ = service::config::create_or_update(
+    ConfigChange {
+        data_id: form.data_id.clone(),
+        group: form.group.clone(),
+        tenant: form.tenant.clone().unwrap_or_default(),
+        content: form.content.clone(),
+        tag: form.tag.clone().unwrap_or_default(),
+        app_name: form.app_name.clone().unwrap_or_default(),
+        src_user,
+        src_ip,
+        config_tags: form.config_tags.clone().unwrap_or_default(),
+        desc: form.desc.clone().unwrap_or_default(),
+        r#use: form.r#use.clone().unwrap_or_default(),
+        effect: form.effect.clone().unwrap_or_default(),
+        r#type: config_type,
+        schema: form.schema.clone().unwrap_or_default(),
+        encrypted_data_key: form.encrypted_data_key.clone().unwrap_or_default(),
+        op_type: String::new(),
+    }
+}
+
+// Run the checks of a publish and report what it would do, nothing is written and no quota is used
+async fn dry_run(
+    data: &web::Data<AppState>,
+    req: &HttpRequest,
+    form: &CreateFormParam,
+) -> HttpResponse {
+    let token_user = guard::current_username(req).unwrap_or_default();
+    let change = to_change(req, form, &token_user);
+    let md5 = service::config::md5_digest(&change.content);
+    let mut violations = Vec::new();
+
+    if let Err(err) = validation::check_config_change(&change) {
+        violations.push(err.to_string());
+    }
+
+    match service::config_schema::validate(&data.database_connection, &data.content_cipher, &change)
+        .await
+    {
+        Ok(schema_violations) => {
+            violations.extend(schema_violations.iter().map(|e| e.to_string()));
+        }
+        Err(err) => return RestResult::<String>::http_error(&err),
+    }
+
+    if let Err(err) = data.write_lock_manager.check(&change.tenant, &change.group) {
+        violations.push(err.to_string());
+    }
+
+    let quota_retry_after = data
+        .write_quota_manager
+        .check(&change.tenant, &token_user)
+        .err();
+    let operation = match service::config::find_md5(
         &data.database_connection,
-        form.data_id.as_str(),
-        form.group.as_str(),
-        form.tenant.clone().unwrap_or_default().as_str(),
-        form.content.as_str(),
-        form.tag.clone().unwrap_or_default().as_str(),
-        form.app_name.clone().unwrap_or_default().as_str(),
-        src_user.as_str(),
-        src_ip.as_str(),
-        form.config_tags.clone().unwrap_or_default().as_str(),
-        form.desc.clone().unwrap_or_default().as_str(),
-        form.r#use.clone().unwrap_or_default().as_str(),
-        form.effect.clone().unwrap_or_default().as_str(),
-        config_type.as_str(),
-        form.schema.clone().unwrap_or_default().as_str(),
-        form.encrypted_data_key.clone().unwrap_or_default().as_str(),
+        &change.data_id,
+        &change.group,
+        &change.tenant,
+    )
+    .await
+    {
+        Ok(Some(current_md5)) if current_md5 == md5 => PublishOperation::Unchanged,
+        Ok(Some(_)) => PublishOperation::Update,
+        Ok(None) => PublishOperation::Create,
+        Err(err) => return RestResult::<String>::http_error(&err),
+    };
+
+    if let Err(err) = data
+        .capacity_policy
+        .check(
+            &data.database_connection,
+            &change,
+            operation == PublishOperation::Create,
+        )
+        .await
+    {
+        if err.downcast_ref::<BusinessError>().is_none() {
+            return RestResult::<String>::http_error(&err);
+        }
+
+        violations.push(err.to_string());
+    }
+
+    let requires_approval = data.approval_manager.is_protected(&change.tenant);
+
+    HttpResponse::Ok().json(RestResult::<PublishReport>::success(PublishReport {
+        would_publish: violations.is_empty() && quota_retry_after.is_none() && !requires_approval,
+        data_id: change.data_id,
+        group: change.group,
+        tenant: change.tenant,
+        md5,
+        operation,
+        violations,
+        quota_retry_after,
+        requires_approval,
+    }))
+}
+
+async fn publish(
+    data: &web::Data<AppState>,
+    req: &HttpRequest,
+    form: &CreateFormParam,
+) -> HttpResponse {
+    let token_user = guard::current_username(req).unwrap_or_default();
+    let change = to_change(req, form, &token_user);
+
+    if let Err(err) = validation::check_config_change(&change) {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    match service::config_schema::validate(&data.database_connection, &data.content_cipher, &change)
+        .await
+    {
+        Ok(violations) if !violations.is_empty() => {
+            return HttpResponse::BadRequest().json(RestResult::<Vec<SchemaViolation>> {
+                code: 400,
+                message: String::from("config content does not match its schema"),
+                data: violations,
+            });
+        }
+        Ok(_) => {}
+        Err(err) => return RestResult::<String>::http_error(&err),
+    }
+
+    if let Err(err) = data.write_lock_manager.check(&change.tenant, &change.group) {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    if data.approval_manager.is_protected(&change.tenant) {
+        let record = match data.approval_manager.submit(change, &token_user).await {
+            Ok(record) => record,
+            Err(err) => return RestResult::<String>::http_error(&err),
+        };
+
+        tracing::info!(
+            "config publish {} submitted for approval by {} from {}",
+            record.id,
+            record.submitter,
+            guard::client_locality(req).map_or(String::from("-"), |e| e.to_string())
+        );
+
+        activity::record(
+            data,
+            req,
+            "config",
+            "submit",
+            &config_resource(
+                &record.change.tenant,
+                &record.change.group,
+                &record.change.data_id,
+            ),
+        );
+
+        return HttpResponse::Accepted().json(RestResult::<ApprovalRecord>::success(record));
+    }
+
+    // only a write that is going to happen uses up quota
+    if let Err(retry_after) = data
+        .write_quota_manager
+        .try_acquire(&change.tenant, &token_user)
+    {
+        return HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.to_string()))
+            .json(ErrorResult {
+                timestamp: Utc::now().to_rfc3339(),
+                status: 429,
+                message: format!("write quota exceeded, retry after {}s", retry_after),
+                error: String::from("Too Many Requests"),
+                path: req.path().to_string(),
+            });
+    }
+
+    let result = service::config::publish(
+        &data.database_connection,
+        &data.content_cipher,
+        &data.capacity_policy,
+        &change,
     )
     .await;
 
-    return HttpResponse::Ok().json(true);
+    return match result {
+        Ok(_) => {
+            data.event_bus.publish(ServerEvent::config_changed(&change));
+
+            activity::record(
+                data,
+                req,
+                "config",
+                "publish",
+                &config_resource(&change.tenant, &change.group, &change.data_id),
+            );
+
+            HttpResponse::Ok().json(true)
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
+}
+
+#[utoipa::path(
+    context_path = "/v1/cs/configs",
+    operation_id = "config_watch",
+    tag = "config",
+    params(WatchParam),
+    responses(
+        (status = 200, description = "Streams one JSON line per config change of the namespace published on this node, e.g. {\"type\":\"CONFIG_CHANGED\",\"dataId\":..,\"group\":..,\"tenant\":..,\"md5\":..}, until the client disconnects", body = String, content_type = "application/x-ndjson")
+    )
+)]
+#[get("/watch")]
+pub async fn watch(data: web::Data<AppState>, params: web::Query<WatchParam>) -> impl Responder {
+    let watcher = data.watch_registry.watch();
+
+    // the first subscription of a watcher is always within the limit
+    let _ = watcher.subscribe(ConfigSubscription::from(params.into_inner()));
+
+    // slow clients lose events they lagged behind on rather than holding back publishers, the
+    // watcher is dropped with the stream when the client disconnects
+    let events = stream::unfold(watcher, |mut watcher| async move {
+        let event = watcher.recv().await?;
+        let mut line = serde_json::to_vec(&event).unwrap_or_default();
+
+        line.push(b'\n');
+
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(line)), watcher))
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(events)
 }
 
 pub fn routers() -> Scope {
     web::scope("/cs/configs")
         .service(search)
+        .service(watch)
         .service(create_or_update)
 }
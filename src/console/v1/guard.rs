@@ -0,0 +1,81 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+
+use crate::{
+    model::{
+        auth::{NacosJwtPayload, GLOBAL_ADMIN_ROLE},
+        common::{AppState, RestResult},
+    },
+    service::{self, locality::Locality},
+};
+
+pub fn current_username(req: &HttpRequest) -> Option<String> {
+    req.extensions()
+        .get::<NacosJwtPayload>()
+        .map(|token_data| token_data.sub.clone())
+}
+
+pub fn client_locality(req: &HttpRequest) -> Option<Locality> {
+    req.extensions().get::<Locality>().cloned()
+}
+
+pub async fn is_global_admin(data: &web::Data<AppState>, req: &HttpRequest) -> bool {
+    has_any_role(data, req, &[GLOBAL_ADMIN_ROLE]).await
+}
+
+// Every caller holds every role where auth is turned off, like the other checks of the server
+pub async fn has_any_role(data: &web::Data<AppState>, req: &HttpRequest, roles: &[&str]) -> bool {
+    let path = req
+        .path()
+        .strip_prefix(data.context_path.as_str())
+        .unwrap_or_default();
+
+    if !data.auth_manager.is_required(path) {
+        return true;
+    }
+
+    let username = match current_username(req) {
+        Some(username) => username,
+        None => return false,
+    };
+
+    service::role::find_by_username(&data.database_connection, &username)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .any(|role| roles.contains(&role.role.as_str()))
+}
+
+// Whether config content of the namespace can be shown to the user without masking
+pub async fn can_unmask(data: &web::Data<AppState>, req: &HttpRequest, namespace: &str) -> bool {
+    if !data.mask_manager.is_enabled() {
+        return true;
+    }
+
+    let username = match current_username(req) {
+        Some(username) => username,
+        None => return false,
+    };
+
+    let roles: Vec<String> = service::role::find_by_username(&data.database_connection, &username)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|role| role.role)
+        .collect();
+
+    if roles.iter().any(|role| role == GLOBAL_ADMIN_ROLE) {
+        return true;
+    }
+
+    service::mask::can_unmask(&data.database_connection, &roles, namespace)
+        .await
+        .unwrap_or_default()
+}
+
+pub fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden().json(RestResult::<String> {
+        code: 403,
+        message: String::from("only global admin can perform this operation"),
+        data: String::from("only global admin can perform this operation"),
+    })
+}
@@ -1,12 +1,33 @@
 use actix_web::{get, web, HttpResponse, Responder, Scope};
 
+use crate::{model::common::AppState, service};
+
+#[utoipa::path(
+    context_path = "/v1/console/health",
+    operation_id = "health_liveness",
+    tag = "health",
+    responses((status = 200, body = String))
+)]
 #[get("/liveness")]
 pub async fn liveness() -> impl Responder {
     HttpResponse::Ok().body("OK")
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/health",
+    operation_id = "health_readiness",
+    tag = "health",
+    responses(
+        (status = 200, body = String),
+        (status = 500, description = "The node failed its self health check", body = String)
+    )
+)]
 #[get("/readiness")]
-pub async fn readiness() -> impl Responder {
+pub async fn readiness(data: web::Data<AppState>) -> impl Responder {
+    if !service::health::is_healthy(&data.member_manager) {
+        return HttpResponse::InternalServerError().body("server is not in readiness");
+    }
+
     HttpResponse::Ok().body("OK")
 }
 
@@ -1,10 +1,19 @@
-use actix_web::{get, web, HttpResponse, Responder, Scope};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
 use serde::Deserialize;
+use utoipa::IntoParams;
 
-use crate::{model::common::AppState, service};
+use crate::{
+    console::v1::guard,
+    model::{
+        common::{AppState, BusinessError, Page, RestResult},
+        config::{ConfigHistoryInfo, ConfigInfoWrapper},
+    },
+    service,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchParam {
     search: Option<String>,
     data_id: Option<String>,
@@ -16,17 +25,35 @@ struct SearchParam {
     page_size: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct GetDataIdsParam {
     tenant: String,
 }
 
+#[utoipa::path(
+    context_path = "/v1/cs/history",
+    operation_id = "history_search",
+    tag = "history",
+    params(SearchParam),
+    responses(
+        (status = 200, description = "History page with search=accurate, otherwise the history entry of nid", body = Page<ConfigHistoryInfo>),
+        (status = 400, description = "nid is missing", body = RestResult<String>)
+    )
+)]
 #[get("")]
-pub async fn search(data: web::Data<AppState>, params: web::Query<SearchParam>) -> impl Responder {
+pub async fn search(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<SearchParam>,
+) -> impl Responder {
     if params.search.is_some() && params.search.as_ref().unwrap() == "accurate" {
+        let unmask =
+            guard::can_unmask(&data, &req, &params.tenant.clone().unwrap_or_default()).await;
         let result = service::history::search_page(
             &data.database_connection,
+            &data.content_cipher,
             params.data_id.clone().unwrap_or_default().as_str(),
             params.group.clone().unwrap_or_default().as_str(),
             params.tenant.clone().unwrap_or_default().as_str(),
@@ -35,25 +62,74 @@ pub async fn search(data: web::Data<AppState>, params: web::Query<SearchParam>)
         )
         .await;
 
-        return HttpResponse::Ok().json(result.ok().unwrap());
-    } else {
-        let result =
-            service::history::get_by_id(&data.database_connection, params.nid.unwrap()).await;
+        return match result {
+            Ok(mut page_result) => {
+                if !unmask {
+                    page_result
+                        .page_items
+                        .iter_mut()
+                        .for_each(|e| e.content = data.mask_manager.mask(&e.content));
+                }
 
-        return HttpResponse::Ok().json(result.ok().unwrap());
+                HttpResponse::Ok().json(page_result)
+            }
+            Err(err) => RestResult::<String>::http_error(&err),
+        };
     }
+
+    let nid = match params.nid {
+        Some(nid) => nid,
+        None => {
+            return RestResult::<String>::http_error(
+                &BusinessError::ParameterMissing(String::from("nid")).into(),
+            )
+        }
+    };
+
+    return match service::history::get_by_id(&data.database_connection, &data.content_cipher, nid)
+        .await
+    {
+        Ok(Some(mut history)) => {
+            if !guard::can_unmask(&data, &req, &history.tenant).await {
+                history.content = data.mask_manager.mask(&history.content);
+            }
+
+            HttpResponse::Ok().json(Some(history))
+        }
+        Ok(None) => HttpResponse::Ok().json(None::<ConfigHistoryInfo>),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/cs/history/",
+    operation_id = "history_get_data_ids",
+    tag = "history",
+    params(GetDataIdsParam),
+    responses((status = 200, description = "Configs of the namespace", body = Vec<ConfigInfoWrapper>))
+)]
 #[get("configs")]
 pub async fn get_data_ids(
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Query<GetDataIdsParam>,
 ) -> impl Responder {
-    let config_infos =
+    let result =
         service::history::get_config_list_by_namespace(&data.database_connection, &params.tenant)
             .await;
 
-    return HttpResponse::Ok().json(config_infos.ok().unwrap());
+    return match result {
+        Ok(mut config_infos) => {
+            if !guard::can_unmask(&data, &req, &params.tenant).await {
+                config_infos
+                    .iter_mut()
+                    .for_each(|e| e.content = data.mask_manager.mask(&e.content));
+            }
+
+            HttpResponse::Ok().json(config_infos)
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
 pub fn routers() -> Scope {
@@ -0,0 +1,95 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
+use chrono::{Local, NaiveDateTime};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    console::v1::guard,
+    model::{
+        common::{AppState, RestResult},
+        config::{HistoryAuditPage, HistoryAuditQuery},
+    },
+    service,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct SearchParam {
+    tenant: Option<String>,
+    // epoch milliseconds, inclusive
+    start_time: Option<i64>,
+    // epoch milliseconds, exclusive
+    end_time: Option<i64>,
+    operator: Option<String>,
+    op_type: Option<String>,
+    cursor: Option<u64>,
+    page_size: Option<u64>,
+}
+
+// history times are stored as local date times
+fn to_local_time(timestamp_millis: Option<i64>) -> Option<NaiveDateTime> {
+    timestamp_millis
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|e| e.with_timezone(&Local).naive_local())
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/history",
+    operation_id = "history_audit_search",
+    tag = "history",
+    params(SearchParam),
+    responses(
+        (status = 200, description = "History of all configs of the namespace, newest first. Pass nextCursor as cursor for the next page. Sensitive values are masked unless the user can unmask the namespace", body = RestResult<HistoryAuditPage>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn search(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<SearchParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let query = HistoryAuditQuery {
+        tenant: params.tenant.clone().unwrap_or_default(),
+        start_time: to_local_time(params.start_time),
+        end_time: to_local_time(params.end_time),
+        src_user: params.operator.clone().unwrap_or_default(),
+        op_type: params.op_type.clone().unwrap_or_default(),
+        cursor: params.cursor,
+        page_size: params
+            .page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+    };
+
+    return match service::history::search_audit(
+        &data.database_connection,
+        &data.content_cipher,
+        &query,
+    )
+    .await
+    {
+        Ok(mut page) => {
+            if !guard::can_unmask(&data, &req, &query.tenant).await {
+                page.page_items
+                    .iter_mut()
+                    .for_each(|e| e.content = data.mask_manager.mask(&e.content));
+            }
+
+            HttpResponse::Ok().json(RestResult::<HistoryAuditPage>::success(page))
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/history").service(search)
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    console::v1::guard,
+    model::{
+        auth::ApiType,
+        common::{AppState, RestResult},
+    },
+    service::ip_filter::{self, IpRules},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = ip_filter::UpdateFormData)]
+struct UpdateFormData {
+    api_type: String,
+    allow: Option<String>,
+    deny: Option<String>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/ip-filter",
+    operation_id = "ip_filter_list",
+    tag = "ip-filter",
+    responses(
+        (status = 200, description = "Allow and deny lists per api type", body = RestResult<HashMap<String, IpRules>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<HashMap<ApiType, IpRules>>::success(
+        data.ip_filter_manager.rules(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/ip-filter",
+    operation_id = "ip_filter_update",
+    tag = "ip-filter",
+    request_body(content = UpdateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, body = RestResult<bool>),
+        (status = 400, description = "Unknown api type or invalid CIDR", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[put("")]
+pub async fn update(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<UpdateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let api_type = match ApiType::from_name(&form.api_type) {
+        Some(api_type) => api_type,
+        None => {
+            return HttpResponse::BadRequest().json(RestResult::<bool> {
+                code: 400,
+                message: format!("unknown api type: {}", form.api_type),
+                data: false,
+            })
+        }
+    };
+
+    let allow = ip_filter::split_list(&form.allow.clone().unwrap_or_default());
+    let deny = ip_filter::split_list(&form.deny.clone().unwrap_or_default());
+
+    match IpRules::new(&allow, &deny) {
+        Ok(rules) => {
+            tracing::info!("ip filter of {:?} updated: {:?}", api_type, rules);
+
+            data.ip_filter_manager.set_rules(api_type, rules);
+
+            HttpResponse::Ok().json(RestResult::<bool>::success(true))
+        }
+        Err(err) => HttpResponse::BadRequest().json(RestResult::<bool> {
+            code: 400,
+            message: err.to_string(),
+            data: false,
+        }),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/ip-filter").service(list).service(update)
+}
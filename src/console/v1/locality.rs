@@ -0,0 +1,77 @@
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    console::v1::guard,
+    model::common::{AppState, RestResult},
+    service::locality::{self, LocalityRule},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = locality::UpdateFormData)]
+struct UpdateFormData {
+    rules: Option<String>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/locality",
+    operation_id = "locality_list",
+    tag = "locality",
+    responses(
+        (status = 200, description = "CIDR to region / zone rules, most specific first", body = RestResult<Vec<LocalityRule>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<Vec<LocalityRule>>::success(
+        data.locality_manager.rules(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/locality",
+    operation_id = "locality_update",
+    tag = "locality",
+    request_body(content = UpdateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, body = RestResult<bool>),
+        (status = 400, description = "Invalid rule", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[put("")]
+pub async fn update(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<UpdateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    match locality::parse_rules(&form.rules.clone().unwrap_or_default()) {
+        Ok(rules) => {
+            tracing::info!("locality rules updated: {:?}", rules);
+
+            data.locality_manager.set_rules(rules);
+
+            HttpResponse::Ok().json(RestResult::<bool>::success(true))
+        }
+        Err(err) => HttpResponse::BadRequest().json(RestResult::<bool> {
+            code: 400,
+            message: err.to_string(),
+            data: false,
+        }),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/locality").service(list).service(update)
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    console::v1::{activity, guard},
+    model::common::{AppState, RestResult},
+    service::maintenance::MaintenanceStatus,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = maintenance::EnterFormData)]
+struct EnterFormData {
+    timeout_seconds: Option<u64>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/maintenance",
+    operation_id = "maintenance_status",
+    tag = "maintenance",
+    responses(
+        (status = 200, description = "Whether the node is in maintenance and safe to restart", body = RestResult<MaintenanceStatus>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn status(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<MaintenanceStatus>::success(
+        data.maintenance_manager.status(&data.member_manager),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/maintenance",
+    operation_id = "maintenance_enter",
+    tag = "maintenance",
+    request_body(content = EnterFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The node reports DOWN, fails readiness and refuses writes. Answers once in-flight requests finished or the timeout passed, safeToRestart tells which", body = RestResult<MaintenanceStatus>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[post("")]
+pub async fn enter(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<EnterFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let timeout = form.timeout_seconds.map_or(
+        data.maintenance_manager.drain_timeout(),
+        Duration::from_secs,
+    );
+
+    tracing::warn!(
+        "node entering maintenance by {}",
+        guard::current_username(&req).unwrap_or_default()
+    );
+
+    activity::record(
+        &data,
+        &req,
+        "maintenance",
+        "enter",
+        &data.member_manager.get_self().address,
+    );
+
+    let maintenance_status = data
+        .maintenance_manager
+        .enter(&data.member_manager, timeout)
+        .await;
+
+    HttpResponse::Ok().json(RestResult::<MaintenanceStatus>::success(maintenance_status))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/maintenance",
+    operation_id = "maintenance_leave",
+    tag = "maintenance",
+    responses(
+        (status = 200, description = "The node is back in the state it had before the maintenance, usually UP", body = RestResult<MaintenanceStatus>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[delete("")]
+pub async fn leave(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    tracing::info!(
+        "node leaving maintenance by {}",
+        guard::current_username(&req).unwrap_or_default()
+    );
+
+    activity::record(
+        &data,
+        &req,
+        "maintenance",
+        "leave",
+        &data.member_manager.get_self().address,
+    );
+
+    HttpResponse::Ok().json(RestResult::<MaintenanceStatus>::success(
+        data.maintenance_manager.leave(&data.member_manager),
+    ))
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/maintenance")
+        .service(status)
+        .service(enter)
+        .service(leave)
+}
@@ -1,76 +1,118 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder, Scope};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder, Scope};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
+    console::v1::activity,
     model::{
-        common::{AppState, RestResult},
-        naming::Namespace,
+        common::{AppState, BusinessError, RestResult},
+        naming::{Namespace, NamespaceDeleteReport},
+        validation,
     },
     service,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct GetParam {
     show: Option<String>,
     namespace_id: Option<String>,
     check_namespace_id_exist: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = namespace::CreateFormData)]
 struct CreateFormData {
     custom_namespace_id: Option<String>,
     namespace_name: String,
     namespace_desc: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = namespace::UpdateFormData)]
 struct UpdateFormData {
     namespace: String,
     namespace_show_name: String,
     namespace_desc: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct DeleteParam {
     namespace_id: String,
+    confirm_token: Option<String>,
 }
 
-const NAMESPACE_ID_MAX_LENGTH: usize = 128;
-
+#[utoipa::path(
+    context_path = "/v1/console/namespaces",
+    operation_id = "namespace_get_all",
+    tag = "namespace",
+    params(GetParam),
+    responses(
+        (status = 200, description = "All namespaces, a single namespace with show=all or whether it exists with checkNamespaceIdExist=true", body = RestResult<Vec<Namespace>>)
+    )
+)]
 #[get("")]
 pub async fn get_all(data: web::Data<AppState>, params: web::Query<GetParam>) -> impl Responder {
     if params.show.is_some() && params.show.as_ref().unwrap() == "all" {
-        let namespace = service::namespace::get_by_namespace_id(
+        let result = service::namespace::get_by_namespace_id(
             &data.database_connection,
-            params.namespace_id.as_ref().unwrap().to_string(),
+            params.namespace_id.clone().unwrap_or_default(),
         )
         .await;
 
-        return HttpResponse::Ok().json(namespace);
+        return match result {
+            Ok(namespace) => HttpResponse::Ok().json(namespace),
+            Err(err) => RestResult::<String>::http_error(&err),
+        };
     }
 
     if params.check_namespace_id_exist.is_some() && params.check_namespace_id_exist.unwrap() {
-        let count = service::namespace::get_count_by_tenant_id(
+        let namespace_id = match &params.namespace_id {
+            Some(namespace_id) => namespace_id.to_string(),
+            None => {
+                return RestResult::<String>::http_error(
+                    &BusinessError::ParameterMissing(String::from("namespaceId")).into(),
+                )
+            }
+        };
+
+        return match service::namespace::get_count_by_tenant_id(
             &data.database_connection,
-            params.namespace_id.as_ref().unwrap().to_string(),
+            namespace_id,
         )
-        .await;
-
-        return HttpResponse::Ok().json(count > 0);
+        .await
+        {
+            Ok(count) => HttpResponse::Ok().json(count > 0),
+            Err(err) => RestResult::<String>::http_error(&err),
+        };
     }
 
-    let namespaces: Vec<Namespace> = service::namespace::find_all(&data.database_connection).await;
-    let rest_result = RestResult::<Vec<Namespace>>::success(namespaces);
-
-    return HttpResponse::Ok().json(rest_result);
+    return match service::namespace::find_all(&data.database_connection).await {
+        Ok(namespaces) => {
+            HttpResponse::Ok().json(RestResult::<Vec<Namespace>>::success(namespaces))
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/namespaces",
+    operation_id = "namespace_create",
+    tag = "namespace",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Whether the namespace was created", body = bool, content_type = "application/json"))
+)]
 #[post("")]
-pub async fn create(data: web::Data<AppState>, form: web::Form<CreateFormData>) -> impl Responder {
+pub async fn create(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<CreateFormData>,
+) -> impl Responder {
     let namespace_id: String;
 
     if form.custom_namespace_id.is_some() && !form.custom_namespace_id.as_ref().unwrap().is_empty()
@@ -82,32 +124,25 @@ pub async fn create(data: web::Data<AppState>, form: web::Form<CreateFormData>)
             .trim()
             .to_string();
 
-        let regex = regex::Regex::new(r"^[\w-]+").unwrap();
-
-        if !regex.is_match(&namespace_id) {
+        if validation::check_namespace_id(&namespace_id).is_err() {
             return HttpResponse::Ok().json(false);
         }
 
-        if namespace_id.len() > NAMESPACE_ID_MAX_LENGTH {
-            return HttpResponse::Ok().json(false);
-        }
-
-        if service::namespace::get_count_by_tenant_id(
+        match service::namespace::get_count_by_tenant_id(
             &data.database_connection,
             namespace_id.clone(),
         )
         .await
-            > 0
         {
-            return HttpResponse::Ok().json(false);
+            Ok(0) => {}
+            Ok(_) => return HttpResponse::Ok().json(false),
+            Err(err) => return RestResult::<String>::http_error(&err),
         }
     } else {
         namespace_id = uuid::Uuid::new_v4().to_string();
     }
 
-    let regex = regex::Regex::new(r"^[^@#$%^&*]+$").unwrap();
-
-    if !regex.is_match(&form.namespace_name) {
+    if validation::check_namespace_name(&form.namespace_name).is_err() {
         return HttpResponse::Ok().json(false);
     }
 
@@ -121,20 +156,41 @@ pub async fn create(data: web::Data<AppState>, form: web::Form<CreateFormData>)
 
     let res = service::namespace::create(
         &data.database_connection,
-        namespace_id,
+        namespace_id.clone(),
         form.namespace_name.clone(),
         namespace_desc,
     )
     .await;
 
+    if res {
+        activity::record(&data, &req, "namespace", "create", &namespace_id);
+    }
+
     return HttpResponse::Ok().json(res);
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/namespaces",
+    operation_id = "namespace_update",
+    tag = "namespace",
+    request_body(content = UpdateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Whether the namespace was updated", body = bool, content_type = "application/json"),
+        (status = 423, description = "The namespace is write locked", body = RestResult<String>),
+        (status = 500, description = "Database error", body = RestResult<String>)
+    )
+)]
 #[put("")]
-pub async fn update(data: web::Data<AppState>, form: web::Form<UpdateFormData>) -> impl Responder {
-    let regex = regex::Regex::new(r"^[^@#$%^&*]+$").unwrap();
+pub async fn update(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<UpdateFormData>,
+) -> impl Responder {
+    if let Err(err) = data.write_lock_manager.check_namespace(&form.namespace) {
+        return RestResult::<String>::http_error(&err);
+    }
 
-    if !regex.is_match(&form.namespace_show_name) {
+    if validation::check_namespace_name(&form.namespace_show_name).is_err() {
         return HttpResponse::Ok().json(false);
     }
 
@@ -154,15 +210,90 @@ pub async fn update(data: web::Data<AppState>, form: web::Form<UpdateFormData>)
     )
     .await;
 
-    return HttpResponse::Ok().json(res);
+    return match res {
+        Ok(res) => {
+            if res {
+                activity::record(&data, &req, "namespace", "update", &form.namespace);
+            }
+
+            HttpResponse::Ok().json(res)
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/namespaces",
+    operation_id = "namespace_delete",
+    tag = "namespace",
+    params(DeleteParam),
+    responses(
+        (status = 200, description = "Without confirmToken, the cascade report and a token to confirm the deletion with. With confirmToken, true once the namespace, its configs and permissions were deleted", body = RestResult<NamespaceDeleteReport>),
+        (status = 400, description = "Default namespace, or the token is invalid or expired", body = RestResult<String>),
+        (status = 404, description = "Namespace not found", body = RestResult<String>),
+        (status = 423, description = "The namespace is write locked", body = RestResult<String>)
+    )
+)]
 #[delete("")]
-pub async fn delete(data: web::Data<AppState>, form: web::Query<DeleteParam>) -> impl Responder {
-    let res =
-        service::namespace::delete(&data.database_connection, form.namespace_id.clone()).await;
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
+    if let Err(err) = data
+        .write_lock_manager
+        .check_namespace(&params.namespace_id)
+    {
+        return RestResult::<String>::http_error(&err);
+    }
 
-    return HttpResponse::Ok().json(res);
+    let confirm_token = match &params.confirm_token {
+        Some(confirm_token) if !confirm_token.is_empty() => confirm_token,
+        _ => {
+            return match service::namespace::delete_report(
+                &data.database_connection,
+                &params.namespace_id,
+            )
+            .await
+            {
+                Ok(mut report) => {
+                    data.delete_confirmation_manager.issue(&mut report);
+
+                    HttpResponse::Ok().json(RestResult::<NamespaceDeleteReport>::success(report))
+                }
+                Err(err) => RestResult::<String>::http_error(&err),
+            };
+        }
+    };
+
+    if !data
+        .delete_confirmation_manager
+        .confirm(&params.namespace_id, confirm_token)
+    {
+        return RestResult::<String>::http_error(
+            &BusinessError::ParameterValidate(String::from(
+                "confirm token is invalid or expired, request a new delete report",
+            ))
+            .into(),
+        );
+    }
+
+    return match service::namespace::delete(
+        &data.database_connection,
+        &data.event_bus,
+        &params.namespace_id,
+    )
+    .await
+    {
+        Ok(()) => {
+            tracing::info!("namespace {} deleted", params.namespace_id);
+
+            activity::record(&data, &req, "namespace", "delete", &params.namespace_id);
+
+            HttpResponse::Ok().json(true)
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
 pub fn routers() -> Scope {
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures_util::future::join_all;
+use serde::Serialize;
+
+use crate::{
+    middleware::auth::{access_token, ACCESS_TOKEN},
+    model::common::{AppState, RestResult},
+};
+
+const PROPAGATE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Applies a change made on this node to the other members with the token of the caller, returns
+// the address and error of every member that was not reached
+pub async fn propagate<F>(
+    data: &AppState,
+    req: &HttpRequest,
+    method: &'static str,
+    path: &'static str,
+    send: F,
+) -> Vec<(String, String)>
+where
+    F: Fn(ureq::Request) -> Result<ureq::Response, Box<ureq::Error>> + Clone + Send + 'static,
+{
+    let access_token = access_token(req).unwrap_or_default();
+    let calls = data
+        .member_manager
+        .peer_urls(&data.context_path)
+        .into_iter()
+        .map(|(address, url)| {
+            let access_token = access_token.clone();
+            let send = send.clone();
+
+            async move {
+                let result = web::block(move || {
+                    send(
+                        ureq::request(method, &format!("{}{}", url, path))
+                            .timeout(PROPAGATE_TIMEOUT)
+                            .set(ACCESS_TOKEN, &access_token),
+                    )
+                })
+                .await;
+
+                match result {
+                    Ok(Ok(_)) => None,
+                    Ok(Err(err)) => Some((address, err.to_string())),
+                    Err(err) => Some((address, err.to_string())),
+                }
+            }
+        });
+    let failures: Vec<(String, String)> = join_all(calls).await.into_iter().flatten().collect();
+
+    failures.iter().for_each(|(address, err)| {
+        tracing::warn!("apply {} on member {} failed: {}", path, address, err);
+    });
+
+    failures
+}
+
+// A member that was not reached keeps its state, the answer names it so the change can be
+// repeated there
+pub fn propagated<T: Serialize>(failures: &[(String, String)], data: T) -> HttpResponse {
+    if failures.is_empty() {
+        return HttpResponse::Ok().json(RestResult::<T>::success(data));
+    }
+
+    HttpResponse::Ok().json(RestResult::<T> {
+        code: 200,
+        message: format!(
+            "not applied on members: {}",
+            failures
+                .iter()
+                .map(|(address, _)| address.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        data,
+    })
+}
@@ -1,13 +1,19 @@
-use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    model::common::{AppState, RestResult},
+    console::v1::activity,
+    model::{
+        auth::PermissionInfo,
+        common::{AppState, Page, RestResult},
+    },
     service,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchPageParam {
     search: Option<String>,
     role: Option<String>,
@@ -15,22 +21,31 @@ struct SearchPageParam {
     page_size: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = permission::CreateFormData)]
 struct CreateFormData {
     role: String,
     resource: String,
     action: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct DeleteParam {
     role: String,
     resource: String,
     action: String,
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "permission_search_page",
+    tag = "permission",
+    params(SearchPageParam),
+    responses((status = 200, description = "Permissions page", body = Page<PermissionInfo>))
+)]
 #[get("/permissions")]
 pub async fn search_page(
     data: web::Data<AppState>,
@@ -53,15 +68,25 @@ pub async fn search_page(
         params.page_size,
         accurate,
     )
-    .await
-    .unwrap();
+    .await;
 
-    return HttpResponse::Ok().json(result);
+    return match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "permission_create",
+    tag = "permission",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Permission granted", body = RestResult<String>))
+)]
 #[post("/permissions")]
 pub async fn create(
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Form<CreateFormData>,
 ) -> impl Responder {
     let result = service::permission::create(
@@ -73,21 +98,38 @@ pub async fn create(
     .await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("add permission ok!"),
-            data: String::from("add permission ok!"),
-        }),
-        Err(err) => HttpResponse::InternalServerError().json(RestResult::<String> {
-            code: 500,
-            message: err.to_string(),
-            data: err.to_string(),
-        }),
+        Ok(()) => {
+            activity::record(
+                &data,
+                &req,
+                "permission",
+                "create",
+                &format!("{}:{}:{}", params.role, params.resource, params.action),
+            );
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("add permission ok!"),
+                data: String::from("add permission ok!"),
+            })
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "permission_delete",
+    tag = "permission",
+    params(DeleteParam),
+    responses((status = 200, description = "Permission revoked", body = RestResult<String>))
+)]
 #[delete("/permissions")]
-pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>) -> impl Responder {
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
     let result = service::permission::delete(
         &data.database_connection,
         &params.role,
@@ -97,17 +139,21 @@ pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>)
     .await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("delete permission ok!"),
-            data: String::from("delete permission ok!"),
-        }),
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(RestResult::<String> {
-                code: 500,
-                message: err.to_string(),
-                data: err.to_string(),
-            });
+        Ok(()) => {
+            activity::record(
+                &data,
+                &req,
+                "permission",
+                "delete",
+                &format!("{}:{}:{}", params.role, params.resource, params.action),
+            );
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("delete permission ok!"),
+                data: String::from("delete permission ok!"),
+            })
         }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
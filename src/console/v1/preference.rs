@@ -0,0 +1,62 @@
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder, Scope};
+
+use crate::{
+    console::v1::guard,
+    model::common::{AppState, RestResult},
+    service::{self, preference::UserPreference},
+};
+
+#[utoipa::path(
+    context_path = "/v1/console/preferences",
+    operation_id = "preference_get",
+    tag = "preference",
+    responses(
+        (status = 200, description = "Console preferences of the current user, empty when never saved", body = RestResult<UserPreference>),
+        (status = 403, description = "Not logged in", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn get(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    let username = match guard::current_username(&req) {
+        Some(username) => username,
+        None => return guard::forbidden(),
+    };
+
+    match service::preference::find(&data.database_connection, &username).await {
+        Ok(preference) => {
+            HttpResponse::Ok().json(RestResult::<UserPreference>::success(preference))
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/v1/console/preferences",
+    operation_id = "preference_update",
+    tag = "preference",
+    request_body(content = UserPreference, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Preferences replaced", body = RestResult<bool>),
+        (status = 400, description = "Too many saved filters or a filter without name", body = RestResult<String>),
+        (status = 403, description = "Not logged in", body = RestResult<String>)
+    )
+)]
+#[put("")]
+pub async fn update(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    preference: web::Json<UserPreference>,
+) -> impl Responder {
+    let username = match guard::current_username(&req) {
+        Some(username) => username,
+        None => return guard::forbidden(),
+    };
+    match service::preference::save(&data.database_connection, &username, &preference).await {
+        Ok(result) => HttpResponse::Ok().json(RestResult::<bool>::success(result)),
+        Err(err) => RestResult::<String>::http_error(&err),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/preferences").service(get).service(update)
+}
@@ -0,0 +1,203 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    console::v1::{guard, peer},
+    model::common::{AppState, RestResult},
+    service::{
+        self,
+        write_quota::{QuotaRule, QuotaScope},
+    },
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = quota::CreateFormData)]
+struct CreateFormData {
+    scope: String,
+    target: String,
+    limit_per_minute: u32,
+    // set when a member passes the change on, it reads the stored rules again
+    local: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct DeleteParam {
+    scope: String,
+    target: String,
+    local: Option<bool>,
+}
+
+fn unknown_scope(scope: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(RestResult::<bool> {
+        code: 400,
+        message: format!("unknown quota scope: {}", scope),
+        data: false,
+    })
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/quota",
+    operation_id = "quota_list",
+    tag = "quota",
+    responses(
+        (status = 200, description = "Configured write quotas", body = RestResult<Vec<QuotaRule>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<Vec<QuotaRule>>::success(
+        data.write_quota_manager.rules(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/quota",
+    operation_id = "quota_create",
+    tag = "quota",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The quota is stored and applied by every member, the message names the members not reached", body = RestResult<bool>),
+        (status = 400, description = "Unknown quota scope", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[post("")]
+pub async fn create(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<CreateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let scope = match QuotaScope::from_name(&form.scope) {
+        Some(scope) => scope,
+        None => return unknown_scope(&form.scope),
+    };
+
+    if !form.local.unwrap_or_default() {
+        if let Err(err) = service::write_quota::save_rule(
+            &data.database_connection,
+            scope,
+            form.target.trim(),
+            form.limit_per_minute,
+        )
+        .await
+        {
+            return RestResult::<String>::http_error(&err);
+        }
+    }
+
+    if let Err(err) = data
+        .write_quota_manager
+        .load(&data.database_connection)
+        .await
+    {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    if form.local.unwrap_or_default() {
+        return HttpResponse::Ok().json(RestResult::<bool>::success(true));
+    }
+
+    let scope = form.scope.clone();
+    let target = form.target.clone();
+    let limit_per_minute = form.limit_per_minute.to_string();
+    let failures = peer::propagate(&data, &req, "POST", "/v1/core/quota", move |request| {
+        request
+            .send_form(&[
+                ("scope", &scope),
+                ("target", &target),
+                ("limitPerMinute", &limit_per_minute),
+                ("local", "true"),
+            ])
+            .map_err(Box::new)
+    })
+    .await;
+
+    peer::propagated(&failures, true)
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/quota",
+    operation_id = "quota_delete",
+    tag = "quota",
+    params(DeleteParam),
+    responses(
+        (status = 200, description = "Whether a quota was removed", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[delete("")]
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let scope = match QuotaScope::from_name(&params.scope) {
+        Some(scope) => scope,
+        None => return unknown_scope(&params.scope),
+    };
+
+    let removed = if params.local.unwrap_or_default() {
+        false
+    } else {
+        match service::write_quota::delete_rule(
+            &data.database_connection,
+            scope,
+            params.target.trim(),
+        )
+        .await
+        {
+            Ok(removed) => removed,
+            Err(err) => return RestResult::<String>::http_error(&err),
+        }
+    };
+
+    if let Err(err) = data
+        .write_quota_manager
+        .load(&data.database_connection)
+        .await
+    {
+        return RestResult::<String>::http_error(&err);
+    }
+
+    if params.local.unwrap_or_default() {
+        return HttpResponse::Ok().json(RestResult::<bool>::success(true));
+    }
+
+    let scope = params.scope.clone();
+    let target = params.target.clone();
+    let failures = peer::propagate(&data, &req, "DELETE", "/v1/core/quota", move |request| {
+        request
+            .query("scope", &scope)
+            .query("target", &target)
+            .query("local", "true")
+            .call()
+            .map_err(Box::new)
+    })
+    .await;
+
+    peer::propagated(&failures, removed)
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/quota")
+        .service(list)
+        .service(create)
+        .service(delete)
+}
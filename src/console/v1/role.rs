@@ -1,13 +1,19 @@
-use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    model::common::{AppState, RestResult},
+    console::v1::activity,
+    model::{
+        auth::RoleInfo,
+        common::{AppState, Page, RestResult},
+    },
     service,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchPageParam {
     search: Option<String>,
     username: Option<String>,
@@ -16,26 +22,36 @@ struct SearchPageParam {
     page_size: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchParam {
     role: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = role::CreateFormData)]
 struct CreateFormData {
     role: String,
     username: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct DeleteParam {
     role: String,
     username: Option<String>,
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "role_search_page",
+    tag = "role",
+    params(SearchPageParam),
+    responses((status = 200, description = "Roles page", body = Page<RoleInfo>))
+)]
 #[get("/roles")]
 pub async fn search_page(
     data: web::Data<AppState>,
@@ -68,45 +84,80 @@ pub async fn search_page(
         params.page_size,
         accurate,
     )
-    .await
-    .unwrap();
+    .await;
 
-    return HttpResponse::Ok().json(result);
+    return match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "role_search",
+    tag = "role",
+    params(SearchParam),
+    responses((status = 200, description = "Roles matching the keyword", body = Vec<String>))
+)]
 #[get("/roles/search")]
 pub async fn search(data: web::Data<AppState>, params: web::Query<SearchParam>) -> impl Responder {
-    let result = service::role::search(&data.database_connection, &params.role)
-        .await
-        .unwrap();
+    let result = service::role::search(&data.database_connection, &params.role).await;
 
-    return HttpResponse::Ok().json(result);
+    return match result {
+        Ok(roles) => HttpResponse::Ok().json(roles),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "role_create",
+    tag = "role",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Role bound to the user", body = RestResult<String>))
+)]
 #[post("/roles")]
 pub async fn create(
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Form<CreateFormData>,
 ) -> impl Responder {
     let result =
         service::role::create(&data.database_connection, &params.role, &params.username).await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("add role ok!"),
-            data: String::from("add role ok!"),
-        }),
-        Err(err) => HttpResponse::InternalServerError().json(RestResult::<String> {
-            code: 500,
-            message: err.to_string(),
-            data: err.to_string(),
-        }),
+        Ok(()) => {
+            activity::record(
+                &data,
+                &req,
+                "role",
+                "create",
+                &format!("{}:{}", params.role, params.username),
+            );
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("add role ok!"),
+                data: String::from("add role ok!"),
+            })
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "role_delete",
+    tag = "role",
+    params(DeleteParam),
+    responses((status = 200, description = "Role removed from the user, or entirely without username", body = RestResult<String>))
+)]
 #[delete("/roles")]
-pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>) -> impl Responder {
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
     let result = service::role::delete(
         &data.database_connection,
         &params.role,
@@ -115,23 +166,31 @@ pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>)
     .await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: format!(
-                "delete role of user {} ok!",
-                params.username.clone().unwrap_or_default()
-            ),
-            data: format!(
-                "delete role of user {} ok!",
-                params.username.clone().unwrap_or_default()
-            ),
-        }),
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(RestResult::<String> {
-                code: 500,
-                message: err.to_string(),
-                data: err.to_string(),
-            });
+        Ok(()) => {
+            activity::record(
+                &data,
+                &req,
+                "role",
+                "delete",
+                &format!(
+                    "{}:{}",
+                    params.role,
+                    params.username.clone().unwrap_or_default()
+                ),
+            );
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: format!(
+                    "delete role of user {} ok!",
+                    params.username.clone().unwrap_or_default()
+                ),
+                data: format!(
+                    "delete role of user {} ok!",
+                    params.username.clone().unwrap_or_default()
+                ),
+            })
         }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
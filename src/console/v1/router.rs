@@ -1,16 +1,31 @@
 use actix_web::{web, Scope};
 
-use super::{auth, config, health, history, namespace, server_state};
+use super::{
+    activity, approval, auth, auth_admin, cluster, config, health, history, history_audit,
+    ip_filter, locality, maintenance, namespace, preference, quota, server_state, sync, write_lock,
+};
 
 pub fn routers() -> Scope {
     return web::scope("/v1")
         .service(auth::routers())
+        .service(auth_admin::routers())
+        .service(cluster::routers())
         .service(config::routers())
         .service(history::routers())
+        .service(history_audit::routers())
+        .service(ip_filter::routers())
+        .service(locality::routers())
+        .service(maintenance::routers())
+        .service(quota::routers())
+        .service(sync::routers())
+        .service(write_lock::routers())
         .service(
             web::scope("/console")
+                .service(activity::routers())
+                .service(approval::routers())
                 .service(health::routers())
                 .service(namespace::routers())
+                .service(preference::routers())
                 .service(server_state::routers()),
         );
 }
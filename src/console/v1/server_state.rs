@@ -2,27 +2,30 @@ use std::collections::HashMap;
 
 use actix_web::{get, web, Scope};
 
-use crate::model::common::{AppState, RestResult};
+use crate::model::{
+    auth::ApiType,
+    common::{AppState, RestResult},
+};
 
+#[utoipa::path(
+    context_path = "/v1/console/server",
+    operation_id = "server_state_state",
+    tag = "server",
+    responses((status = 200, description = "Server state flags used by the console", body = HashMap<String, Option<String>>))
+)]
 #[get("/state")]
 pub async fn state(data: web::Data<AppState>) -> web::Json<HashMap<String, Option<String>>> {
     let mut state_map: HashMap<String, Option<String>> = HashMap::new();
 
+    let console_auth_enabled = data.auth_manager.is_enabled(ApiType::ConsoleApi);
+
     state_map.insert(
         "auth_enabled".to_string(),
-        Some(
-            data.app_config
-                .get_string("nacos.core.auth.enabled")
-                .unwrap_or("false".to_string()),
-        ),
+        Some(console_auth_enabled.to_string()),
     );
     state_map.insert(
         "login_page_enabled".to_string(),
-        Some(
-            data.app_config
-                .get_string("nacos.core.auth.enabled")
-                .unwrap_or("false".to_string()),
-        ),
+        Some(console_auth_enabled.to_string()),
     );
     state_map.insert(
         "auth_system_type".to_string(),
@@ -168,11 +171,11 @@ pub async fn state(data: web::Data<AppState>) -> web::Json<HashMap<String, Optio
     // Env state
     state_map.insert(
         "startup_mode".to_string(),
-        Some(
-            data.app_config
-                .get_string("nacos.standalone")
-                .unwrap_or("standalone".to_string()),
-        ),
+        Some(if data.member_manager.is_standalone() {
+            "standalone".to_string()
+        } else {
+            "cluster".to_string()
+        }),
     );
     state_map.insert(
         "function_mode".to_string(),
@@ -194,6 +197,12 @@ pub async fn state(data: web::Data<AppState>) -> web::Json<HashMap<String, Optio
     web::Json(state_map)
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/server",
+    operation_id = "server_state_announcement",
+    tag = "server",
+    responses((status = 200, body = RestResult<String>))
+)]
 #[get("/announcement")]
 pub async fn announcement() -> web::Json<RestResult<String>> {
     let rest_result = RestResult::<String>::success("".to_string());
@@ -201,6 +210,12 @@ pub async fn announcement() -> web::Json<RestResult<String>> {
     web::Json(rest_result)
 }
 
+#[utoipa::path(
+    context_path = "/v1/console/server",
+    operation_id = "server_state_guide",
+    tag = "server",
+    responses((status = 200, body = RestResult<String>))
+)]
 #[get("/guide")]
 pub async fn guide() -> web::Json<RestResult<String>> {
     let rest_result = RestResult::<String>::success("".to_string());
@@ -0,0 +1,201 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    console::v1::{activity, guard},
+    model::common::{AppState, BusinessError, RestResult},
+    service::sync::{self, SyncContext, SyncReport, SyncTask},
+};
+
+const DEFAULT_INTERVAL_SECONDS: u64 = 60;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = sync::CreateFormData)]
+struct CreateFormData {
+    /// Base url of the remote cluster including the context path, like http://10.0.0.1:8848/nacos
+    remote_server: String,
+    /// Needs read access to the remote namespace, masked values are copied as masked otherwise
+    remote_username: Option<String>,
+    remote_password: Option<String>,
+    remote_namespace: Option<String>,
+    /// Local namespace, the remote one by default
+    namespace: Option<String>,
+    /// Group pattern, * matches any characters
+    group: Option<String>,
+    /// DataId pattern, * matches any characters
+    data_id: Option<String>,
+    interval_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct IdParam {
+    id: u64,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/sync",
+    operation_id = "sync_list",
+    tag = "sync",
+    responses(
+        (status = 200, description = "Config sync tasks of this node with the outcome of their last run", body = RestResult<Vec<SyncTask>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<Vec<SyncTask>>::success(
+        data.sync_manager.tasks(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/sync",
+    operation_id = "sync_create",
+    tag = "sync",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The task, it first runs within a second", body = RestResult<SyncTask>),
+        (status = 400, description = "The remote server is not an http or https url, or the local namespace is protected by approval", body = RestResult<String>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[post("")]
+pub async fn create(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<CreateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let form = form.into_inner();
+
+    if !form.remote_server.starts_with("http://") && !form.remote_server.starts_with("https://") {
+        return RestResult::<String>::http_error(
+            &BusinessError::ParameterValidate(format!(
+                "remote server '{}' is not an http or https url",
+                form.remote_server
+            ))
+            .into(),
+        );
+    }
+
+    let remote_namespace = form.remote_namespace.unwrap_or_default();
+    let namespace = form.namespace.unwrap_or(remote_namespace.clone());
+
+    if data.approval_manager.is_protected(&namespace) {
+        return RestResult::<String>::http_error(&sync::protected_namespace(&namespace).into());
+    }
+
+    let task = data.sync_manager.create(SyncTask {
+        remote_server: form.remote_server,
+        namespace,
+        remote_namespace,
+        group: form.group.unwrap_or_default(),
+        data_id: form.data_id.unwrap_or_default(),
+        interval_seconds: form.interval_seconds.unwrap_or(DEFAULT_INTERVAL_SECONDS),
+        operator: guard::current_username(&req).unwrap_or_default(),
+        username: form.remote_username.filter(|e| !e.is_empty()),
+        password: form.remote_password,
+        ..Default::default()
+    });
+
+    tracing::info!(
+        "config sync task {} from {} namespace '{}' to namespace '{}' created by {}",
+        task.id,
+        task.remote_server,
+        task.remote_namespace,
+        task.namespace,
+        task.operator
+    );
+
+    activity::record(&data, &req, "sync", "create", &task.id.to_string());
+
+    HttpResponse::Ok().json(RestResult::<SyncTask>::success(task))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/sync",
+    operation_id = "sync_delete",
+    tag = "sync",
+    params(IdParam),
+    responses(
+        (status = 200, description = "Whether a task was removed, configs synced so far are kept", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[delete("")]
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<IdParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let removed = data.sync_manager.delete(params.id);
+
+    if removed {
+        activity::record(&data, &req, "sync", "delete", &params.id.to_string());
+
+        tracing::info!("config sync task {} removed", params.id);
+    }
+
+    HttpResponse::Ok().json(RestResult::<bool>::success(removed))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/sync",
+    operation_id = "sync_run",
+    tag = "sync",
+    params(IdParam),
+    responses(
+        (status = 200, description = "Outcome of running the task now", body = RestResult<SyncReport>),
+        (status = 400, description = "The local namespace is protected by approval", body = RestResult<String>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>),
+        (status = 404, description = "Task not found", body = RestResult<String>),
+        (status = 409, description = "The task is already running", body = RestResult<String>)
+    )
+)]
+#[post("/run")]
+pub async fn run(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<IdParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    let context = SyncContext {
+        db: data.database_connection.clone(),
+        content_cipher: data.content_cipher.clone(),
+        capacity_policy: data.capacity_policy.clone(),
+        event_bus: data.event_bus.clone(),
+        write_lock_manager: data.write_lock_manager.clone(),
+        approval_manager: data.approval_manager.clone(),
+    };
+
+    match data.sync_manager.run(params.id, &context).await {
+        Ok(report) => HttpResponse::Ok().json(RestResult::<SyncReport>::success(report)),
+        Err(err) => RestResult::<String>::http_error(&err),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/sync")
+        .service(list)
+        .service(create)
+        .service(delete)
+        .service(run)
+}
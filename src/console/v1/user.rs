@@ -1,14 +1,17 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
+use crate::console::v1::activity;
 use crate::model::{
-    auth::{DEFAULT_USER, GLOBAL_ADMIN_ROLE},
-    common::{AppState, BusinessError, RestResult},
+    auth::{User, DEFAULT_USER, GLOBAL_ADMIN_ROLE},
+    common::{AppState, BusinessError, Page, RestResult},
 };
 use crate::service;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchPageParam {
     search: Option<String>,
     username: Option<String>,
@@ -16,32 +19,43 @@ struct SearchPageParam {
     page_size: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchParam {
     username: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = user::CreateFormData)]
 struct CreateFormData {
     username: String,
     password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[schema(as = user::UpdateFormData)]
 struct UpdateFormData {
     username: String,
     new_password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct DeleteParam {
     username: String,
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "user_search_page",
+    tag = "user",
+    params(SearchPageParam),
+    responses((status = 200, description = "Users page", body = Page<User>))
+)]
 #[get("/users")]
 pub async fn search_page(
     data: web::Data<AppState>,
@@ -64,24 +78,45 @@ pub async fn search_page(
         params.page_size,
         accurate,
     )
-    .await
-    .unwrap();
+    .await;
 
-    return HttpResponse::Ok().json(result);
+    return match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "user_search",
+    tag = "user",
+    params(SearchParam),
+    responses((status = 200, description = "Usernames matching the keyword", body = Vec<String>))
+)]
 #[get("/users/search")]
 pub async fn search(data: web::Data<AppState>, params: web::Query<SearchParam>) -> impl Responder {
-    let result = service::user::search(&data.database_connection, &params.username)
-        .await
-        .unwrap();
+    let result = service::user::search(&data.database_connection, &params.username).await;
 
-    return HttpResponse::Ok().json(result);
+    return match result {
+        Ok(usernames) => HttpResponse::Ok().json(usernames),
+        Err(err) => RestResult::<String>::http_error(&err),
+    };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "user_create",
+    tag = "user",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "User created", body = RestResult<String>),
+        (status = 409, description = "User already exists", body = RestResult<String>)
+    )
+)]
 #[post("/users")]
 pub async fn create(
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Form<CreateFormData>,
 ) -> impl Responder {
     if params.username == DEFAULT_USER {
@@ -95,8 +130,9 @@ pub async fn create(
     let user = service::user::find_by_username(&data.database_connection, &params.username).await;
 
     if user.is_some() {
-        return HttpResponse::BadRequest()
-            .json(format!("user '{}' already exist!", params.username));
+        return RestResult::<String>::http_error(&anyhow::Error::from(
+            BusinessError::UserAlreadyExist(params.username.clone()),
+        ));
     }
 
     let password = bcrypt::hash(params.password.clone(), 10u32).ok().unwrap();
@@ -105,22 +141,30 @@ pub async fn create(
         service::user::create(&data.database_connection, &params.username, &password).await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("create user ok!"),
-            data: String::from("create user ok!"),
-        }),
-        Err(err) => HttpResponse::InternalServerError().json(RestResult::<String> {
-            code: 500,
-            message: err.to_string(),
-            data: err.to_string(),
-        }),
+        Ok(()) => {
+            activity::record(&data, &req, "user", "create", &params.username);
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("create user ok!"),
+                data: String::from("create user ok!"),
+            })
+        }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "user_update",
+    tag = "user",
+    request_body(content = UpdateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Password updated", body = RestResult<String>))
+)]
 #[put("/users")]
 pub async fn update(
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Form<UpdateFormData>,
 ) -> impl Responder {
     let result = service::user::update(
@@ -131,32 +175,38 @@ pub async fn update(
     .await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("update user ok!"),
-            data: String::from("update user ok!"),
-        }),
-        Err(err) => {
-            let code = match err.downcast_ref() {
-                Some(BusinessError::UserNotExist(_)) => 400,
-                _ => 500,
-            };
-
-            return HttpResponse::InternalServerError().json(RestResult::<String> {
-                code: code,
-                message: err.to_string(),
-                data: err.to_string(),
-            });
+        Ok(()) => {
+            activity::record(&data, &req, "user", "update", &params.username);
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("update user ok!"),
+                data: String::from("update user ok!"),
+            })
         }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
 
+#[utoipa::path(
+    context_path = "/v1/auth",
+    operation_id = "user_delete",
+    tag = "user",
+    params(DeleteParam),
+    responses(
+        (status = 200, description = "User deleted", body = RestResult<String>),
+        (status = 400, description = "The default admin user can not be deleted", body = RestResult<String>)
+    )
+)]
 #[delete("/users")]
-pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>) -> impl Responder {
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
     let global_admin = service::role::find_by_username(&data.database_connection, &params.username)
         .await
-        .ok()
-        .unwrap()
+        .unwrap_or_default()
         .iter()
         .any(|role| role.role == GLOBAL_ADMIN_ROLE);
 
@@ -171,22 +221,15 @@ pub async fn delete(data: web::Data<AppState>, params: web::Query<DeleteParam>)
     let result = service::user::delete(&data.database_connection, &params.username).await;
 
     return match result {
-        Ok(()) => HttpResponse::Ok().json(RestResult::<String> {
-            code: 200,
-            message: String::from("delete user ok!"),
-            data: String::from("delete user ok!"),
-        }),
-        Err(err) => {
-            let code = match err.downcast_ref() {
-                Some(BusinessError::UserNotExist(_)) => 400,
-                _ => 500,
-            };
-
-            return HttpResponse::InternalServerError().json(RestResult::<String> {
-                code: code,
-                message: err.to_string(),
-                data: err.to_string(),
-            });
+        Ok(()) => {
+            activity::record(&data, &req, "user", "delete", &params.username);
+
+            HttpResponse::Ok().json(RestResult::<String> {
+                code: 200,
+                message: String::from("delete user ok!"),
+                data: String::from("delete user ok!"),
+            })
         }
+        Err(err) => RestResult::<String>::http_error(&err),
     };
 }
@@ -0,0 +1,214 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    console::v1::{activity, guard, peer},
+    model::common::{AppState, RestResult},
+    service::{self, write_lock::WriteLock},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = write_lock::CreateFormData)]
+struct CreateFormData {
+    namespace: String,
+    group: Option<String>,
+    reason: String,
+    expire_seconds: Option<i64>,
+    // set when a member passes the change on, it reads the stored locks again
+    local: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct DeleteParam {
+    namespace: String,
+    group: Option<String>,
+    local: Option<bool>,
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/write-lock",
+    operation_id = "write_lock_list",
+    tag = "write-lock",
+    responses(
+        (status = 200, description = "Active write locks", body = RestResult<Vec<WriteLock>>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[get("")]
+pub async fn list(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    HttpResponse::Ok().json(RestResult::<Vec<WriteLock>>::success(
+        data.write_lock_manager.locks(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/write-lock",
+    operation_id = "write_lock_create",
+    tag = "write-lock",
+    request_body(content = CreateFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The lock, without group the whole namespace is locked. It is stored and applied by every member, the message names the members not reached", body = RestResult<WriteLock>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[post("")]
+pub async fn create(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<CreateFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    if form.local.unwrap_or_default() {
+        return reload(&data).await;
+    }
+
+    let lock = data.write_lock_manager.lock(
+        &form.namespace,
+        &form.group.clone().unwrap_or_default(),
+        &form.reason,
+        &guard::current_username(&req).unwrap_or_default(),
+        form.expire_seconds.filter(|e| *e > 0),
+    );
+
+    if let Err(err) = service::write_lock::save(&data.database_connection, &lock).await {
+        data.write_lock_manager.unlock(&lock.namespace, &lock.group);
+
+        return RestResult::<String>::http_error(&err);
+    }
+
+    tracing::info!(
+        "write lock on namespace {} group {} set by {}: {}",
+        lock.namespace,
+        lock.group,
+        lock.operator,
+        lock.reason
+    );
+
+    activity::record(
+        &data,
+        &req,
+        "write-lock",
+        "lock",
+        &format!("{}:{}", lock.namespace, lock.group),
+    );
+
+    let namespace = lock.namespace.clone();
+    let group = lock.group.clone();
+    let failures = peer::propagate(&data, &req, "POST", "/v1/core/write-lock", move |request| {
+        request
+            .send_form(&[
+                ("namespace", &namespace),
+                ("group", &group),
+                ("reason", ""),
+                ("local", "true"),
+            ])
+            .map_err(Box::new)
+    })
+    .await;
+
+    peer::propagated(&failures, lock)
+}
+
+#[utoipa::path(
+    context_path = "/v1/core/write-lock",
+    operation_id = "write_lock_delete",
+    tag = "write-lock",
+    params(DeleteParam),
+    responses(
+        (status = 200, description = "Whether a lock was removed", body = RestResult<bool>),
+        (status = 403, description = "Not a global admin", body = RestResult<String>)
+    )
+)]
+#[delete("")]
+pub async fn delete(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<DeleteParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return guard::forbidden();
+    }
+
+    if params.local.unwrap_or_default() {
+        return reload(&data).await;
+    }
+
+    let group = params.group.clone().unwrap_or_default();
+    let removed =
+        match service::write_lock::delete(&data.database_connection, &params.namespace, &group)
+            .await
+        {
+            Ok(removed) => removed,
+            Err(err) => return RestResult::<String>::http_error(&err),
+        };
+
+    data.write_lock_manager.unlock(&params.namespace, &group);
+
+    if removed {
+        activity::record(
+            &data,
+            &req,
+            "write-lock",
+            "unlock",
+            &format!(
+                "{}:{}",
+                params.namespace,
+                params.group.clone().unwrap_or_default()
+            ),
+        );
+
+        tracing::info!(
+            "write lock on namespace {} group {} removed",
+            params.namespace,
+            params.group.clone().unwrap_or_default()
+        );
+    }
+
+    let namespace = params.namespace.clone();
+    let failures = peer::propagate(
+        &data,
+        &req,
+        "DELETE",
+        "/v1/core/write-lock",
+        move |request| {
+            request
+                .query("namespace", &namespace)
+                .query("group", &group)
+                .query("local", "true")
+                .call()
+                .map_err(Box::new)
+        },
+    )
+    .await;
+
+    peer::propagated(&failures, removed)
+}
+
+async fn reload(data: &web::Data<AppState>) -> HttpResponse {
+    match data
+        .write_lock_manager
+        .load(&data.database_connection)
+        .await
+    {
+        Ok(()) => HttpResponse::Ok().json(RestResult::<bool>::success(true)),
+        Err(err) => RestResult::<String>::http_error(&err),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/write-lock")
+        .service(list)
+        .service(create)
+        .service(delete)
+}
@@ -1,15 +1,18 @@
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
 use serde::Deserialize;
+use utoipa::IntoParams;
 
-use chrono::Utc;
-
-use crate::model::{
-    common::{AppState, ErrorResult, Page},
-    config::ConfigInfo,
+use crate::{
+    console::v1::guard,
+    model::{
+        common::{AppState, BusinessError, Page, Result},
+        config::{ConfigInfo, ConfigSortBy},
+    },
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 struct SearchParam {
     search: Option<String>,
     data_id: Option<String>,
@@ -21,21 +24,64 @@ struct SearchParam {
     types: Option<String>,
     #[serde(rename = "config_detail")]
     config_detail: Option<String>,
+    sort_by: Option<String>,
+    order: Option<String>,
     page_no: u64,
     page_size: u64,
 }
 
+#[utoipa::path(
+    context_path = "/v2/cs/config/",
+    operation_id = "v2_config_search",
+    tag = "config",
+    params(SearchParam),
+    responses(
+        (status = 200, description = "Configs matching search=blur", body = Page<ConfigInfo>),
+        (status = 400, description = "Unknown sortBy, one of id, dataId, group, appName and modifiedTime", body = Result<String>),
+        (status = 403, description = "config_detail search without the unmask permission", body = Result<String>)
+    )
+)]
 #[get("searchDetail")]
 pub async fn search(
-    req: HttpRequest,
     data: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Query<SearchParam>,
 ) -> impl Responder {
     if params.search.is_some() && params.search.as_ref().unwrap() == "blur" {
+        let unmask =
+            guard::can_unmask(&data, &req, &params.tenant.clone().unwrap_or_default()).await;
+        // a content search tells the masked values apart
+        if !unmask
+            && params
+                .config_detail
+                .as_deref()
+                .is_some_and(|e| !e.is_empty())
+        {
+            return Result::<String>::http_error(
+                &BusinessError::AccessDenied(String::from(
+                    "config_detail search needs the unmask permission on the namespace",
+                ))
+                .into(),
+            );
+        }
+
         let search_param = params.0;
+        let sort_by = match search_param.sort_by.as_deref() {
+            None => ConfigSortBy::default(),
+            Some(name) => match ConfigSortBy::from_name(name) {
+                Some(sort_by) => sort_by,
+                None => {
+                    return Result::<String>::http_error(
+                        &BusinessError::ParameterValidate(format!("unknown sortBy: {}", name))
+                            .into(),
+                    )
+                }
+            },
+        };
 
         let result = crate::service::config::search_page(
             &data.database_connection,
+            &data.content_cipher,
             search_param.page_no,
             search_param.page_size,
             search_param.tenant.unwrap_or_default().as_str(),
@@ -45,18 +91,23 @@ pub async fn search(
             search_param.config_tags.unwrap_or_default().as_str(),
             search_param.types.clone().unwrap_or_default().as_str(),
             search_param.config_detail.unwrap_or_default().as_str(),
+            sort_by,
+            search_param.order.as_deref() == Some("desc"),
         )
         .await;
 
         return match result {
-            Ok(page_result) => HttpResponse::Ok().json(page_result),
-            Err(err) => HttpResponse::InternalServerError().json(ErrorResult {
-                timestamp: Utc::now().to_rfc3339(),
-                status: 403,
-                message: err.to_string(),
-                error: String::from("Forbiden"),
-                path: req.path().to_string(),
-            }),
+            Ok(mut page_result) => {
+                if !unmask {
+                    page_result
+                        .page_items
+                        .iter_mut()
+                        .for_each(|e| e.content = data.mask_manager.mask(&e.content));
+                }
+
+                HttpResponse::Ok().json(page_result)
+            }
+            Err(err) => Result::<String>::http_error(&err),
         };
     }
 
@@ -1,15 +1,41 @@
-use actix_web::{get, web, Scope};
+use actix_web::{get, web, HttpResponse, Responder, Scope};
 
-use crate::model::common::Result;
+use crate::{
+    model::common::{AppState, Result, SERVER_ERROR},
+    service,
+};
 
+#[utoipa::path(
+    context_path = "/v2/console/health",
+    operation_id = "v2_health_liveness",
+    tag = "health",
+    responses((status = 200, body = Result<String>))
+)]
 #[get("/liveness")]
 pub async fn liveness() -> web::Json<Result<String>> {
     web::Json(Result::<String>::success("ok".to_string()))
 }
 
+#[utoipa::path(
+    context_path = "/v2/console/health",
+    operation_id = "v2_health_readiness",
+    tag = "health",
+    responses(
+        (status = 200, body = Result<String>),
+        (status = 500, description = "The node failed its self health check", body = Result<String>)
+    )
+)]
 #[get("/readiness")]
-pub async fn readiness() -> web::Json<Result<String>> {
-    web::Json(Result::<String>::success("ok".to_string()))
+pub async fn readiness(data: web::Data<AppState>) -> impl Responder {
+    if !service::health::is_healthy(&data.member_manager) {
+        return HttpResponse::InternalServerError().json(Result::<String> {
+            code: SERVER_ERROR.code,
+            message: SERVER_ERROR.message.to_string(),
+            data: "server is not in readiness".to_string(),
+        });
+    }
+
+    HttpResponse::Ok().json(Result::<String>::success("ok".to_string()))
 }
 
 pub fn routers() -> Scope {
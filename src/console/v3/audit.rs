@@ -0,0 +1,77 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    console::v1::guard,
+    model::common::{AppState, BusinessError, Page, Result},
+    service::audit::{AuditQuery, AuditRecord},
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+struct SearchParam {
+    operator: Option<String>,
+    /// config, user, role, permission, namespace and other console resources
+    resource_type: Option<String>,
+    action: Option<String>,
+    /// Inclusive, epoch milliseconds
+    start_time: Option<i64>,
+    /// Exclusive, epoch milliseconds
+    end_time: Option<i64>,
+    page_no: Option<u64>,
+    page_size: Option<u64>,
+}
+
+#[utoipa::path(
+    context_path = "/v3/console/audit",
+    operation_id = "v3_audit_search",
+    tag = "audit",
+    params(SearchParam),
+    responses(
+        (status = 200, description = "Audit records of console write operations, newest first. Empty while nacos.core.audit.enabled is off and no persistence is plugged in", body = Result<Page<AuditRecord>>),
+        (status = 403, description = "Not a global admin", body = Result<String>)
+    )
+)]
+#[get("")]
+pub async fn search(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    params: web::Query<SearchParam>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return Result::<String>::http_error(
+            &BusinessError::AccessDenied(String::from(
+                "only global admin can perform this operation",
+            ))
+            .into(),
+        );
+    }
+
+    let params = params.into_inner();
+    let query = AuditQuery {
+        operator: params.operator.unwrap_or_default(),
+        resource_type: params.resource_type.unwrap_or_default(),
+        action: params.action.unwrap_or_default(),
+        start_time: params.start_time,
+        end_time: params.end_time,
+    };
+
+    match data
+        .audit_manager
+        .search(
+            &query,
+            params.page_no.unwrap_or(1).max(1),
+            params.page_size.unwrap_or(20).max(1),
+        )
+        .await
+    {
+        Ok(page) => HttpResponse::Ok().json(Result::<Page<AuditRecord>>::success(page)),
+        Err(err) => Result::<String>::http_error(&err),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/audit").service(search)
+}
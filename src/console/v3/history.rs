@@ -0,0 +1,164 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use similar::TextDiff;
+use utoipa::ToSchema;
+
+use crate::{
+    console::v1::{
+        activity::{self, config_resource},
+        guard,
+    },
+    model::{
+        common::{AppState, BusinessError, Result, PARAMETER_VALIDATE_ERROR},
+        config::{PublishOperation, RollbackReport},
+        validation,
+    },
+    service::{self, approval::ApprovalRecord, event::ServerEvent},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = history::RollbackFormData)]
+struct RollbackFormData {
+    nid: u64,
+    dry_run: Option<bool>,
+}
+
+#[utoipa::path(
+    context_path = "/v3/admin/cs/history",
+    operation_id = "v3_history_rollback",
+    tag = "history",
+    request_body(content = RollbackFormData, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The config got the content of history entry nid back, recorded in history with op_type R. With dryRun=true nothing is written and the report previews the change as a unified diff, and requiresApproval tells whether applying it waits for approval", body = Result<RollbackReport>),
+        (status = 202, description = "The namespace is protected, the rollback waits for approval", body = Result<ApprovalRecord>),
+        (status = 400, description = "The restored content fails validation, a schema bound to the dataId or a write lock, listed in the violations of the report", body = Result<RollbackReport>),
+        (status = 403, description = "Not a global admin", body = Result<String>),
+        (status = 404, description = "No history entry nid", body = Result<String>)
+    )
+)]
+#[post("/rollback")]
+pub async fn rollback(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<RollbackFormData>,
+) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return Result::<String>::http_error(
+            &BusinessError::AccessDenied(String::from(
+                "only global admin can perform this operation",
+            ))
+            .into(),
+        );
+    }
+
+    let token_user = guard::current_username(&req).unwrap_or_default();
+    let src_ip = String::from(
+        req.connection_info()
+            .realip_remote_addr()
+            .unwrap_or_default(),
+    );
+    let (change, current) = match service::history::rollback_change(
+        &data.database_connection,
+        &data.content_cipher,
+        form.nid,
+        &token_user,
+        &src_ip,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(err) => return Result::<String>::http_error(&err),
+    };
+
+    let md5 = service::config::md5_digest(&change.content);
+    let current_content = current.as_ref().map_or("", |e| e.content.as_str());
+    let mut violations = Vec::new();
+
+    if let Err(err) = validation::check_config_change(&change) {
+        violations.push(err.to_string());
+    }
+
+    match service::config_schema::validate(&data.database_connection, &data.content_cipher, &change)
+        .await
+    {
+        Ok(schema_violations) => {
+            violations.extend(schema_violations.iter().map(|e| e.to_string()));
+        }
+        Err(err) => return Result::<String>::http_error(&err),
+    }
+
+    if let Err(err) = data.write_lock_manager.check(&change.tenant, &change.group) {
+        violations.push(err.to_string());
+    }
+
+    let mut report = RollbackReport {
+        nid: form.nid,
+        data_id: change.data_id.clone(),
+        group: change.group.clone(),
+        tenant: change.tenant.clone(),
+        md5: md5.clone(),
+        operation: match &current {
+            Some(current) if current.md5 == md5 => PublishOperation::Unchanged,
+            Some(_) => PublishOperation::Update,
+            None => PublishOperation::Create,
+        },
+        // only global admins get here, and they may see unmasked content
+        diff: TextDiff::from_lines(current_content, change.content.as_str())
+            .unified_diff()
+            .header("current", &format!("history {}", form.nid))
+            .to_string(),
+        violations,
+        requires_approval: data.approval_manager.is_protected(&change.tenant),
+        rolled_back: false,
+    };
+
+    if form.dry_run.unwrap_or_default() || report.operation == PublishOperation::Unchanged {
+        return HttpResponse::Ok().json(Result::<RollbackReport>::success(report));
+    }
+
+    if !report.violations.is_empty() {
+        return HttpResponse::BadRequest().json(Result::<RollbackReport> {
+            code: PARAMETER_VALIDATE_ERROR.code,
+            message: PARAMETER_VALIDATE_ERROR.message.to_string(),
+            data: report,
+        });
+    }
+
+    let resource = config_resource(&change.tenant, &change.group, &change.data_id);
+
+    if report.requires_approval {
+        let record = match data.approval_manager.submit(change, &token_user).await {
+            Ok(record) => record,
+            Err(err) => return Result::<String>::http_error(&err),
+        };
+
+        activity::record(&data, &req, "config", "submit", &resource);
+
+        return HttpResponse::Accepted().json(Result::<ApprovalRecord>::success(record));
+    }
+
+    if let Err(err) = service::config::publish_as(
+        &data.database_connection,
+        &data.content_cipher,
+        &data.capacity_policy,
+        &change,
+        &change.op_type,
+    )
+    .await
+    {
+        return Result::<String>::http_error(&err);
+    }
+
+    data.event_bus.publish(ServerEvent::config_changed(&change));
+
+    activity::record(&data, &req, "config", "rollback", &resource);
+
+    report.rolled_back = true;
+
+    HttpResponse::Ok().json(Result::<RollbackReport>::success(report))
+}
+
+pub fn routers() -> Scope {
+    web::scope("/cs/history").service(rollback)
+}
@@ -0,0 +1,73 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, Scope};
+
+use crate::{
+    console::v1::guard,
+    model::{
+        auth::{ApiType, PermissionCheck, PermissionCheckResult, GLOBAL_ADMIN_ROLE},
+        common::{AppState, Result},
+    },
+    service,
+};
+
+const MAX_CHECKS: usize = 200;
+
+#[utoipa::path(
+    context_path = "/v3/auth/permissions",
+    operation_id = "v3_permission_check",
+    tag = "permission",
+    request_body(content = Vec<PermissionCheck>, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Whether the current user may perform each action on each resource, in request order. Everything is allowed while console auth is off", body = Result<Vec<PermissionCheckResult>>),
+        (status = 400, description = "More than 200 checks", body = Result<String>)
+    )
+)]
+#[post("/check")]
+pub async fn check(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    checks: web::Json<Vec<PermissionCheck>>,
+) -> impl Responder {
+    if checks.len() > MAX_CHECKS {
+        return HttpResponse::BadRequest().json(Result::<String> {
+            code: 400,
+            message: format!("at most {} checks are allowed", MAX_CHECKS),
+            data: String::new(),
+        });
+    }
+
+    let roles: Vec<String> = match guard::current_username(&req) {
+        Some(username) => {
+            match service::role::find_by_username(&data.database_connection, &username).await {
+                Ok(roles) => roles.into_iter().map(|role| role.role).collect(),
+                Err(err) => return Result::<String>::http_error(&err),
+            }
+        }
+        None => Vec::new(),
+    };
+    let allow_all = !data.auth_manager.is_enabled(ApiType::ConsoleApi)
+        || roles.iter().any(|role| role == GLOBAL_ADMIN_ROLE);
+    let permissions = if allow_all {
+        Vec::new()
+    } else {
+        match service::permission::find_by_roles(&data.database_connection, &roles).await {
+            Ok(permissions) => permissions,
+            Err(err) => return Result::<String>::http_error(&err),
+        }
+    };
+
+    let results = checks
+        .iter()
+        .map(|check| PermissionCheckResult {
+            resource: check.resource.clone(),
+            action: check.action.clone(),
+            allowed: allow_all
+                || service::permission::is_granted(&permissions, &check.resource, &check.action),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(Result::<Vec<PermissionCheckResult>>::success(results))
+}
+
+pub fn routers() -> Scope {
+    web::scope("/permissions").service(check)
+}
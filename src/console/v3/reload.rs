@@ -0,0 +1,42 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, Scope};
+
+use crate::{
+    console::v1::{activity, guard},
+    model::common::{AppState, BusinessError, Result},
+    service::reload::ReloadReport,
+};
+
+#[utoipa::path(
+    context_path = "/v3/admin/core/config",
+    operation_id = "v3_config_reload",
+    tag = "server",
+    responses(
+        (status = 200, description = "The config file was read again. The report lists the applied settings among log-level, auth, quota and ip-filter, only settings whose value changed are applied", body = Result<ReloadReport>),
+        (status = 403, description = "Not a global admin", body = Result<String>),
+        (status = 500, description = "The config file could not be read or a changed setting is invalid", body = Result<String>)
+    )
+)]
+#[post("/reload")]
+pub async fn reload(data: web::Data<AppState>, req: HttpRequest) -> impl Responder {
+    if !guard::is_global_admin(&data, &req).await {
+        return Result::<String>::http_error(
+            &BusinessError::AccessDenied(String::from(
+                "only global admin can perform this operation",
+            ))
+            .into(),
+        );
+    }
+
+    match data.config_reloader.reload() {
+        Ok(report) => {
+            activity::record(&data, &req, "server", "reload", "config");
+
+            HttpResponse::Ok().json(Result::<ReloadReport>::success(report))
+        }
+        Err(err) => Result::<String>::http_error(&err),
+    }
+}
+
+pub fn routers() -> Scope {
+    web::scope("/core/config").service(reload)
+}
@@ -0,0 +1,17 @@
+use actix_web::{web, Scope};
+
+use super::{audit, history, permission, reload, ws};
+use crate::console::openapi;
+
+pub fn routers() -> Scope {
+    web::scope("/v3")
+        .service(openapi::api_docs)
+        .service(web::scope("/auth").service(permission::routers()))
+        .service(
+            web::scope("/admin")
+                .service(history::routers())
+                .service(reload::routers()),
+        )
+        .service(web::scope("/console").service(audit::routers()))
+        .service(ws::routers())
+}
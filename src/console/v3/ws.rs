@@ -0,0 +1,127 @@
+use actix_web::{get, rt, web, HttpRequest, Responder, Scope};
+use actix_ws::{Message, Session};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    model::common::{AppState, Result},
+    service::{
+        event::{ConfigSubscription, ServerEvent},
+        watch::Watcher,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Operation {
+    Subscribe,
+    Unsubscribe,
+}
+
+// {"operation":"subscribe","tenant":"","group":"DEFAULT_GROUP","dataId":"app-*"}
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionRequest {
+    operation: Operation,
+    #[serde(flatten)]
+    subscription: ConfigSubscription,
+}
+
+#[utoipa::path(
+    context_path = "/v3/ws",
+    operation_id = "v3_ws_config",
+    tag = "config",
+    params(
+        ("tenant" = Option<String>, Query, description = "Namespace of an initial subscription"),
+        ("group" = Option<String>, Query, description = "Group pattern of an initial subscription, * matches any characters"),
+        ("dataId" = Option<String>, Query, description = "DataId pattern of an initial subscription, * matches any characters")
+    ),
+    responses(
+        (status = 101, description = "WebSocket for config change notifications. Text messages like {\"operation\":\"subscribe\",\"tenant\":\"\",\"group\":\"DEFAULT_GROUP\",\"dataId\":\"app-*\"} add or, with unsubscribe, remove a subscription. Every change published on this node that matches one is sent as {\"type\":\"CONFIG_CHANGED\",\"dataId\":..,\"group\":..,\"tenant\":..,\"md5\":..}. Browsers pass the token as the accessToken query parameter"),
+        (status = 400, description = "Not a WebSocket handshake", body = Result<String>)
+    )
+)]
+#[get("/config")]
+pub async fn config(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Payload,
+    initial: web::Query<ConfigSubscription>,
+) -> actix_web::Result<impl Responder> {
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+    let mut watcher = data.watch_registry.watch();
+    let initial = initial.into_inner();
+
+    // the first subscription of a watcher is always within the limit
+    if initial != ConfigSubscription::default() {
+        let _ = watcher.subscribe(initial);
+    }
+
+    rt::spawn(async move {
+        loop {
+            tokio::select! {
+                message = messages.recv() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(message) = apply(&watcher, &text) {
+                            let error = json!({"type": "ERROR", "message": message});
+
+                            if session.text(error.to_string()).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        let _ = session.close(reason).await;
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                },
+                event = watcher.recv() => match event {
+                    Some(event) => {
+                        if send_event(&mut session, &event).await.is_err() {
+                            return;
+                        }
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+fn apply(watcher: &Watcher, text: &str) -> std::result::Result<(), String> {
+    let request: SubscriptionRequest =
+        serde_json::from_str(text).map_err(|err| format!("invalid subscription: {}", err))?;
+
+    match request.operation {
+        Operation::Subscribe => watcher
+            .subscribe(request.subscription)
+            .map_err(|err| err.to_string())?,
+        Operation::Unsubscribe => watcher.unsubscribe(&request.subscription),
+    }
+
+    Ok(())
+}
+
+async fn send_event(
+    session: &mut Session,
+    event: &ServerEvent,
+) -> std::result::Result<(), actix_ws::Closed> {
+    session
+        .text(serde_json::to_string(event).unwrap_or_default())
+        .await
+}
+
+pub fn routers() -> Scope {
+    web::scope("/ws").service(config)
+}
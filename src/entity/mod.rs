@@ -2,6 +2,9 @@
 
 pub mod prelude;
 
+pub mod audit_record;
+pub mod config_approval;
+pub mod config_approval_namespace;
 pub mod config_info;
 pub mod config_info_aggr;
 pub mod config_info_beta;
@@ -13,4 +16,7 @@ pub mod permissions;
 pub mod roles;
 pub mod tenant_capacity;
 pub mod tenant_info;
+pub mod user_preference;
 pub mod users;
+pub mod write_lock;
+pub mod write_quota;
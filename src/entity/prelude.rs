@@ -1,5 +1,8 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
+pub use super::audit_record::Entity as AuditRecord;
+pub use super::config_approval::Entity as ConfigApproval;
+pub use super::config_approval_namespace::Entity as ConfigApprovalNamespace;
 pub use super::config_info::Entity as ConfigInfo;
 pub use super::config_info_aggr::Entity as ConfigInfoAggr;
 pub use super::config_info_beta::Entity as ConfigInfoBeta;
@@ -11,4 +14,7 @@ pub use super::permissions::Entity as Permissions;
 pub use super::roles::Entity as Roles;
 pub use super::tenant_capacity::Entity as TenantCapacity;
 pub use super::tenant_info::Entity as TenantInfo;
+pub use super::user_preference::Entity as UserPreference;
 pub use super::users::Entity as Users;
+pub use super::write_lock::Entity as WriteLock;
+pub use super::write_quota::Entity as WriteQuota;
@@ -2,4 +2,5 @@ pub mod console;
 pub mod entity;
 pub mod middleware;
 pub mod model;
+pub mod server;
 pub mod service;
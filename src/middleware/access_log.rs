@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Ready},
+    time::Instant,
+};
+
+use actix_service::forward_ready;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::{self, Data},
+    Error, HttpMessage,
+};
+use chrono::Utc;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    model::{auth::NacosJwtPayload, common::AppState},
+    service::access_log::AccessLogEntry,
+};
+
+// Writes a sampled structured access log entry per request, see AccessLogManager
+pub struct AccessLog;
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AccessLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogMiddleware { service }))
+    }
+}
+
+pub struct AccessLogMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<Data<AppState>>().unwrap().clone();
+
+        if !app_state.access_log_manager.is_enabled() {
+            return Box::pin(self.service.call(req));
+        }
+
+        let start = Instant::now();
+        let res = self.service.call(req);
+
+        Box::pin(async move {
+            let res = res.await?;
+            let request = res.request();
+            let status = res.status();
+            let path = request
+                .path()
+                .strip_prefix(app_state.context_path.as_str())
+                .unwrap_or(request.path());
+
+            if status.is_server_error() || app_state.access_log_manager.sample(path) {
+                // v1 and v2 APIs name the namespace tenant or namespaceId
+                let query =
+                    web::Query::<HashMap<String, String>>::from_query(request.query_string())
+                        .map(|query| query.into_inner())
+                        .unwrap_or_default();
+
+                app_state.access_log_manager.write(&AccessLogEntry {
+                    time: Utc::now().to_rfc3339(),
+                    method: request.method().to_string(),
+                    path: request.path().to_string(),
+                    status: status.as_u16(),
+                    latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+                    client_ip: request
+                        .connection_info()
+                        .realip_remote_addr()
+                        .unwrap_or_default()
+                        .to_string(),
+                    user: request
+                        .extensions()
+                        .get::<NacosJwtPayload>()
+                        .map(|token_data| token_data.sub.clone())
+                        .unwrap_or_default(),
+                    namespace: query
+                        .get("tenant")
+                        .or_else(|| query.get("namespaceId"))
+                        .cloned()
+                        .unwrap_or_default(),
+                });
+            }
+
+            Ok(res)
+        })
+    }
+}
@@ -1,28 +1,33 @@
+use std::collections::HashMap;
+
 use actix_service::forward_ready;
 use actix_utils::future::{ok, Ready};
 use actix_web::{
     body::EitherBody,
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
     http::Method,
-    web::Data,
-    Error, HttpMessage, HttpResponse,
+    web::{self, Data},
+    Error, HttpMessage, HttpRequest, HttpResponse,
 };
 use chrono::Utc;
 use futures_core::future::LocalBoxFuture;
 
-use crate::{
-    model::common::{AppState, ErrorResult},
-    service,
+use crate::model::{
+    auth::LOGIN_PATH,
+    common::{AppState, ErrorResult},
 };
 
-const IGNORE_ROUTES: [&str; 4] = [
-    "/v1/auth/users/login",
+// Matched exactly, paths below these like /metrics/cluster still need a token
+const IGNORE_ROUTES: [&str; 6] = [
+    LOGIN_PATH,
     "/v1/console/server/state",
     "/v1/console/server/announcement",
     "/v1/console/server/guide",
+    "/v3/api-docs",
+    "/actuator/prometheus",
 ];
 
-const ACCESS_TOKEN: &str = "accessToken";
+pub const ACCESS_TOKEN: &str = "accessToken";
 
 pub struct Authentication;
 
@@ -60,58 +65,43 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let app_state = req.app_data::<Data<AppState>>().unwrap();
+        let app_state = req.app_data::<Data<AppState>>().unwrap().clone();
         let context_path = app_state.context_path.as_str();
-        let mut authenticate_pass: bool;
-
-        if Method::OPTIONS == *req.method() {
-            authenticate_pass = true;
-        } else {
-            authenticate_pass = IGNORE_ROUTES.iter().any(|ignore_route| {
-                let path = format!("{}{}", &context_path, ignore_route);
-
-                req.path().starts_with(&path)
-            });
-        }
-
-        if !authenticate_pass {
-            if let Some(authen_header) = req.headers().get(ACCESS_TOKEN) {
-                if let Ok(authen_str) = authen_header.to_str() {
-                    let token = authen_str.trim();
-                    let secret_key = req
-                        .app_data::<Data<AppState>>()
-                        .unwrap()
-                        .token_secret_key
-                        .clone();
-
-                    let decode_result = service::auth::decode_jwt_token(token, &secret_key);
-
-                    match decode_result {
-                        Ok(token_data) => {
-                            authenticate_pass = true;
-                            req.extensions_mut().insert(token_data.claims);
-                        }
-                        Err(err) => {
-                            let err_msg = match err.kind() {
-                                jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
-                                    "token expired!"
-                                }
-                                _ => "token invalid!",
-                            };
-                            let (request, _pl) = req.into_parts();
-                            let response = HttpResponse::Forbidden()
-                                .json(ErrorResult {
-                                    timestamp: Utc::now().to_rfc3339(),
-                                    status: 403,
-                                    message: err_msg.to_string(),
-                                    error: String::from("Forbiden"),
-                                    path: request.path().to_string(),
-                                })
-                                .map_into_right_body();
-
-                            return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
-                        }
-                    }
+        let path = req.path().strip_prefix(context_path).unwrap_or_default();
+        let mut authenticate_pass = Method::OPTIONS == *req.method()
+            || !app_state.auth_manager.is_required(path)
+            || IGNORE_ROUTES.contains(&path);
+
+        // a supplied token is decoded even where none is required, so the handlers still know
+        // the user behind the request. Browsers cannot set headers on a WebSocket handshake, so
+        // the query parameter works too
+        if let Some(authen_str) = access_token(req.request()) {
+            let token = authen_str.trim();
+            let decode_result = app_state.auth_manager.decode_token(token);
+
+            match decode_result {
+                Ok(token_data) => {
+                    authenticate_pass = true;
+                    req.extensions_mut().insert(token_data.claims);
+                }
+                Err(_) if authenticate_pass => {}
+                Err(err) => {
+                    let err_msg = match err.kind() {
+                        jsonwebtoken::errors::ErrorKind::ExpiredSignature => "token expired!",
+                        _ => "token invalid!",
+                    };
+                    let (request, _pl) = req.into_parts();
+                    let response = HttpResponse::Forbidden()
+                        .json(ErrorResult {
+                            timestamp: Utc::now().to_rfc3339(),
+                            status: 403,
+                            message: err_msg.to_string(),
+                            error: String::from("Forbiden"),
+                            path: request.path().to_string(),
+                        })
+                        .map_into_right_body();
+
+                    return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
                 }
             }
         }
@@ -136,3 +126,15 @@ where
         Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) })
     }
 }
+
+pub fn access_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(ACCESS_TOKEN)
+        .and_then(|authen_header| authen_header.to_str().ok())
+        .map(String::from)
+        .or_else(|| {
+            web::Query::<HashMap<String, String>>::from_query(req.query_string())
+                .ok()
+                .and_then(|mut query| query.remove(ACCESS_TOKEN))
+        })
+}
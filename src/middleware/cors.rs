@@ -0,0 +1,184 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        Method,
+    },
+    web::Data,
+    Error, HttpResponse,
+};
+use chrono::Utc;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    model::{
+        auth::ApiType,
+        common::{AppState, ErrorResult},
+    },
+    service::cors::CorsPolicy,
+};
+
+pub struct Cors;
+
+impl<S, B> Transform<S, ServiceRequest> for Cors
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CorsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CorsMiddleware { service }))
+    }
+}
+
+pub struct CorsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for CorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<Data<AppState>>().unwrap().clone();
+        let path = req
+            .path()
+            .strip_prefix(app_state.context_path.as_str())
+            .unwrap_or_default();
+        let origin = header_str(req.headers(), header::ORIGIN);
+        let policy = origin.as_ref().and_then(|_| {
+            app_state
+                .cors_manager
+                .policy(ApiType::from_path(path))
+                .cloned()
+        });
+
+        let (origin, policy) = match (origin, policy) {
+            (Some(origin), Some(policy)) => (origin, policy),
+            _ => {
+                let res = self.service.call(req);
+
+                return Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) });
+            }
+        };
+
+        let request_method = header_str(req.headers(), header::ACCESS_CONTROL_REQUEST_METHOD);
+
+        // preflights are answered here so they never reach authentication
+        if req.method() == Method::OPTIONS && request_method.is_some() {
+            let (request, _pl) = req.into_parts();
+
+            if !policy.allows_origin(&origin)
+                || !policy.allows_method(request_method.as_deref().unwrap_or_default())
+            {
+                let response = HttpResponse::Forbidden()
+                    .json(ErrorResult {
+                        timestamp: Utc::now().to_rfc3339(),
+                        status: 403,
+                        message: format!("cors request from {} is not allowed!", origin),
+                        error: String::from("Forbiden"),
+                        path: request.path().to_string(),
+                    })
+                    .map_into_right_body();
+
+                return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+            }
+
+            let mut response = HttpResponse::Ok().finish().map_into_right_body();
+            let headers = response.headers_mut();
+
+            insert_origin_headers(headers, &policy, &origin);
+            insert_header(
+                headers,
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                &policy.allowed_methods.join(","),
+            );
+
+            if let Some(allow_headers) = policy.allow_headers_value(
+                header_str(request.headers(), header::ACCESS_CONTROL_REQUEST_HEADERS).as_deref(),
+            ) {
+                insert_header(
+                    headers,
+                    header::ACCESS_CONTROL_ALLOW_HEADERS,
+                    &allow_headers,
+                );
+            }
+
+            insert_header(
+                headers,
+                header::ACCESS_CONTROL_MAX_AGE,
+                &policy.max_age.to_string(),
+            );
+
+            return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+        }
+
+        let res = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = res.await?;
+
+            // a disallowed origin just gets no cors headers and the browser blocks the response
+            if policy.allows_origin(&origin) {
+                let headers = res.headers_mut();
+
+                insert_origin_headers(headers, &policy, &origin);
+
+                if !policy.exposed_headers.is_empty() {
+                    insert_header(
+                        headers,
+                        header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                        &policy.exposed_headers.join(","),
+                    );
+                }
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|e| e.to_str().ok())
+        .map(String::from)
+}
+
+fn insert_header(headers: &mut HeaderMap, name: header::HeaderName, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        headers.insert(name, value);
+    }
+}
+
+fn insert_origin_headers(headers: &mut HeaderMap, policy: &CorsPolicy, origin: &str) {
+    insert_header(
+        headers,
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        &policy.allow_origin_value(origin),
+    );
+
+    if policy.allow_credentials {
+        insert_header(headers, header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+    }
+
+    headers.append(header::VARY, HeaderValue::from_static("Origin"));
+}
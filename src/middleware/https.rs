@@ -0,0 +1,87 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    Error, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+
+// Sends requests arriving on a plain http listener to the https port
+pub struct HttpsRedirect {
+    pub https_port: Option<u16>,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for HttpsRedirect
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = HttpsRedirectMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HttpsRedirectMiddleware {
+            service,
+            https_port: self.https_port,
+        }))
+    }
+}
+
+pub struct HttpsRedirectMiddleware<S> {
+    service: S,
+    https_port: Option<u16>,
+}
+
+impl<S, B> Service<ServiceRequest> for HttpsRedirectMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(https_port) = self.https_port.filter(|_| !req.app_config().secure()) {
+            let host = req.connection_info().host().to_string();
+            // strip the port of the plain listener, bracketed ipv6 hosts keep their colons
+            let host = match host.rsplit_once(':') {
+                Some((name, port)) if !port.contains(']') => name.to_string(),
+                _ => host,
+            };
+            let authority = match https_port {
+                443 => host,
+                port => format!("{}:{}", host, port),
+            };
+            let location = format!(
+                "https://{}{}",
+                authority,
+                req.uri()
+                    .path_and_query()
+                    .map_or(req.path(), |path_and_query| path_and_query.as_str())
+            );
+
+            let (request, _pl) = req.into_parts();
+            let response = HttpResponse::PermanentRedirect()
+                .insert_header((header::LOCATION, location))
+                .finish()
+                .map_into_right_body();
+
+            return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+        }
+
+        let res = self.service.call(req);
+
+        Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
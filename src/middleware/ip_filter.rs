@@ -0,0 +1,87 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::Data,
+    Error, HttpResponse,
+};
+use chrono::Utc;
+use futures_core::future::LocalBoxFuture;
+
+use crate::model::{
+    auth::ApiType,
+    common::{AppState, ErrorResult},
+};
+
+pub struct IpFilter;
+
+impl<S, B> Transform<S, ServiceRequest> for IpFilter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = IpFilterMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IpFilterMiddleware { service }))
+    }
+}
+
+pub struct IpFilterMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for IpFilterMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<Data<AppState>>().unwrap();
+        let path = req
+            .path()
+            .strip_prefix(app_state.context_path.as_str())
+            .unwrap_or_default();
+
+        // the peer address is used on purpose, forwarded headers can be forged by clients
+        if let Some(peer_addr) = req.peer_addr() {
+            let ip = peer_addr.ip();
+
+            if !app_state
+                .ip_filter_manager
+                .is_allowed(ApiType::from_path(path), &ip)
+            {
+                let (request, _pl) = req.into_parts();
+                let response = HttpResponse::Forbidden()
+                    .json(ErrorResult {
+                        timestamp: Utc::now().to_rfc3339(),
+                        status: 403,
+                        message: format!("ip {} is not allowed!", ip),
+                        error: String::from("Forbiden"),
+                        path: request.path().to_string(),
+                    })
+                    .map_into_right_body();
+
+                return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+            }
+        }
+
+        let res = self.service.call(req);
+
+        Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
@@ -0,0 +1,90 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::Method,
+    web::Data,
+    Error, HttpResponse,
+};
+use chrono::Utc;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    model::{
+        auth::LOGIN_PATH,
+        common::{AppState, ErrorResult},
+    },
+    service::{self, maintenance::MAINTENANCE_PATH},
+};
+
+// Rejects write requests while the local node isolated itself or is drained for maintenance
+pub struct SelfIsolation;
+
+impl<S, B> Transform<S, ServiceRequest> for SelfIsolation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SelfIsolationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SelfIsolationMiddleware { service }))
+    }
+}
+
+pub struct SelfIsolationMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for SelfIsolationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let read_only = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+        let app_state = req.app_data::<Data<AppState>>().unwrap();
+        // a node in maintenance is not healthy on purpose and must still be able to leave it,
+        // which takes a token from the login first
+        let exempt = req
+            .path()
+            .strip_prefix(app_state.context_path.as_str())
+            .is_some_and(|path| path.starts_with(MAINTENANCE_PATH) || path == LOGIN_PATH);
+
+        if !read_only
+            && !exempt
+            && service::health::refuses_writes(app_state.member_manager.get_self().state)
+        {
+            let (request, _pl) = req.into_parts();
+            let response = HttpResponse::ServiceUnavailable()
+                .json(ErrorResult {
+                    timestamp: Utc::now().to_rfc3339(),
+                    status: 503,
+                    message: String::from("server is isolated, write is not allowed!"),
+                    error: String::from("Service Unavailable"),
+                    path: request.path().to_string(),
+                })
+                .map_into_right_body();
+
+            return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+        }
+
+        let res = self.service.call(req);
+
+        Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
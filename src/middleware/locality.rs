@@ -0,0 +1,61 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::Data,
+    Error, HttpMessage,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::model::common::AppState;
+
+// Attaches the region / zone labels of the client to the request extensions
+pub struct ClientLocality;
+
+impl<S, B> Transform<S, ServiceRequest> for ClientLocality
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ClientLocalityMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ClientLocalityMiddleware { service }))
+    }
+}
+
+pub struct ClientLocalityMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ClientLocalityMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<Data<AppState>>().unwrap();
+
+        if let Some(locality) = req
+            .peer_addr()
+            .and_then(|peer_addr| app_state.locality_manager.resolve(&peer_addr.ip()))
+        {
+            req.extensions_mut().insert(locality);
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
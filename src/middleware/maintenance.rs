@@ -0,0 +1,63 @@
+use std::future::{ready, Ready};
+
+use actix_service::forward_ready;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::Data,
+    Error,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::model::common::AppState;
+
+// Keeps count of the requests in flight so maintenance mode knows when the node is drained
+pub struct InFlight;
+
+impl<S, B> Transform<S, ServiceRequest> for InFlight
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = InFlightMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(InFlightMiddleware { service }))
+    }
+}
+
+pub struct InFlightMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for InFlightMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<Data<AppState>>().unwrap();
+        let guard = app_state.maintenance_manager.track();
+
+        let res = self.service.call(req);
+
+        Box::pin(async move {
+            let res = res.await;
+
+            drop(guard);
+
+            res
+        })
+    }
+}
@@ -1 +1,8 @@
+pub mod access_log;
 pub mod auth;
+pub mod cors;
+pub mod https;
+pub mod ip_filter;
+pub mod isolation;
+pub mod locality;
+pub mod maintenance;
@@ -1,12 +1,60 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::entity;
 
 pub const DEFAULT_TOKEN_EXPIRE_SECONDS: i64 = 1800;
 pub const GLOBAL_ADMIN_ROLE: &str = "ROLE_ADMIN";
 pub const DEFAULT_USER: &str = "nacos";
+pub const AUTH_ADMIN_PATH: &str = "/v1/core/auth";
+pub const LOGIN_PATH: &str = "/v1/auth/users/login";
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiType {
+    AdminApi,
+    ConsoleApi,
+    OpenApi,
+}
+
+impl ApiType {
+    pub const ALL: [ApiType; 3] = [ApiType::AdminApi, ApiType::ConsoleApi, ApiType::OpenApi];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "ADMIN_API" | "ADMIN" => Some(ApiType::AdminApi),
+            "CONSOLE_API" | "CONSOLE" => Some(ApiType::ConsoleApi),
+            "OPEN_API" | "OPEN" => Some(ApiType::OpenApi),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ApiType::AdminApi => "admin",
+            ApiType::ConsoleApi => "console",
+            ApiType::OpenApi => "open",
+        }
+    }
+
+    // Classify a request path, without the context path
+    pub fn from_path(path: &str) -> Self {
+        if path.starts_with("/v1/core") || path.starts_with("/v3/admin") {
+            ApiType::AdminApi
+        } else if path.starts_with("/v1/console")
+            || path.starts_with("/v2/console")
+            || path.starts_with("/v1/auth")
+            || path.starts_with("/v3/auth")
+            || path.starts_with("/v3/console")
+        {
+            ApiType::ConsoleApi
+        } else {
+            ApiType::OpenApi
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct User {
     pub username: String,
     pub password: String,
@@ -37,7 +85,7 @@ pub struct NacosJwtPayload {
     pub exp: i64,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RoleInfo {
     pub role: String,
@@ -53,7 +101,7 @@ impl From<entity::roles::Model> for RoleInfo {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PermissionInfo {
     pub role: String,
@@ -70,3 +118,19 @@ impl From<entity::permissions::Model> for PermissionInfo {
         }
     }
 }
+
+// A resource is namespace:group:type/name like public:DEFAULT_GROUP:config/app.yml, action r or w
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCheck {
+    pub resource: String,
+    pub action: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCheckResult {
+    pub resource: String,
+    pub action: String,
+    pub allowed: bool,
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum NodeState {
     Starting,
@@ -12,13 +13,18 @@ pub enum NodeState {
     Isolation,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub const DEFAULT_SERVER_PORT: i32 = 8848;
+pub const RAFT_PORT: &str = "raftPort";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct Member {
     pub ip: String,
     pub port: i32,
     pub state: NodeState,
+    #[serde(alias = "extendInfo")]
     pub extend_info: BTreeMap<String, serde_json::Value>,
     pub address: String,
+    #[serde(alias = "failAccessCnt")]
     pub fail_access_cnt: i32,
 }
 
@@ -43,3 +49,116 @@ impl Member {
         }
     }
 }
+
+impl Member {
+    // Parse a member from `ip[:port][?raft_port=xxx]`, the format used by cluster.conf and nacos.member.list.
+    // An IPv6 address takes a port only in the `[ip]:port` form
+    pub fn from_address(address: &str) -> Self {
+        let mut member = Member::new();
+        let (address, params) = match address.trim().split_once('?') {
+            Some((address, params)) => (address, Some(params)),
+            None => (address.trim(), None),
+        };
+        let (ip, port) = match address.strip_prefix('[') {
+            Some(rest) => match rest.split_once(']') {
+                Some((ip, port)) => (ip, port.strip_prefix(':')),
+                None => (rest, None),
+            },
+            // more than one colon is a bare IPv6 address
+            None if address.matches(':').count() > 1 => (address, None),
+            None => match address.split_once(':') {
+                Some((ip, port)) => (ip, Some(port)),
+                None => (address, None),
+            },
+        };
+        let port = port
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(DEFAULT_SERVER_PORT);
+
+        member.ip = ip.to_string();
+        member.port = port;
+        member.address = format_address(ip, port);
+
+        if let Some(params) = params {
+            params
+                .split('&')
+                .filter_map(|param| param.split_once('='))
+                .filter(|(key, _)| *key == "raft_port")
+                .for_each(|(_, value)| {
+                    if let Ok(raft_port) = value.parse::<i32>() {
+                        member
+                            .extend_info
+                            .insert(String::from(RAFT_PORT), serde_json::Value::from(raft_port));
+                    }
+                });
+        }
+
+        member
+    }
+}
+
+// ip:port, with the brackets an IPv6 address needs to tell it from the port
+pub fn format_address(ip: &str, port: i32) -> String {
+    if ip.contains(':') {
+        format!("[{}]:{}", ip, port)
+    } else {
+        format!("{}:{}", ip, port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_address_parses_ipv4_and_hostnames() {
+        let member = Member::from_address("10.0.0.1:8849?raft_port=7849");
+
+        assert_eq!(member.ip, "10.0.0.1");
+        assert_eq!(member.port, 8849);
+        assert_eq!(member.address, "10.0.0.1:8849");
+        assert_eq!(member.extend_info[RAFT_PORT], 7849);
+
+        let member = Member::from_address("nacos-0.nacos");
+
+        assert_eq!(member.port, DEFAULT_SERVER_PORT);
+        assert_eq!(member.address, "nacos-0.nacos:8848");
+    }
+
+    #[test]
+    fn from_address_parses_ipv6() {
+        let member = Member::from_address("[fe80::1]:8849");
+
+        assert_eq!(member.ip, "fe80::1");
+        assert_eq!(member.port, 8849);
+        assert_eq!(member.address, "[fe80::1]:8849");
+
+        for address in ["fe80::1", "[fe80::1]"] {
+            let member = Member::from_address(address);
+
+            assert_eq!(member.ip, "fe80::1");
+            assert_eq!(member.port, DEFAULT_SERVER_PORT);
+            assert_eq!(member.address, "[fe80::1]:8848");
+        }
+    }
+
+    #[test]
+    fn member_keeps_its_serialized_field_names() {
+        let json = serde_json::to_value(Member::from_address("10.0.0.1")).unwrap();
+
+        assert!(json.get("extend_info").is_some());
+        assert!(json.get("fail_access_cnt").is_some());
+
+        let member: Member = serde_json::from_value(serde_json::json!({
+            "ip": "10.0.0.1",
+            "port": 8848,
+            "state": "UP",
+            "extendInfo": {},
+            "address": "10.0.0.1:8848",
+            "failAccessCnt": 2,
+        }))
+        .unwrap();
+
+        assert_eq!(member.fail_access_cnt, 2);
+    }
+}
@@ -1,9 +1,24 @@
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, HttpResponse};
 use config::Config;
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, DbErr};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::service::{
+    access_log::AccessLogManager, activity::ActivityManager, approval::ApprovalManager,
+    audit::AuditManager, auth::AuthManager, capacity::CapacityPolicy, cluster::ServerMemberManager,
+    cors::CorsManager, crypto::ContentCipher, db_pool::PoolMonitor, event::EventBus,
+    idempotency::IdempotencyManager, ip_filter::IpFilterManager, ldap::LdapAuthProvider,
+    locality::LocalityManager, maintenance::MaintenanceManager, mask::MaskManager,
+    md5_sweep::Md5Sweeper, namespace::DeleteConfirmationManager, reload::ConfigReloader,
+    sync::SyncManager, watch::WatchRegistry, write_lock::WriteLockManager,
+    write_quota::WriteQuotaManager,
+};
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct RestResult<T> {
     pub code: i32,
     pub message: String,
@@ -20,7 +35,20 @@ impl<T> RestResult<T> {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl RestResult<String> {
+    // v1 envelope, the code is the http status
+    pub fn http_error(err: &anyhow::Error) -> HttpResponse {
+        let (status, _) = error_mapping(err);
+
+        HttpResponse::build(status).json(RestResult::<String> {
+            code: status.as_u16() as i32,
+            message: err.to_string(),
+            data: err.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Page<T> {
     pub total_count: u64,
@@ -55,6 +83,79 @@ impl<T> Page<T> {
 pub enum BusinessError {
     #[error("user '{0}' not exist!")]
     UserNotExist(String),
+    #[error("user '{0}' already exist!")]
+    UserAlreadyExist(String),
+    #[error("required parameter '{0}' is missing")]
+    ParameterMissing(String),
+    #[error("{0}")]
+    ParameterValidate(String),
+    #[error("{0}")]
+    AccessDenied(String),
+    #[error("{0}")]
+    ResourceNotFound(String),
+    #[error("{0}")]
+    ResourceConflict(String),
+    #[error("{0}")]
+    ResourceLocked(String),
+    #[error("namespace '{0}' already exist!")]
+    NamespaceAlreadyExist(String),
+    #[error("namespace '{0}' not exist!")]
+    NamespaceNotExist(String),
+    #[error("{0}")]
+    IllegalState(String),
+    #[error("{0}")]
+    CapacityExceeded(String),
+}
+
+impl BusinessError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            BusinessError::UserNotExist(_)
+            | BusinessError::UserAlreadyExist(_)
+            | BusinessError::ParameterMissing(_)
+            | BusinessError::ParameterValidate(_)
+            | BusinessError::NamespaceAlreadyExist(_) => StatusCode::BAD_REQUEST,
+            BusinessError::AccessDenied(_) => StatusCode::FORBIDDEN,
+            BusinessError::ResourceNotFound(_) | BusinessError::NamespaceNotExist(_) => {
+                StatusCode::NOT_FOUND
+            }
+            BusinessError::ResourceConflict(_) => StatusCode::CONFLICT,
+            BusinessError::ResourceLocked(_) => StatusCode::LOCKED,
+            BusinessError::IllegalState(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            BusinessError::CapacityExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    pub fn error_code(&self) -> ErrorCode<'static> {
+        match self {
+            BusinessError::UserNotExist(_) => RESOURCE_NOT_FOUND,
+            BusinessError::UserAlreadyExist(_) => RESOURCE_CONFLICT,
+            BusinessError::ParameterMissing(_) => PARAMETER_MISSING,
+            BusinessError::ParameterValidate(_) => PARAMETER_VALIDATE_ERROR,
+            BusinessError::AccessDenied(_) => ACCESS_DENIED,
+            BusinessError::ResourceNotFound(_) => RESOURCE_NOT_FOUND,
+            BusinessError::ResourceConflict(_) | BusinessError::ResourceLocked(_) => {
+                RESOURCE_CONFLICT
+            }
+            BusinessError::NamespaceAlreadyExist(_) => NAMESPACE_ALREADY_EXIST,
+            BusinessError::NamespaceNotExist(_) => NAMESPACE_NOT_EXIST,
+            BusinessError::IllegalState(_) => ILLEGAL_STATE,
+            BusinessError::CapacityExceeded(_) => CONFIG_CAPACITY_EXCEEDED,
+        }
+    }
+}
+
+// Central mapping from service errors to the http status and Nacos error code of the response
+pub fn error_mapping(err: &anyhow::Error) -> (StatusCode, ErrorCode<'static>) {
+    if let Some(business_error) = err.downcast_ref::<BusinessError>() {
+        return (business_error.status(), business_error.error_code());
+    }
+
+    if err.downcast_ref::<DbErr>().is_some() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, DATA_ACCESS_ERROR);
+    }
+
+    (StatusCode::INTERNAL_SERVER_ERROR, SERVER_ERROR)
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -185,7 +286,7 @@ pub const HEALTHY_PARAM_MISS: ErrorCode<'static> = ErrorCode {
 
 pub const HEALTH_CHECK_STILL_RUNNING: ErrorCode<'static> = ErrorCode {
     code: 21011,
-    message: "health check still runnin",
+    message: "health check still running",
 };
 
 pub const ILLEGAL_NAMESPACE: ErrorCode<'static> = ErrorCode {
@@ -193,9 +294,14 @@ pub const ILLEGAL_NAMESPACE: ErrorCode<'static> = ErrorCode {
     message: "illegal namespace",
 };
 
+pub const NAMESPACE_ALREADY_EXIST: ErrorCode<'static> = ErrorCode {
+    code: 22001,
+    message: "namespace already exist",
+};
+
 pub const NAMESPACE_NOT_EXIST: ErrorCode<'static> = ErrorCode {
     code: 22002,
-    message: "namespace already exist",
+    message: "namespace not exist",
 };
 
 pub const ILLEGAL_STATE: ErrorCode<'static> = ErrorCode {
@@ -213,12 +319,18 @@ pub const NODE_DOWN_FAILURE: ErrorCode<'static> = ErrorCode {
     message: "node down failure",
 };
 
+// Not a Nacos code, the range is left unused by Nacos
+pub const CONFIG_CAPACITY_EXCEEDED: ErrorCode<'static> = ErrorCode {
+    code: 24000,
+    message: "config capacity exceeded",
+};
+
 pub const SERVER_ERROR: ErrorCode<'static> = ErrorCode {
     code: 30000,
     message: "server error",
 };
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct Result<T> {
     pub code: i32,
     pub message: String,
@@ -235,15 +347,51 @@ impl<T> Result<T> {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+impl Result<String> {
+    // v2 envelope, the code is the Nacos error code and the data carries the detail
+    pub fn http_error(err: &anyhow::Error) -> HttpResponse {
+        let (status, error_code) = error_mapping(err);
+
+        HttpResponse::build(status).json(Result::<String> {
+            code: error_code.code,
+            message: error_code.message.to_string(),
+            data: err.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct AppState {
     pub app_config: Config,
     pub database_connection: DatabaseConnection,
+    pub content_cipher: Arc<ContentCipher>,
     pub context_path: String,
-    pub token_secret_key: String,
+    pub auth_manager: Arc<AuthManager>,
+    pub member_manager: Arc<ServerMemberManager>,
+    pub write_quota_manager: Arc<WriteQuotaManager>,
+    pub ip_filter_manager: Arc<IpFilterManager>,
+    pub delete_confirmation_manager: Arc<DeleteConfirmationManager>,
+    pub approval_manager: Arc<ApprovalManager>,
+    pub write_lock_manager: Arc<WriteLockManager>,
+    pub mask_manager: Arc<MaskManager>,
+    pub locality_manager: Arc<LocalityManager>,
+    pub idempotency_manager: Arc<IdempotencyManager>,
+    pub pool_monitor: Arc<PoolMonitor>,
+    pub activity_manager: Arc<ActivityManager>,
+    pub cors_manager: Arc<CorsManager>,
+    pub maintenance_manager: Arc<MaintenanceManager>,
+    pub event_bus: Arc<EventBus>,
+    pub ldap_auth_provider: Option<Arc<LdapAuthProvider>>,
+    pub capacity_policy: Arc<CapacityPolicy>,
+    pub audit_manager: Arc<AuditManager>,
+    pub access_log_manager: Arc<AccessLogManager>,
+    pub sync_manager: Arc<SyncManager>,
+    pub config_reloader: Arc<ConfigReloader>,
+    pub md5_sweeper: Arc<Md5Sweeper>,
+    pub watch_registry: Arc<WatchRegistry>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResult {
     pub timestamp: String,
     pub status: i32,
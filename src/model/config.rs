@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::entity;
 
@@ -14,7 +15,7 @@ pub struct ConfigBase {
     pub encrypted_data_key: String,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigInfo {
     pub id: i64,
@@ -44,7 +45,7 @@ impl From<entity::config_info::Model> for ConfigInfo {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigInfoWrapper {
     pub id: i64,
@@ -76,7 +77,7 @@ impl From<entity::config_info::Model> for ConfigInfoWrapper {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigAllInfo {
     pub id: i64,
@@ -148,7 +149,7 @@ impl From<entity::config_info::Model> for ConfigInfoStateWrapper {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigHistoryInfo {
     pub id: u64,
@@ -187,3 +188,125 @@ impl From<entity::his_config_info::Model> for ConfigHistoryInfo {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConfigSortBy {
+    #[default]
+    Id,
+    DataId,
+    Group,
+    AppName,
+    ModifiedTime,
+}
+
+impl ConfigSortBy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "" | "id" => Some(ConfigSortBy::Id),
+            "dataId" => Some(ConfigSortBy::DataId),
+            "group" => Some(ConfigSortBy::Group),
+            "appName" => Some(ConfigSortBy::AppName),
+            "modifiedTime" | "lastModified" => Some(ConfigSortBy::ModifiedTime),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HistoryAuditQuery {
+    pub tenant: String,
+    pub start_time: Option<NaiveDateTime>,
+    pub end_time: Option<NaiveDateTime>,
+    pub src_user: String,
+    pub op_type: String,
+    pub cursor: Option<u64>,
+    pub page_size: u64,
+}
+
+// A page of history records, newest first, next_cursor continues after the last record
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryAuditPage {
+    pub page_items: Vec<ConfigHistoryInfo>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishOperation {
+    #[default]
+    Create,
+    Update,
+    Unchanged,
+}
+
+// What a publish would do, returned by a dry run instead of writing anything
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishReport {
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub md5: String,
+    pub operation: PublishOperation,
+    pub violations: Vec<String>,
+    pub quota_retry_after: Option<i64>,
+    pub requires_approval: bool,
+    pub would_publish: bool,
+}
+
+// What restoring a config to the history entry nid does, diff is a unified diff from the current
+// content to the restored one
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub nid: u64,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub md5: String,
+    pub operation: PublishOperation,
+    pub diff: String,
+    pub violations: Vec<String>,
+    pub requires_approval: bool,
+    pub rolled_back: bool,
+}
+
+// Where config content breaks a schema bound to its dataId, path is a JSON pointer into the content
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaViolation {
+    pub schema: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.schema, self.path, self.message)
+    }
+}
+
+// A config publish as submitted by a client, kept as is while it waits for approval
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub content: String,
+    pub tag: String,
+    pub app_name: String,
+    pub src_user: String,
+    pub src_ip: String,
+    pub config_tags: String,
+    pub desc: String,
+    pub r#use: String,
+    pub effect: String,
+    pub r#type: String,
+    pub schema: String,
+    pub encrypted_data_key: String,
+    // history op_type of the content it replaces once applied, empty for an update
+    #[serde(default)]
+    pub op_type: String,
+}
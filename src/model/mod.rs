@@ -3,3 +3,4 @@ pub mod cluster;
 pub mod common;
 pub mod config;
 pub mod naming;
+pub mod validation;
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::entity;
 
 const DEFAULT_NAMESPACE_QUOTA: i32 = 200;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Namespace {
     pub namespace: String,
@@ -40,3 +41,16 @@ impl From<entity::tenant_info::Model> for Namespace {
         }
     }
 }
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceDeleteReport {
+    pub namespace_id: String,
+    pub config_count: u64,
+    // beta and tag versions of the configs, deleted with them
+    pub beta_count: u64,
+    pub tag_count: u64,
+    pub permission_count: u64,
+    pub confirm_token: String,
+    pub expire_time: i64,
+}
@@ -0,0 +1,158 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::model::{common::BusinessError, config::ConfigChange};
+
+pub const DATA_ID_MAX_LENGTH: usize = 256;
+pub const GROUP_MAX_LENGTH: usize = 128;
+pub const TENANT_MAX_LENGTH: usize = 128;
+pub const APP_NAME_MAX_LENGTH: usize = 128;
+pub const TAG_MAX_LENGTH: usize = 64;
+pub const CONFIG_TAGS_MAX_COUNT: usize = 5;
+pub const DESC_MAX_LENGTH: usize = 128;
+pub const USE_MAX_LENGTH: usize = 32;
+pub const EFFECT_MAX_LENGTH: usize = 32;
+pub const TYPE_MAX_LENGTH: usize = 32;
+pub const SCHEMA_MAX_LENGTH: usize = 32768;
+pub const CONTENT_MAX_LENGTH: usize = 10 * 1024 * 1024;
+pub const NAMESPACE_ID_MAX_LENGTH: usize = 128;
+pub const NAMESPACE_NAME_MAX_LENGTH: usize = 128;
+
+// Parameter rules of the Nacos open api, checked before anything is written
+
+static NAMESPACE_ID_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[\w-]+$").unwrap());
+static NAMESPACE_NAME_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[^@#$%^&*]+$").unwrap());
+
+// Letters, digits and _-.: like ParamUtils.isValid of Nacos
+pub fn is_valid(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+fn invalid(field: &str, reason: String) -> anyhow::Error {
+    BusinessError::ParameterValidate(format!("invalid {}: {}", field, reason)).into()
+}
+
+fn check_length(field: &str, value: &str, max_length: usize) -> anyhow::Result<()> {
+    if value.chars().count() > max_length {
+        return Err(invalid(
+            field,
+            format!("longer than {} characters", max_length),
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_key(field: &str, value: &str, max_length: usize, required: bool) -> anyhow::Result<()> {
+    if value.trim().is_empty() {
+        if required {
+            return Err(BusinessError::ParameterMissing(field.to_string()).into());
+        }
+
+        return Ok(());
+    }
+
+    check_length(field, value, max_length)?;
+
+    if !is_valid(value) {
+        return Err(invalid(
+            field,
+            format!(
+                "'{}' may only contain letters, digits and _-.: characters",
+                value
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn check_data_id(data_id: &str) -> anyhow::Result<()> {
+    check_key("dataId", data_id, DATA_ID_MAX_LENGTH, true)
+}
+
+pub fn check_group(group: &str) -> anyhow::Result<()> {
+    check_key("group", group, GROUP_MAX_LENGTH, true)
+}
+
+pub fn check_tenant(tenant: &str) -> anyhow::Result<()> {
+    check_key("tenant", tenant, TENANT_MAX_LENGTH, false)
+}
+
+pub fn check_namespace_id(namespace_id: &str) -> anyhow::Result<()> {
+    check_length("namespaceId", namespace_id, NAMESPACE_ID_MAX_LENGTH)?;
+
+    if !NAMESPACE_ID_PATTERN.is_match(namespace_id) {
+        return Err(invalid(
+            "namespaceId",
+            format!(
+                "'{}' may only contain letters, digits, _ and -",
+                namespace_id
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn check_namespace_name(namespace_name: &str) -> anyhow::Result<()> {
+    check_length("namespaceName", namespace_name, NAMESPACE_NAME_MAX_LENGTH)?;
+
+    if !NAMESPACE_NAME_PATTERN.is_match(namespace_name) {
+        return Err(invalid(
+            "namespaceName",
+            format!("'{}' is empty or contains @#$%^&*", namespace_name),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn check_config_change(change: &ConfigChange) -> anyhow::Result<()> {
+    check_data_id(&change.data_id)?;
+    check_group(&change.group)?;
+    check_tenant(&change.tenant)?;
+    check_key("tag", &change.tag, TAG_MAX_LENGTH, false)?;
+    check_key("appName", &change.app_name, APP_NAME_MAX_LENGTH, false)?;
+
+    let config_tags: Vec<&str> = change
+        .config_tags
+        .split(',')
+        .map(|e| e.trim())
+        .filter(|e| !e.is_empty())
+        .collect();
+
+    if config_tags.len() > CONFIG_TAGS_MAX_COUNT {
+        return Err(invalid(
+            "config_tags",
+            format!("more than {} tags", CONFIG_TAGS_MAX_COUNT),
+        ));
+    }
+
+    for config_tag in config_tags {
+        check_length("config_tags", config_tag, TAG_MAX_LENGTH)?;
+    }
+
+    check_length("desc", &change.desc, DESC_MAX_LENGTH)?;
+    check_length("use", &change.r#use, USE_MAX_LENGTH)?;
+    check_length("effect", &change.effect, EFFECT_MAX_LENGTH)?;
+    check_length("type", &change.r#type, TYPE_MAX_LENGTH)?;
+    check_length("schema", &change.schema, SCHEMA_MAX_LENGTH)?;
+
+    if change.content.trim().is_empty() {
+        return Err(BusinessError::ParameterMissing(String::from("content")).into());
+    }
+
+    if change.content.len() > CONTENT_MAX_LENGTH {
+        return Err(invalid(
+            "content",
+            format!("larger than {} bytes", CONTENT_MAX_LENGTH),
+        ));
+    }
+
+    Ok(())
+}
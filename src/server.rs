@@ -0,0 +1,411 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use actix_web::{dev::Server, middleware::Logger, web, App, HttpMessage, HttpServer};
+use config::Config;
+use openssl::ssl::SslAcceptorBuilder;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use tokio::sync::broadcast;
+
+use crate::{
+    console,
+    middleware::{
+        access_log::AccessLog, auth::Authentication, cors::Cors, https::HttpsRedirect,
+        ip_filter::IpFilter, isolation::SelfIsolation, locality::ClientLocality,
+        maintenance::InFlight,
+    },
+    model::common::AppState,
+    service::{
+        self,
+        access_log::AccessLogManager,
+        activity::ActivityManager,
+        approval::ApprovalManager,
+        audit::{AuditManager, AuditPersistence},
+        auth::AuthManager,
+        capacity::CapacityPolicy,
+        cluster::ServerMemberManager,
+        cors::CorsManager,
+        crypto::ContentCipher,
+        db_credential::CredentialFiles,
+        db_pool::PoolMonitor,
+        event::{EventBus, ServerEvent},
+        idempotency::IdempotencyManager,
+        ip_filter::IpFilterManager,
+        ldap::LdapAuthProvider,
+        locality::{Locality, LocalityManager},
+        maintenance::MaintenanceManager,
+        mask::MaskManager,
+        md5_sweep::Md5Sweeper,
+        namespace::DeleteConfirmationManager,
+        reload::{ConfigReloader, LogLevelReloader, ReloadTargets},
+        sync::{SyncContext, SyncManager},
+        watch::WatchRegistry,
+        write_lock::WriteLockManager,
+        write_quota::WriteQuotaManager,
+    },
+};
+
+pub const DEFAULT_CONFIG_FILE: &str = "conf/application.yml";
+
+// Builds the whole server, used by the binary and by programs embedding it
+#[derive(Clone, Debug)]
+pub struct BatataServerBuilder {
+    config_file: String,
+    args: Vec<String>,
+    properties: Vec<(String, String)>,
+    audit_persistence: Option<Arc<dyn AuditPersistence>>,
+    log_level_reloader: Option<Arc<dyn LogLevelReloader>>,
+}
+
+impl Default for BatataServerBuilder {
+    fn default() -> Self {
+        BatataServerBuilder {
+            config_file: String::from(DEFAULT_CONFIG_FILE),
+            args: Vec::new(),
+            properties: Vec::new(),
+            audit_persistence: None,
+            log_level_reloader: None,
+        }
+    }
+}
+
+impl BatataServerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config_file(mut self, path: &str) -> Self {
+        self.config_file = path.to_string();
+        self
+    }
+
+    // --key=value command line arguments, anything else is ignored
+    pub fn args(mut self, args: impl IntoIterator<Item = String>) -> Self {
+        self.args.extend(args);
+        self
+    }
+
+    // Wins over the config file, environment variables and arguments. A server.port of 0 binds
+    // a random port, see RunningServer::addrs
+    pub fn property(mut self, key: &str, value: &str) -> Self {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    // Stores audit records somewhere else than the configured nacos.core.audit.persistence
+    pub fn audit_persistence(mut self, persistence: Arc<dyn AuditPersistence>) -> Self {
+        self.audit_persistence = Some(persistence);
+        self
+    }
+
+    // Lets nacos.core.log.level and its reloads change the log filter of the process
+    pub fn log_level_reloader(mut self, reloader: Arc<dyn LogLevelReloader>) -> Self {
+        self.log_level_reloader = Some(reloader);
+        self
+    }
+
+    pub async fn build(self) -> anyhow::Result<BatataServer> {
+        let args: Vec<String> = self
+            .args
+            .into_iter()
+            .chain(
+                self.properties
+                    .into_iter()
+                    .map(|(key, value)| format!("--{}={}", key, value)),
+            )
+            .collect();
+        let app_config = service::app_config::load(&self.config_file, args.iter().cloned())?;
+        let content_cipher = Arc::new(ContentCipher::from_config(&app_config)?);
+
+        let max_connections = app_config
+            .get_int("db.pool.config.maximumPoolSize")
+            .unwrap_or(100) as u32;
+        let min_connections = app_config
+            .get_int("db.pool.config.minimumIdle")
+            .or_else(|_| app_config.get_int("db.pool.config.minimumPoolSize"))
+            .unwrap_or(1) as u32;
+        let connect_timeout = pool_timeout(&app_config, &["connectionTimeout"], 30);
+        let acquire_timeout = pool_timeout(
+            &app_config,
+            &["acquireTimeout", "initializationFailTimeout"],
+            8,
+        );
+        let idle_timeout = pool_timeout(&app_config, &["idleTimeout"], 10);
+        let max_lifetime = pool_timeout(&app_config, &["maxLifetime"], 30);
+
+        let url = app_config.get_string("db.url")?;
+
+        let mut opt = ConnectOptions::new(url);
+
+        opt.max_connections(max_connections)
+            .min_connections(min_connections)
+            .connect_timeout(connect_timeout)
+            .acquire_timeout(acquire_timeout)
+            .idle_timeout(idle_timeout)
+            .max_lifetime(max_lifetime);
+
+        let credential_files = CredentialFiles::new(&app_config);
+
+        if let Some(credential_files) = &credential_files {
+            credential_files.apply(&mut opt)?;
+        }
+
+        let database_connection: DatabaseConnection = Database::connect(opt).await?;
+
+        if let Some(credential_files) = credential_files {
+            credential_files.start_rotation(database_connection.clone());
+        }
+
+        let pool_monitor = Arc::new(PoolMonitor::new(
+            &app_config,
+            max_connections,
+            database_connection.clone(),
+        ));
+
+        pool_monitor.clone().start();
+        let address = app_config
+            .get_string("server.address")
+            .unwrap_or("0.0.0.0".to_string());
+        let server_port = app_config.get_int("server.port").unwrap_or(8848) as u16;
+        let context_path = app_config
+            .get_string("server.servlet.contextPath")
+            .unwrap_or("/nacos".to_string());
+        let tls_acceptor = service::tls::acceptor(&app_config)?;
+        let http_redirect_port = service::tls::http_redirect_port(&app_config);
+
+        let auth_manager = Arc::new(AuthManager::new(&app_config)?);
+
+        let member_manager = Arc::new(ServerMemberManager::new(&app_config)?);
+
+        member_manager.start().await;
+
+        service::health::start_self_health_check(
+            &app_config,
+            database_connection.clone(),
+            member_manager.clone(),
+        );
+
+        let write_quota_manager = Arc::new(WriteQuotaManager::new(&app_config));
+
+        if let Err(err) = write_quota_manager.load(&database_connection).await {
+            tracing::warn!("load write quotas failed: {}", err);
+        }
+
+        let ip_filter_manager = Arc::new(IpFilterManager::new(&app_config)?);
+        let delete_confirmation_manager = Arc::new(DeleteConfirmationManager::new(
+            &app_config,
+            auth_manager.clone(),
+        ));
+        let write_lock_manager = Arc::new(WriteLockManager::default());
+
+        if let Err(err) = write_lock_manager.load(&database_connection).await {
+            tracing::warn!("load write locks failed: {}", err);
+        }
+
+        write_lock_manager
+            .clone()
+            .start(database_connection.clone());
+
+        let approval_manager = Arc::new(ApprovalManager::new(
+            &app_config,
+            database_connection.clone(),
+            content_cipher.clone(),
+            write_lock_manager.clone(),
+        ));
+
+        if let Err(err) = approval_manager.load().await {
+            tracing::warn!("load protected namespaces failed: {}", err);
+        }
+
+        let mask_manager = Arc::new(MaskManager::new(&app_config)?);
+        let locality_manager = Arc::new(LocalityManager::new(&app_config)?);
+        let idempotency_manager = Arc::new(IdempotencyManager::new(&app_config));
+        let activity_manager = Arc::new(ActivityManager::new(&app_config));
+        let cors_manager = Arc::new(CorsManager::new(&app_config));
+        let maintenance_manager = Arc::new(MaintenanceManager::new(&app_config));
+        let event_bus = Arc::new(EventBus::default());
+        let capacity_policy = Arc::new(CapacityPolicy::new(&app_config));
+        let watch_registry = Arc::new(WatchRegistry::default());
+
+        watch_registry.clone().start(&event_bus);
+
+        let md5_sweeper = Arc::new(Md5Sweeper::new(
+            &app_config,
+            database_connection.clone(),
+            content_cipher.clone(),
+            event_bus.clone(),
+        ));
+
+        md5_sweeper.clone().start();
+        let audit_manager = Arc::new(AuditManager::new(
+            &app_config,
+            database_connection.clone(),
+            self.audit_persistence,
+        )?);
+        let access_log_manager = Arc::new(AccessLogManager::new(&app_config)?);
+        let ldap_auth_provider = LdapAuthProvider::new(&app_config)?.map(Arc::new);
+        let sync_manager = Arc::new(SyncManager::default());
+        let config_reloader = Arc::new(ConfigReloader::new(
+            &self.config_file,
+            args,
+            &app_config,
+            self.log_level_reloader,
+            ReloadTargets {
+                auth_manager: auth_manager.clone(),
+                write_quota_manager: write_quota_manager.clone(),
+                ip_filter_manager: ip_filter_manager.clone(),
+                member_manager: member_manager.clone(),
+            },
+        )?);
+
+        config_reloader.clone().start(&app_config);
+
+        sync_manager.clone().start(SyncContext {
+            db: database_connection.clone(),
+            content_cipher: content_cipher.clone(),
+            capacity_policy: capacity_policy.clone(),
+            event_bus: event_bus.clone(),
+            write_lock_manager: write_lock_manager.clone(),
+            approval_manager: approval_manager.clone(),
+        });
+
+        let app_state = AppState {
+            app_config,
+            database_connection,
+            content_cipher,
+            context_path: context_path.clone(),
+            auth_manager,
+            member_manager,
+            write_quota_manager,
+            ip_filter_manager,
+            delete_confirmation_manager,
+            approval_manager,
+            write_lock_manager,
+            mask_manager,
+            locality_manager,
+            idempotency_manager,
+            pool_monitor,
+            activity_manager,
+            cors_manager,
+            maintenance_manager,
+            event_bus,
+            ldap_auth_provider,
+            capacity_policy,
+            audit_manager,
+            access_log_manager,
+            sync_manager,
+            config_reloader,
+            md5_sweeper,
+            watch_registry,
+        };
+
+        Ok(BatataServer {
+            app_state,
+            address,
+            server_port,
+            tls_acceptor,
+            http_redirect_port,
+        })
+    }
+}
+
+// The pool keys have always been in seconds, the same key with an Ms suffix is in milliseconds
+// and takes precedence. Keys are tried in order
+fn pool_timeout(app_config: &Config, keys: &[&str], default_seconds: u64) -> Duration {
+    keys.iter()
+        .find_map(|key| {
+            let key = format!("db.pool.config.{}", key);
+
+            app_config
+                .get_int(&format!("{}Ms", key))
+                .map(|e| Duration::from_millis(e.max(0) as u64))
+                .or_else(|_| {
+                    app_config
+                        .get_int(&key)
+                        .map(|e| Duration::from_secs(e.max(0) as u64))
+                })
+                .ok()
+        })
+        .unwrap_or(Duration::from_secs(default_seconds))
+}
+
+pub struct BatataServer {
+    app_state: AppState,
+    address: String,
+    server_port: u16,
+    tls_acceptor: Option<SslAcceptorBuilder>,
+    http_redirect_port: Option<u16>,
+}
+
+pub struct RunningServer {
+    pub addrs: Vec<SocketAddr>,
+    pub server: Server,
+}
+
+impl BatataServer {
+    pub fn app_state(&self) -> &AppState {
+        &self.app_state
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.app_state.event_bus.subscribe()
+    }
+
+    // Binds the listener, the returned server has to be awaited or spawned to serve requests
+    pub fn start(self) -> std::io::Result<RunningServer> {
+        let app_state = self.app_state;
+        let context_path = app_state.context_path.clone();
+        // the redirect listener only exists next to an https one
+        let http_redirect_port = self
+            .http_redirect_port
+            .filter(|_| self.tls_acceptor.is_some());
+        let https_port = http_redirect_port.map(|_| self.server_port);
+
+        let server = HttpServer::new(move || {
+            App::new()
+                .wrap(
+                    Logger::new(r#"%a %{locality}xi %{X-Request-Id}i "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+                        .custom_request_replace("locality", |req| {
+                            req.extensions()
+                                .get::<Locality>()
+                                .map_or(String::from("-"), |locality| locality.to_string())
+                        }),
+                )
+                .wrap(Authentication)
+                .wrap(SelfIsolation)
+                .wrap(IpFilter)
+                .wrap(ClientLocality)
+                .wrap(InFlight)
+                .wrap(Cors)
+                .wrap(HttpsRedirect { https_port })
+                // outermost so requests rejected by any other middleware are logged too
+                .wrap(AccessLog)
+                .app_data(web::Data::new(app_state.clone()))
+                .service(
+                    web::scope(&context_path)
+                        .service(console::v1::router::routers())
+                        .service(console::v2::router::routers())
+                        .service(console::v3::router::routers())
+                        .service(console::actuator::routers())
+                        .service(console::actuator::metrics_routers()),
+                )
+        });
+        let server = match self.tls_acceptor {
+            Some(tls_acceptor) => {
+                let server =
+                    server.bind_openssl((self.address.as_str(), self.server_port), tls_acceptor)?;
+
+                match http_redirect_port {
+                    Some(port) => server.bind((self.address.as_str(), port))?,
+                    None => server,
+                }
+            }
+            None => server.bind((self.address, self.server_port))?,
+        };
+        let addrs = server.addrs();
+
+        Ok(RunningServer {
+            addrs,
+            server: server.run(),
+        })
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use config::Config;
+use serde::Serialize;
+use tracing_appender::{
+    non_blocking::{NonBlocking, WorkerGuard},
+    rolling::{RollingFileAppender, Rotation},
+};
+
+use crate::service::ip_filter::split_list;
+
+pub const ACCESS_LOG_ENABLED: &str = "nacos.core.access-log.enabled";
+pub const ACCESS_LOG_DIR: &str = "nacos.core.access-log.dir";
+pub const ACCESS_LOG_ROTATION: &str = "nacos.core.access-log.rotation";
+pub const ACCESS_LOG_SAMPLE_RATE: &str = "nacos.core.access-log.sample-rate";
+pub const ACCESS_LOG_SAMPLE_RATES: &str = "nacos.core.access-log.sample-rates";
+
+const DEFAULT_ACCESS_LOG_DIR: &str = "logs";
+const ACCESS_LOG_FILE_PREFIX: &str = "access.log";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessLogEntry {
+    pub time: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: f64,
+    pub client_ip: String,
+    pub user: String,
+    pub namespace: String,
+}
+
+// Keeps exactly rate of the requests it sees, spread evenly instead of at random
+#[derive(Debug)]
+struct Sampler {
+    rate: f64,
+    seen: AtomicU64,
+}
+
+impl Sampler {
+    fn new(rate: f64) -> Self {
+        Sampler {
+            rate: rate.clamp(0.0, 1.0),
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    fn sample(&self) -> bool {
+        let seen = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+
+        ((seen + 1.0) * self.rate).floor() > (seen * self.rate).floor()
+    }
+}
+
+// Structured access log in its own rolled files, apart from the application log.
+// Requests are sampled by the rate of the longest matching path prefix, falling back to the
+// default rate, and server errors are always written
+#[derive(Debug)]
+pub struct AccessLogManager {
+    writer: Option<(NonBlocking, WorkerGuard)>,
+    default_sampler: Sampler,
+    route_samplers: Vec<(String, Sampler)>,
+}
+
+impl AccessLogManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let default_sampler =
+            Sampler::new(app_config.get_float(ACCESS_LOG_SAMPLE_RATE).unwrap_or(1.0));
+
+        if !app_config.get_bool(ACCESS_LOG_ENABLED).unwrap_or(false) {
+            return Ok(AccessLogManager {
+                writer: None,
+                default_sampler,
+                route_samplers: Vec::new(),
+            });
+        }
+
+        let mut route_samplers = split_list(
+            &app_config
+                .get_string(ACCESS_LOG_SAMPLE_RATES)
+                .unwrap_or_default(),
+        )
+        .into_iter()
+        .map(|rule| match rule.split_once('=') {
+            Some((prefix, rate)) => match rate.trim().parse::<f64>() {
+                Ok(rate) => Ok((prefix.trim().to_string(), Sampler::new(rate))),
+                Err(_) => anyhow::bail!("invalid access log sample rate '{}'", rule),
+            },
+            None => anyhow::bail!("access log sample rate '{}' is not <path>=<rate>", rule),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+        route_samplers.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+        let rotation = match app_config
+            .get_string(ACCESS_LOG_ROTATION)
+            .unwrap_or("daily".to_string())
+            .as_str()
+        {
+            "minutely" => Rotation::MINUTELY,
+            "hourly" => Rotation::HOURLY,
+            "daily" => Rotation::DAILY,
+            "never" => Rotation::NEVER,
+            other => anyhow::bail!(
+                "unknown access log rotation '{}', expected minutely, hourly, daily or never",
+                other
+            ),
+        };
+        let appender = RollingFileAppender::new(
+            rotation,
+            app_config
+                .get_string(ACCESS_LOG_DIR)
+                .unwrap_or(DEFAULT_ACCESS_LOG_DIR.to_string()),
+            ACCESS_LOG_FILE_PREFIX,
+        );
+
+        Ok(AccessLogManager {
+            writer: Some(tracing_appender::non_blocking(appender)),
+            default_sampler,
+            route_samplers,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    // path is relative to the context path
+    pub fn sample(&self, path: &str) -> bool {
+        self.route_samplers
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map_or(&self.default_sampler, |(_, sampler)| sampler)
+            .sample()
+    }
+
+    pub fn write(&self, entry: &AccessLogEntry) {
+        if let Some((writer, _)) = &self.writer {
+            if let Ok(mut line) = serde_json::to_vec(entry) {
+                line.push(b'\n');
+
+                // the non blocking writer only queues the line for its worker thread
+                let _ = writer.clone().write_all(&line);
+            }
+        }
+    }
+}
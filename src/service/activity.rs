@@ -0,0 +1,103 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use config::Config;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::model::common::Page;
+
+pub const ACTIVITY_CAPACITY: &str = "nacos.core.activity.capacity";
+
+const DEFAULT_ACTIVITY_CAPACITY: i64 = 1000;
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    pub id: u64,
+    pub actor: String,
+    pub resource_type: String,
+    pub action: String,
+    pub resource: String,
+    pub time: i64,
+}
+
+#[derive(Debug, Default)]
+struct ActivityState {
+    next_id: u64,
+    activities: VecDeque<Activity>,
+}
+
+// Latest write operations of console users on this node, the oldest are dropped beyond the capacity
+#[derive(Debug, Default)]
+pub struct ActivityManager {
+    capacity: usize,
+    state: Mutex<ActivityState>,
+}
+
+impl ActivityManager {
+    pub fn new(app_config: &Config) -> Self {
+        Self {
+            capacity: app_config
+                .get_int(ACTIVITY_CAPACITY)
+                .unwrap_or(DEFAULT_ACTIVITY_CAPACITY)
+                .max(0) as usize,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&self, actor: &str, resource_type: &str, action: &str, resource: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        state.next_id += 1;
+
+        let activity = Activity {
+            id: state.next_id,
+            actor: actor.to_string(),
+            resource_type: resource_type.to_string(),
+            action: action.to_string(),
+            resource: resource.to_string(),
+            time: chrono::Utc::now().timestamp_millis(),
+        };
+
+        if state.activities.len() >= self.capacity {
+            state.activities.pop_front();
+        }
+
+        state.activities.push_back(activity);
+    }
+
+    // Newest first, empty filters match everything
+    pub fn search(
+        &self,
+        actor: &str,
+        resource_type: &str,
+        page_no: u64,
+        page_size: u64,
+    ) -> Page<Activity> {
+        let state = self.state.lock().unwrap();
+        let matched: Vec<&Activity> = state
+            .activities
+            .iter()
+            .rev()
+            .filter(|e| actor.is_empty() || e.actor == actor)
+            .filter(|e| resource_type.is_empty() || e.resource_type == resource_type)
+            .collect();
+
+        if matched.is_empty() {
+            return Page::<Activity>::default();
+        }
+
+        let page_items = matched
+            .iter()
+            .skip(((page_no - 1) * page_size) as usize)
+            .take(page_size as usize)
+            .map(|e| (*e).clone())
+            .collect();
+
+        Page::<Activity>::new(matched.len() as u64, page_no, page_size, page_items)
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use config::{Config, Map, Source, Value};
+
+// Load the application config, later sources win: application.yml, environment variables, then
+// --key=value command line arguments.
+//
+// An environment variable overrides a property when it is named like the property itself
+// (nacos.core.auth.enabled, allowed by docker and kubernetes), or like its relaxed form
+// (NACOS_CORE_AUTH_ENABLED: dots to underscores, dashes removed, upper case) for properties
+// present in application.yml. List values such as nacos.member.list are comma separated.
+pub fn load(path: &str, args: impl Iterator<Item = String>) -> anyhow::Result<Config> {
+    let file_config = Config::builder()
+        .add_source(config::File::with_name(path))
+        .build()?;
+
+    let mut keys = Vec::new();
+
+    flatten("", file_config.collect()?, &mut keys);
+
+    let roots: HashSet<&str> = keys
+        .iter()
+        .filter_map(|key| key.split('.').next())
+        .collect();
+    let mut builder = Config::builder().add_source(file_config.clone());
+
+    for (name, value) in std::env::vars() {
+        let key = if name.contains('.') {
+            name.split('.')
+                .next()
+                .filter(|root| roots.contains(root))
+                .map(|_| name.clone())
+        } else {
+            keys.iter().find(|key| relaxed_name(key) == name).cloned()
+        };
+
+        if let Some(key) = key {
+            builder = builder.set_override(key, value)?;
+        }
+    }
+
+    for arg in args {
+        if let Some((key, value)) = arg.strip_prefix("--").and_then(|e| e.split_once('=')) {
+            builder = builder.set_override(key, value)?;
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+pub fn relaxed_name(key: &str) -> String {
+    key.replace('.', "_").replace('-', "").to_uppercase()
+}
+
+fn flatten(prefix: &str, table: Map<String, Value>, keys: &mut Vec<String>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        match value.clone().into_table() {
+            Ok(table) => flatten(&key, table, keys),
+            Err(_) => keys.push(key),
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+use config::Config;
+use sea_orm::{sea_query::Expr, *};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    entity::{config_approval, config_approval_namespace},
+    model::{
+        common::{BusinessError, Page},
+        config::ConfigChange,
+    },
+    service::{self, crypto::ContentCipher, ip_filter::split_list, write_lock::WriteLockManager},
+};
+
+pub const PROTECTED_NAMESPACES: &str = "nacos.core.config.approval.namespaces";
+pub const APPROVER_ROLE: &str = "nacos.core.config.approval.approver-role";
+
+const DEFAULT_APPROVER_ROLE: &str = "ROLE_APPROVER";
+const DEFAULT_NAMESPACE: &str = "public";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalStatus {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "PENDING" => Some(ApprovalStatus::Pending),
+            "APPROVED" => Some(ApprovalStatus::Approved),
+            "REJECTED" => Some(ApprovalStatus::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "PENDING",
+            ApprovalStatus::Approved => "APPROVED",
+            ApprovalStatus::Rejected => "REJECTED",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRecord {
+    pub id: u64,
+    pub change: ConfigChange,
+    // md5 of the config the change was made against, none for a new config
+    pub base_md5: Option<String>,
+    pub status: ApprovalStatus,
+    pub submitter: String,
+    pub submit_time: i64,
+    pub reviewer: Option<String>,
+    pub review_time: Option<i64>,
+    pub comment: Option<String>,
+}
+
+impl ApprovalRecord {
+    // Move a pending record to its reviewed status, the submitter can never review its own change
+    fn review(
+        &mut self,
+        reviewer: &str,
+        status: ApprovalStatus,
+        comment: Option<String>,
+    ) -> anyhow::Result<()> {
+        if self.status != ApprovalStatus::Pending {
+            return Err(BusinessError::ResourceConflict(format!(
+                "approval {} is already {:?}",
+                self.id, self.status
+            ))
+            .into());
+        }
+
+        // without auth every request has the same empty user
+        if reviewer.is_empty() {
+            return Err(BusinessError::AccessDenied(String::from(
+                "a change can only be reviewed by a logged in user",
+            ))
+            .into());
+        }
+
+        if self.submitter == reviewer {
+            return Err(BusinessError::AccessDenied(String::from(
+                "a change can not be reviewed by its submitter",
+            ))
+            .into());
+        }
+
+        self.status = status;
+        self.reviewer = Some(reviewer.to_string());
+        self.review_time = Some(chrono::Utc::now().timestamp_millis());
+        self.comment = comment;
+
+        Ok(())
+    }
+
+    fn reopen(&mut self) {
+        self.status = ApprovalStatus::Pending;
+        self.reviewer = None;
+        self.review_time = None;
+        self.comment = None;
+    }
+}
+
+impl TryFrom<config_approval::Model> for ApprovalRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: config_approval::Model) -> anyhow::Result<Self> {
+        Ok(ApprovalRecord {
+            id: value.id,
+            change: serde_json::from_str(&value.config_change)?,
+            base_md5: value.base_md5,
+            status: ApprovalStatus::from_name(&value.status)
+                .ok_or_else(|| anyhow::anyhow!("unknown approval status {}", value.status))?,
+            submitter: value.submitter,
+            submit_time: value.submit_time,
+            reviewer: value.reviewer,
+            review_time: value.review_time,
+            comment: value.comment,
+        })
+    }
+}
+
+// Publishes to protected namespaces wait in the config_approval table until a second user with
+// the approver role reviews them, so every member sees them. Reviewed records are kept there
+// as the audit trail. Protected namespaces set through the admin API are stored in the
+// config_approval_namespace table and replace the ones of the config
+#[derive(Debug)]
+pub struct ApprovalManager {
+    db: DatabaseConnection,
+    content_cipher: Arc<ContentCipher>,
+    write_lock_manager: Arc<WriteLockManager>,
+    configured_namespaces: HashSet<String>,
+    protected_namespaces: RwLock<HashSet<String>>,
+    approver_role: String,
+}
+
+impl ApprovalManager {
+    pub fn new(
+        app_config: &Config,
+        db: DatabaseConnection,
+        content_cipher: Arc<ContentCipher>,
+        write_lock_manager: Arc<WriteLockManager>,
+    ) -> Self {
+        let configured_namespaces: HashSet<String> = split_list(
+            &app_config
+                .get_string(PROTECTED_NAMESPACES)
+                .unwrap_or_default(),
+        )
+        .into_iter()
+        .collect();
+
+        Self {
+            db,
+            content_cipher,
+            write_lock_manager,
+            protected_namespaces: RwLock::new(configured_namespaces.clone()),
+            configured_namespaces,
+            approver_role: app_config
+                .get_string(APPROVER_ROLE)
+                .unwrap_or(DEFAULT_APPROVER_ROLE.to_string()),
+        }
+    }
+
+    pub fn approver_role(&self) -> &str {
+        &self.approver_role
+    }
+
+    pub fn protected_namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> = self
+            .protected_namespaces
+            .read()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect();
+
+        namespaces.sort();
+
+        namespaces
+    }
+
+    // Stores the namespaces for every member, an empty list brings back the ones of the config
+    pub async fn set_protected_namespaces(
+        &self,
+        namespaces: Vec<String>,
+        operator: &str,
+    ) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let txn = self.db.begin().await?;
+
+        config_approval_namespace::Entity::delete_many()
+            .exec(&txn)
+            .await?;
+
+        if !namespaces.is_empty() {
+            config_approval_namespace::Entity::insert_many(namespaces.iter().map(|namespace| {
+                config_approval_namespace::ActiveModel {
+                    namespace: Set(namespace.clone()),
+                    operator: Set(operator.to_string()),
+                    gmt_modified: Set(now),
+                }
+            }))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        self.load().await
+    }
+
+    // Reads the stored protected namespaces again
+    pub async fn load(&self) -> anyhow::Result<()> {
+        let stored: HashSet<String> = config_approval_namespace::Entity::find()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|row| row.namespace)
+            .collect();
+
+        *self.protected_namespaces.write().unwrap() = if stored.is_empty() {
+            self.configured_namespaces.clone()
+        } else {
+            stored
+        };
+
+        Ok(())
+    }
+
+    pub fn is_protected(&self, tenant: &str) -> bool {
+        let protected_namespaces = self.protected_namespaces.read().unwrap();
+
+        protected_namespaces.contains(tenant)
+            || (tenant.is_empty() && protected_namespaces.contains(DEFAULT_NAMESPACE))
+    }
+
+    pub async fn submit(
+        &self,
+        change: ConfigChange,
+        submitter: &str,
+    ) -> anyhow::Result<ApprovalRecord> {
+        // the submitter could approve its own change
+        if submitter.is_empty() {
+            return Err(BusinessError::IllegalState(String::from(
+                "publishes to protected namespaces need auth enabled to be reviewed",
+            ))
+            .into());
+        }
+
+        let submit_time = chrono::Utc::now().timestamp_millis();
+        let base_md5 =
+            service::config::find_md5(&self.db, &change.data_id, &change.group, &change.tenant)
+                .await?;
+        // a pending content is encrypted at rest like a published one
+        let stored = ConfigChange {
+            content: self.content_cipher.encrypt(&change.content)?,
+            ..change.clone()
+        };
+        let entity = config_approval::ActiveModel {
+            data_id: Set(change.data_id.clone()),
+            group_id: Set(change.group.clone()),
+            tenant_id: Set(change.tenant.clone()),
+            config_change: Set(serde_json::to_string(&stored)?),
+            base_md5: Set(base_md5.clone()),
+            status: Set(ApprovalStatus::Pending.name().to_string()),
+            submitter: Set(submitter.to_string()),
+            submit_time: Set(submit_time),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(ApprovalRecord {
+            id: entity.id,
+            change,
+            base_md5,
+            status: ApprovalStatus::Pending,
+            submitter: submitter.to_string(),
+            submit_time,
+            reviewer: None,
+            review_time: None,
+            comment: None,
+        })
+    }
+
+    // Newest first, page_no starts at 1
+    pub async fn list(
+        &self,
+        status: Option<ApprovalStatus>,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<ApprovalRecord>> {
+        let mut select = config_approval::Entity::find();
+
+        if let Some(status) = status {
+            select = select.filter(config_approval::Column::Status.eq(status.name()));
+        }
+
+        let page_no = page_no.max(1);
+        let page_size = page_size.max(1);
+        let total_count = select.clone().count(&self.db).await?;
+
+        if total_count == 0 {
+            return Ok(Page::<ApprovalRecord>::default());
+        }
+
+        let page_items = select
+            .order_by_desc(config_approval::Column::Id)
+            .paginate(&self.db, page_size)
+            .fetch_page(page_no - 1)
+            .await?
+            .into_iter()
+            .map(|entity| self.record(entity))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Page::<ApprovalRecord>::new(
+            total_count,
+            page_no,
+            page_size,
+            page_items,
+        ))
+    }
+
+    // The write lock is only checked for an approval, rejecting a change to a locked namespace
+    // writes nothing
+    pub async fn review(
+        &self,
+        id: u64,
+        reviewer: &str,
+        status: ApprovalStatus,
+        comment: Option<String>,
+    ) -> anyhow::Result<ApprovalRecord> {
+        let mut record = self.find(id).await?;
+
+        record.review(reviewer, status, comment)?;
+
+        if status == ApprovalStatus::Approved {
+            self.write_lock_manager
+                .check(&record.change.tenant, &record.change.group)?;
+
+            // approving would silently overwrite what was published since the submission
+            let current_md5 = service::config::find_md5(
+                &self.db,
+                &record.change.data_id,
+                &record.change.group,
+                &record.change.tenant,
+            )
+            .await?;
+
+            if current_md5 != record.base_md5 {
+                return Err(BusinessError::ResourceConflict(format!(
+                    "config changed since approval {} was submitted, submit the change again",
+                    record.id
+                ))
+                .into());
+            }
+        }
+
+        self.update(&record, ApprovalStatus::Pending).await?;
+
+        Ok(record)
+    }
+
+    // Put an approved record back to pending when applying it failed
+    pub async fn reopen(&self, id: u64) -> anyhow::Result<()> {
+        let mut record = self.find(id).await?;
+
+        record.reopen();
+
+        self.update(&record, ApprovalStatus::Approved).await
+    }
+
+    async fn find(&self, id: u64) -> anyhow::Result<ApprovalRecord> {
+        let entity = config_approval::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| BusinessError::ResourceNotFound(format!("approval {} not exist", id)))?;
+
+        self.record(entity)
+    }
+
+    fn record(&self, entity: config_approval::Model) -> anyhow::Result<ApprovalRecord> {
+        let mut record = ApprovalRecord::try_from(entity)?;
+
+        record.change.content = self.content_cipher.decrypt(&record.change.content)?;
+
+        Ok(record)
+    }
+
+    // Only while the record still has the expected status, so two members reviewing the same
+    // record at once cannot both succeed
+    async fn update(
+        &self,
+        record: &ApprovalRecord,
+        expected: ApprovalStatus,
+    ) -> anyhow::Result<()> {
+        let result = config_approval::Entity::update_many()
+            .col_expr(
+                config_approval::Column::Status,
+                Expr::value(record.status.name()),
+            )
+            .col_expr(
+                config_approval::Column::Reviewer,
+                Expr::value(record.reviewer.clone()),
+            )
+            .col_expr(
+                config_approval::Column::ReviewTime,
+                Expr::value(record.review_time),
+            )
+            .col_expr(
+                config_approval::Column::Comment,
+                Expr::value(record.comment.clone()),
+            )
+            .filter(config_approval::Column::Id.eq(record.id))
+            .filter(config_approval::Column::Status.eq(expected.name()))
+            .exec(&self.db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(BusinessError::ResourceConflict(format!(
+                "approval {} was reviewed meanwhile",
+                record.id
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> ApprovalRecord {
+        ApprovalRecord {
+            id: 1,
+            change: ConfigChange::default(),
+            base_md5: None,
+            status: ApprovalStatus::Pending,
+            submitter: String::from("alice"),
+            submit_time: 0,
+            reviewer: None,
+            review_time: None,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn pending_record_can_be_approved_or_rejected_once() {
+        for status in [ApprovalStatus::Approved, ApprovalStatus::Rejected] {
+            let mut record = record();
+
+            record
+                .review("bob", status, Some(String::from("ok")))
+                .unwrap();
+
+            assert_eq!(record.status, status);
+            assert_eq!(record.reviewer.as_deref(), Some("bob"));
+            assert!(record.review_time.is_some());
+
+            let err = record
+                .review("carol", ApprovalStatus::Approved, None)
+                .unwrap_err();
+
+            assert!(matches!(
+                err.downcast_ref::<BusinessError>(),
+                Some(BusinessError::ResourceConflict(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn submitter_can_not_review_its_own_change() {
+        let mut record = record();
+        let err = record
+            .review("alice", ApprovalStatus::Approved, None)
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<BusinessError>(),
+            Some(BusinessError::AccessDenied(_))
+        ));
+        assert_eq!(record.status, ApprovalStatus::Pending);
+    }
+
+    #[test]
+    fn anonymous_reviewer_is_refused() {
+        let mut record = ApprovalRecord {
+            submitter: String::new(),
+            ..record()
+        };
+        let err = record
+            .review("", ApprovalStatus::Approved, None)
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<BusinessError>(),
+            Some(BusinessError::AccessDenied(_))
+        ));
+        assert_eq!(record.status, ApprovalStatus::Pending);
+    }
+
+    #[test]
+    fn reopened_record_can_be_reviewed_again() {
+        let mut record = record();
+
+        record
+            .review("bob", ApprovalStatus::Approved, None)
+            .unwrap();
+        record.reopen();
+
+        assert_eq!(record.status, ApprovalStatus::Pending);
+        assert!(record.reviewer.is_none());
+        assert!(record.review("bob", ApprovalStatus::Rejected, None).is_ok());
+    }
+
+    #[test]
+    fn status_names_round_trip() {
+        for status in [
+            ApprovalStatus::Pending,
+            ApprovalStatus::Approved,
+            ApprovalStatus::Rejected,
+        ] {
+            assert_eq!(ApprovalStatus::from_name(status.name()), Some(status));
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use config::Config;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Select,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{entity::audit_record, model::common::Page};
+
+pub const AUDIT_ENABLED: &str = "nacos.core.audit.enabled";
+pub const AUDIT_PERSISTENCE: &str = "nacos.core.audit.persistence";
+pub const AUDIT_FILE: &str = "nacos.core.audit.file";
+pub const AUDIT_FILE_MAX_SIZE: &str = "nacos.core.audit.file.max-size";
+
+const DEFAULT_AUDIT_FILE: &str = "logs/audit.log";
+const DEFAULT_AUDIT_FILE_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub operator: String,
+    pub src_ip: String,
+    pub resource_type: String,
+    pub action: String,
+    pub resource: String,
+    pub time: i64,
+}
+
+// Empty filters match everything, times are epoch milliseconds
+#[derive(Clone, Debug, Default)]
+pub struct AuditQuery {
+    pub operator: String,
+    pub resource_type: String,
+    pub action: String,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        (self.operator.is_empty() || record.operator == self.operator)
+            && (self.resource_type.is_empty() || record.resource_type == self.resource_type)
+            && (self.action.is_empty() || record.action == self.action)
+            && self
+                .start_time
+                .is_none_or(|start_time| record.time >= start_time)
+            && self.end_time.is_none_or(|end_time| record.time < end_time)
+    }
+}
+
+// Storage of audit records, programs embedding the server can plug their own through
+// BatataServerBuilder::audit_persistence
+#[async_trait]
+pub trait AuditPersistence: Debug + Send + Sync {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()>;
+
+    // Newest first
+    async fn search(
+        &self,
+        query: &AuditQuery,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<AuditRecord>>;
+}
+
+// The audit_record table of conf/batata-schema.sql, shared by every node of a cluster
+#[derive(Debug)]
+pub struct DatabaseAuditPersistence {
+    db: DatabaseConnection,
+}
+
+impl DatabaseAuditPersistence {
+    pub fn new(db: DatabaseConnection) -> Self {
+        DatabaseAuditPersistence { db }
+    }
+
+    fn select(query: &AuditQuery) -> Select<audit_record::Entity> {
+        let mut select = audit_record::Entity::find();
+
+        if !query.operator.is_empty() {
+            select = select.filter(audit_record::Column::Operator.eq(query.operator.as_str()));
+        }
+        if !query.resource_type.is_empty() {
+            select =
+                select.filter(audit_record::Column::ResourceType.eq(query.resource_type.as_str()));
+        }
+        if !query.action.is_empty() {
+            select = select.filter(audit_record::Column::Action.eq(query.action.as_str()));
+        }
+        if let Some(start_time) = query.start_time {
+            select = select.filter(audit_record::Column::Time.gte(start_time));
+        }
+        if let Some(end_time) = query.end_time {
+            select = select.filter(audit_record::Column::Time.lt(end_time));
+        }
+
+        select
+    }
+}
+
+#[async_trait]
+impl AuditPersistence for DatabaseAuditPersistence {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        audit_record::Entity::insert(audit_record::ActiveModel {
+            operator: Set(record.operator.clone()),
+            src_ip: Set(record.src_ip.clone()),
+            resource_type: Set(record.resource_type.clone()),
+            action: Set(record.action.clone()),
+            resource: Set(record.resource.clone()),
+            time: Set(record.time),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query: &AuditQuery,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<AuditRecord>> {
+        let page_no = page_no.max(1);
+        let page_size = page_size.max(1);
+        let select = Self::select(query);
+        let total_count = select.clone().count(&self.db).await?;
+
+        if total_count == 0 {
+            return Ok(Page::<AuditRecord>::default());
+        }
+
+        let page_items = select
+            .order_by_desc(audit_record::Column::Id)
+            .paginate(&self.db, page_size)
+            .fetch_page(page_no - 1)
+            .await?
+            .into_iter()
+            .map(|entity| AuditRecord {
+                operator: entity.operator,
+                src_ip: entity.src_ip,
+                resource_type: entity.resource_type,
+                action: entity.action,
+                resource: entity.resource,
+                time: entity.time,
+            })
+            .collect();
+
+        Ok(Page::<AuditRecord>::new(
+            total_count,
+            page_no,
+            page_size,
+            page_items,
+        ))
+    }
+}
+
+// One JSON record per line in a local file, each node of a cluster keeps its own. A file over
+// max_size is moved to <file>.1 replacing the previous one, search reads both
+#[derive(Debug)]
+pub struct FileAuditPersistence {
+    path: PathBuf,
+    max_size: u64,
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileAuditPersistence {
+    pub fn new(path: impl Into<PathBuf>, max_size: u64) -> Self {
+        FileAuditPersistence {
+            path: path.into(),
+            max_size,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn rotated_path(path: &Path) -> PathBuf {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+
+        PathBuf::from(rotated)
+    }
+
+    fn append_line(path: &Path, max_size: u64, line: &str) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if fs::metadata(path).is_ok_and(|metadata| metadata.len() >= max_size) {
+            fs::rename(path, Self::rotated_path(path))?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    // Reads the files line by line keeping only the newest records up to the requested page
+    fn search_files(
+        path: &Path,
+        query: &AuditQuery,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<AuditRecord>> {
+        let keep = page_no.saturating_mul(page_size) as usize;
+        let mut total_count = 0;
+        let mut newest: VecDeque<AuditRecord> = VecDeque::new();
+
+        for path in [Self::rotated_path(path), path.to_path_buf()] {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            for line in BufReader::new(file).lines() {
+                let record = match serde_json::from_str::<AuditRecord>(&line?) {
+                    Ok(record) if query.matches(&record) => record,
+                    _ => continue,
+                };
+
+                total_count += 1;
+
+                if newest.len() == keep {
+                    newest.pop_front();
+                }
+                newest.push_back(record);
+            }
+        }
+
+        if total_count == 0 {
+            return Ok(Page::<AuditRecord>::default());
+        }
+
+        let page_items = newest
+            .into_iter()
+            .rev()
+            .skip(((page_no - 1) * page_size) as usize)
+            .collect();
+
+        Ok(Page::<AuditRecord>::new(
+            total_count,
+            page_no,
+            page_size,
+            page_items,
+        ))
+    }
+}
+
+#[async_trait]
+impl AuditPersistence for FileAuditPersistence {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let path = self.path.clone();
+        let max_size = self.max_size;
+        let lock = self.lock.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let _guard = lock.lock().unwrap();
+
+            Self::append_line(&path, max_size, &line)
+        })
+        .await?
+    }
+
+    async fn search(
+        &self,
+        query: &AuditQuery,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<AuditRecord>> {
+        let path = self.path.clone();
+        let query = query.clone();
+        let lock = self.lock.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let _guard = lock.lock().unwrap();
+
+            Self::search_files(&path, &query, page_no.max(1), page_size.max(1))
+        })
+        .await?
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AuditManager {
+    persistence: Option<Arc<dyn AuditPersistence>>,
+}
+
+impl AuditManager {
+    // A plugged persistence is used even if the audit log is not enabled
+    pub fn new(
+        app_config: &Config,
+        db: DatabaseConnection,
+        persistence: Option<Arc<dyn AuditPersistence>>,
+    ) -> anyhow::Result<Self> {
+        let persistence = match persistence {
+            Some(persistence) => Some(persistence),
+            None if app_config.get_bool(AUDIT_ENABLED).unwrap_or(false) => Some(
+                match app_config
+                    .get_string(AUDIT_PERSISTENCE)
+                    .unwrap_or("database".to_string())
+                    .as_str()
+                {
+                    "database" => {
+                        Arc::new(DatabaseAuditPersistence::new(db)) as Arc<dyn AuditPersistence>
+                    }
+                    "file" => Arc::new(FileAuditPersistence::new(
+                        app_config
+                            .get_string(AUDIT_FILE)
+                            .unwrap_or(DEFAULT_AUDIT_FILE.to_string()),
+                        app_config
+                            .get_int(AUDIT_FILE_MAX_SIZE)
+                            .map(|max_size| max_size.max(1) as u64)
+                            .unwrap_or(DEFAULT_AUDIT_FILE_MAX_SIZE),
+                    )),
+                    other => anyhow::bail!(
+                        "unknown audit persistence '{}', use database or file",
+                        other
+                    ),
+                },
+            ),
+            None => None,
+        };
+
+        Ok(AuditManager { persistence })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.persistence.is_some()
+    }
+
+    // Written in the background so a slow store does not hold back the request
+    pub fn record(&self, record: AuditRecord) {
+        if let Some(persistence) = self.persistence.clone() {
+            tokio::spawn(async move {
+                if let Err(err) = persistence.append(&record).await {
+                    tracing::warn!(
+                        "write audit record {} {} {} failed: {}",
+                        record.resource_type,
+                        record.action,
+                        record.resource,
+                        err
+                    );
+                }
+            });
+        }
+    }
+
+    pub async fn search(
+        &self,
+        query: &AuditQuery,
+        page_no: u64,
+        page_size: u64,
+    ) -> anyhow::Result<Page<AuditRecord>> {
+        match &self.persistence {
+            Some(persistence) => persistence.search(query, page_no, page_size).await,
+            None => Ok(Page::<AuditRecord>::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: i64) -> AuditRecord {
+        AuditRecord {
+            operator: String::from("admin"),
+            src_ip: String::from("127.0.0.1"),
+            resource_type: String::from("config"),
+            action: String::from("publish"),
+            resource: format!("public/DEFAULT_GROUP/app-{}", time),
+            time,
+        }
+    }
+
+    #[test]
+    fn file_search_pages_newest_first_across_rotation() {
+        let path = std::env::temp_dir()
+            .join(format!("batata-audit-{}", std::process::id()))
+            .join("audit.log");
+        let line_size = serde_json::to_string(&record(0)).unwrap().len() as u64 + 1;
+
+        for time in 0..5 {
+            let line = format!("{}\n", serde_json::to_string(&record(time)).unwrap());
+
+            // every third record goes to a new file
+            FileAuditPersistence::append_line(&path, line_size * 3, &line).unwrap();
+        }
+
+        let query = AuditQuery::default();
+        let first = FileAuditPersistence::search_files(&path, &query, 1, 2).unwrap();
+        let last = FileAuditPersistence::search_files(&path, &query, 3, 2).unwrap();
+        let beyond = FileAuditPersistence::search_files(&path, &query, 4, 2).unwrap();
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(first.total_count, 5);
+        assert_eq!(
+            first.page_items.iter().map(|r| r.time).collect::<Vec<_>>(),
+            vec![4, 3]
+        );
+        assert_eq!(
+            last.page_items.iter().map(|r| r.time).collect::<Vec<_>>(),
+            vec![0]
+        );
+        assert!(beyond.page_items.is_empty());
+    }
+}
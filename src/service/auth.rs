@@ -1,7 +1,162 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
 use chrono;
+use config::Config;
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::model::auth::{ApiType, NacosJwtPayload, NacosUser, AUTH_ADMIN_PATH};
+
+pub const AUTH_ENABLED: &str = "nacos.core.auth.enabled";
+pub const TOKEN_SECRET_KEY: &str = "nacos.core.auth.plugin.nacos.token.secret.key";
+
+const MIN_SECRET_KEY_LENGTH: usize = 32;
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthSwitchState {
+    pub enabled: HashMap<ApiType, bool>,
+    pub previous_key_expire_time: Option<i64>,
+}
+
+#[derive(Debug)]
+struct AuthSettings {
+    enabled: HashMap<ApiType, bool>,
+    secret_key: String,
+    previous_secret_key: Option<(String, i64)>,
+}
+
+// Runtime auth switches and the token secret, which can be rotated while the previous
+// secret is still accepted for a grace period
+#[derive(Debug)]
+pub struct AuthManager {
+    settings: RwLock<AuthSettings>,
+}
+
+impl AuthManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let enabled = app_config.get_bool(AUTH_ENABLED).unwrap_or(true);
+        let secret_key = app_config.get_string(TOKEN_SECRET_KEY)?;
+
+        // only a new key has to pass the check, existing deployments keep starting with theirs
+        if let Err(err) = check_secret_key(&secret_key) {
+            tracing::warn!("{}, rotate it to a stronger one", err);
+        }
+
+        Ok(Self {
+            settings: RwLock::new(AuthSettings {
+                enabled: ApiType::ALL.iter().map(|e| (*e, enabled)).collect(),
+                secret_key,
+                previous_secret_key: None,
+            }),
+        })
+    }
+
+    pub fn is_enabled(&self, api_type: ApiType) -> bool {
+        *self
+            .settings
+            .read()
+            .unwrap()
+            .enabled
+            .get(&api_type)
+            .unwrap_or(&true)
+    }
+
+    // The auth admin endpoints always take a token, whatever the switches say
+    pub fn is_required(&self, path: &str) -> bool {
+        path.starts_with(AUTH_ADMIN_PATH) || self.is_enabled(ApiType::from_path(path))
+    }
+
+    pub fn set_enabled(&self, api_type: ApiType, enabled: bool) {
+        self.settings
+            .write()
+            .unwrap()
+            .enabled
+            .insert(api_type, enabled);
+    }
+
+    pub fn state(&self) -> AuthSwitchState {
+        let settings = self.settings.read().unwrap();
+
+        AuthSwitchState {
+            enabled: settings.enabled.clone(),
+            previous_key_expire_time: settings
+                .previous_secret_key
+                .as_ref()
+                .map(|(_, expire_time)| *expire_time)
+                .filter(|expire_time| *expire_time > chrono::Utc::now().timestamp()),
+        }
+    }
+
+    pub fn secret_key(&self) -> String {
+        self.settings.read().unwrap().secret_key.clone()
+    }
+
+    pub fn rotate_secret_key(&self, secret_key: &str, grace_seconds: i64) -> anyhow::Result<()> {
+        check_secret_key(secret_key)?;
+
+        let mut settings = self.settings.write().unwrap();
+        let expire_time = chrono::Utc::now().timestamp() + grace_seconds;
+        let previous_secret_key =
+            std::mem::replace(&mut settings.secret_key, secret_key.to_string());
+
+        settings.previous_secret_key = Some((previous_secret_key, expire_time));
+
+        Ok(())
+    }
 
-use crate::model::auth::{NacosJwtPayload, NacosUser};
+    pub fn decode_token(
+        &self,
+        token: &str,
+    ) -> jsonwebtoken::errors::Result<jsonwebtoken::TokenData<NacosJwtPayload>> {
+        let (secret_key, previous_secret_key) = {
+            let settings = self.settings.read().unwrap();
+
+            (
+                settings.secret_key.clone(),
+                settings
+                    .previous_secret_key
+                    .clone()
+                    .filter(|(_, expire_time)| *expire_time > chrono::Utc::now().timestamp()),
+            )
+        };
+
+        match decode_jwt_token(token, &secret_key) {
+            Err(err) if *err.kind() == jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+                match previous_secret_key {
+                    Some((previous_secret_key, _)) => decode_jwt_token(token, &previous_secret_key),
+                    None => Err(err),
+                }
+            }
+            result => result,
+        }
+    }
+}
+
+pub fn generate_secret_key() -> String {
+    STANDARD.encode(format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    ))
+}
+
+fn check_secret_key(secret_key: &str) -> anyhow::Result<()> {
+    let decoded = STANDARD
+        .decode(secret_key)
+        .map_err(|_| anyhow::anyhow!("the token secret key must be a base64 string"))?;
+
+    if decoded.len() < MIN_SECRET_KEY_LENGTH {
+        return Err(anyhow::anyhow!(
+            "the token secret key must be at least {} bytes",
+            MIN_SECRET_KEY_LENGTH
+        ));
+    }
+
+    Ok(())
+}
 
 pub fn decode_jwt_token(
     token: &str,
@@ -9,7 +164,7 @@ pub fn decode_jwt_token(
 ) -> jsonwebtoken::errors::Result<jsonwebtoken::TokenData<NacosJwtPayload>> {
     decode::<NacosJwtPayload>(
         token,
-        &DecodingKey::from_base64_secret(secret_key).unwrap(),
+        &DecodingKey::from_base64_secret(secret_key)?,
         &Validation::default(),
     )
 }
@@ -45,6 +200,65 @@ pub fn encode_jwt_token(
     encode(
         &header,
         &payload,
-        &EncodingKey::from_base64_secret(secret_key).unwrap(),
+        &EncodingKey::from_base64_secret(secret_key)?,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(secret_key: &str) -> AuthManager {
+        let app_config = Config::builder()
+            .set_override(TOKEN_SECRET_KEY, secret_key)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        AuthManager::new(&app_config).unwrap()
+    }
+
+    fn token(secret_key: &str) -> String {
+        let user = NacosUser {
+            username: String::from("nacos"),
+            password: String::new(),
+            token: String::new(),
+            global_admin: false,
+        };
+
+        encode_jwt_token(&user, secret_key, 60).unwrap()
+    }
+
+    #[test]
+    fn weak_secret_key_only_fails_rotation() {
+        let manager = manager("c2hvcnQ=");
+
+        assert!(manager.rotate_secret_key("c2hvcnQ=", 60).is_err());
+        assert!(manager.rotate_secret_key("not base64!", 60).is_err());
+        assert!(manager
+            .rotate_secret_key(&generate_secret_key(), 60)
+            .is_ok());
+    }
+
+    #[test]
+    fn previous_secret_key_is_accepted_during_grace_period() {
+        let old_key = generate_secret_key();
+        let manager = manager(&old_key);
+        let old_token = token(&old_key);
+
+        manager
+            .rotate_secret_key(&generate_secret_key(), 60)
+            .unwrap();
+
+        assert!(manager.decode_token(&old_token).is_ok());
+        assert!(manager.decode_token(&token(&manager.secret_key())).is_ok());
+
+        let manager = self::manager(&old_key);
+
+        manager
+            .rotate_secret_key(&generate_secret_key(), -1)
+            .unwrap();
+
+        assert!(manager.decode_token(&old_token).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::Local;
+use config::Config;
+use sea_orm::{sea_query::Expr, *};
+
+use crate::{
+    entity::{config_info, group_capacity, tenant_capacity},
+    model::{common::BusinessError, config::ConfigChange},
+};
+
+const DEFAULT_TENANT_QUOTA: u32 = 200;
+const DEFAULT_GROUP_QUOTA: u32 = 200;
+const DEFAULT_MAX_SIZE: u32 = 100 * 1024;
+
+// Config count and content size limits kept in tenant_capacity for namespaces and in
+// group_capacity for the groups of the public namespace. A quota or max_size of 0 in a row
+// falls back to the defaults
+#[derive(Clone, Debug)]
+pub struct CapacityPolicy {
+    pub manage: bool,
+    pub limit_check: bool,
+    pub default_tenant_quota: u32,
+    pub default_group_quota: u32,
+    pub default_max_size: u32,
+}
+
+enum CapacityScope<'a> {
+    Tenant(&'a str),
+    Group(&'a str),
+}
+
+struct Capacity {
+    quota: u32,
+    max_size: u32,
+    usage: u32,
+}
+
+impl<'a> CapacityScope<'a> {
+    fn of(change: &'a ConfigChange) -> Self {
+        if change.tenant.is_empty() {
+            CapacityScope::Group(&change.group)
+        } else {
+            CapacityScope::Tenant(&change.tenant)
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            CapacityScope::Tenant(tenant) => format!("namespace '{}'", tenant),
+            CapacityScope::Group(group) => format!("group '{}'", group),
+        }
+    }
+}
+
+impl CapacityPolicy {
+    pub fn new(app_config: &Config) -> Self {
+        let get_u32 = |key: &str, default: u32| {
+            app_config
+                .get_int(key)
+                .ok()
+                .and_then(|e| u32::try_from(e).ok())
+                .unwrap_or(default)
+        };
+
+        CapacityPolicy {
+            manage: app_config.get_bool("isManageCapacity").unwrap_or(true),
+            limit_check: app_config.get_bool("isCapacityLimitCheck").unwrap_or(false),
+            default_tenant_quota: get_u32("defaultTenantQuota", DEFAULT_TENANT_QUOTA),
+            default_group_quota: get_u32("defaultGroupQuota", DEFAULT_GROUP_QUOTA),
+            default_max_size: get_u32("defaultMaxSize", DEFAULT_MAX_SIZE),
+        }
+    }
+
+    // Checks the content size and counts a new config against the quota of its namespace or
+    // group. The usage is only incremented while it is below the quota, so concurrent
+    // publishes cannot overshoot it
+    pub async fn acquire(
+        &self,
+        db: &DatabaseConnection,
+        change: &ConfigChange,
+        is_new: bool,
+    ) -> anyhow::Result<()> {
+        if !self.manage {
+            return Ok(());
+        }
+
+        let scope = CapacityScope::of(change);
+        let capacity = self.find_or_create(db, &scope).await?;
+
+        self.check_size(&scope, &capacity, change)?;
+
+        if !is_new {
+            return Ok(());
+        }
+
+        let limit = self.limit_check.then_some(capacity.quota);
+        let now = Local::now().naive_local();
+        let updated = match scope {
+            CapacityScope::Tenant(tenant) => {
+                tenant_capacity::Entity::update_many()
+                    .col_expr(
+                        tenant_capacity::Column::Usage,
+                        Expr::col(tenant_capacity::Column::Usage).add(1),
+                    )
+                    .col_expr(tenant_capacity::Column::GmtModified, Expr::value(now))
+                    .filter(tenant_capacity::Column::TenantId.eq(tenant))
+                    .apply_if(limit, |query, limit| {
+                        query.filter(tenant_capacity::Column::Usage.lt(limit))
+                    })
+                    .exec(db)
+                    .await?
+            }
+            CapacityScope::Group(group) => {
+                group_capacity::Entity::update_many()
+                    .col_expr(
+                        group_capacity::Column::Usage,
+                        Expr::col(group_capacity::Column::Usage).add(1),
+                    )
+                    .col_expr(group_capacity::Column::GmtModified, Expr::value(now))
+                    .filter(group_capacity::Column::GroupId.eq(group))
+                    .apply_if(limit, |query, limit| {
+                        query.filter(group_capacity::Column::Usage.lt(limit))
+                    })
+                    .exec(db)
+                    .await?
+            }
+        };
+
+        if updated.rows_affected == 0 {
+            return Err(quota_exceeded(&scope, &capacity));
+        }
+
+        Ok(())
+    }
+
+    // The checks of acquire without taking usage or creating the capacity row, for dry runs
+    pub async fn check(
+        &self,
+        db: &DatabaseConnection,
+        change: &ConfigChange,
+        is_new: bool,
+    ) -> anyhow::Result<()> {
+        if !self.manage {
+            return Ok(());
+        }
+
+        let scope = CapacityScope::of(change);
+        let capacity = match self.find(db, &scope).await? {
+            Some(capacity) => capacity,
+            None => self.capacity(&scope, 0, 0, count_usage(db, &scope).await?),
+        };
+
+        self.check_size(&scope, &capacity, change)?;
+
+        if is_new && self.limit_check && capacity.usage >= capacity.quota {
+            return Err(quota_exceeded(&scope, &capacity));
+        }
+
+        Ok(())
+    }
+
+    fn check_size(
+        &self,
+        scope: &CapacityScope<'_>,
+        capacity: &Capacity,
+        change: &ConfigChange,
+    ) -> anyhow::Result<()> {
+        if self.limit_check && change.content.len() as u64 > capacity.max_size as u64 {
+            return Err(BusinessError::CapacityExceeded(format!(
+                "config content of {} bytes exceeds the max size of {} bytes in {}",
+                change.content.len(),
+                capacity.max_size,
+                scope.name()
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+
+    // Gives back the usage taken by acquire when the publish failed afterwards
+    pub async fn release(&self, db: &DatabaseConnection, change: &ConfigChange) {
+        if !self.manage {
+            return;
+        }
+
+        let now = Local::now().naive_local();
+        let result = match CapacityScope::of(change) {
+            CapacityScope::Tenant(tenant) => {
+                tenant_capacity::Entity::update_many()
+                    .col_expr(
+                        tenant_capacity::Column::Usage,
+                        Expr::col(tenant_capacity::Column::Usage).sub(1),
+                    )
+                    .col_expr(tenant_capacity::Column::GmtModified, Expr::value(now))
+                    .filter(tenant_capacity::Column::TenantId.eq(tenant))
+                    .filter(tenant_capacity::Column::Usage.gt(0))
+                    .exec(db)
+                    .await
+            }
+            CapacityScope::Group(group) => {
+                group_capacity::Entity::update_many()
+                    .col_expr(
+                        group_capacity::Column::Usage,
+                        Expr::col(group_capacity::Column::Usage).sub(1),
+                    )
+                    .col_expr(group_capacity::Column::GmtModified, Expr::value(now))
+                    .filter(group_capacity::Column::GroupId.eq(group))
+                    .filter(group_capacity::Column::Usage.gt(0))
+                    .exec(db)
+                    .await
+            }
+        };
+
+        if let Err(err) = result {
+            tracing::warn!("release config capacity failed: {}", err);
+        }
+    }
+
+    async fn find(
+        &self,
+        db: &DatabaseConnection,
+        scope: &CapacityScope<'_>,
+    ) -> anyhow::Result<Option<Capacity>> {
+        let row = match *scope {
+            CapacityScope::Tenant(tenant) => tenant_capacity::Entity::find()
+                .filter(tenant_capacity::Column::TenantId.eq(tenant))
+                .one(db)
+                .await?
+                .map(|row| (row.quota, row.max_size, row.usage)),
+            CapacityScope::Group(group) => group_capacity::Entity::find()
+                .filter(group_capacity::Column::GroupId.eq(group))
+                .one(db)
+                .await?
+                .map(|row| (row.quota, row.max_size, row.usage)),
+        };
+
+        Ok(row.map(|(quota, max_size, usage)| self.capacity(scope, quota, max_size, usage)))
+    }
+
+    // Rows are created on first use with the current config count as usage
+    async fn find_or_create(
+        &self,
+        db: &DatabaseConnection,
+        scope: &CapacityScope<'_>,
+    ) -> anyhow::Result<Capacity> {
+        if let Some(capacity) = self.find(db, scope).await? {
+            return Ok(capacity);
+        }
+
+        let usage = count_usage(db, scope).await?;
+        let now = Local::now().naive_local();
+        let inserted = match *scope {
+            CapacityScope::Tenant(tenant) => {
+                tenant_capacity::Entity::insert(tenant_capacity::ActiveModel {
+                    tenant_id: Set(tenant.to_string()),
+                    usage: Set(usage),
+                    gmt_create: Set(now),
+                    gmt_modified: Set(now),
+                    ..Default::default()
+                })
+                .exec(db)
+                .await
+                .map(|_| ())
+            }
+            CapacityScope::Group(group) => {
+                group_capacity::Entity::insert(group_capacity::ActiveModel {
+                    group_id: Set(group.to_string()),
+                    usage: Set(usage),
+                    gmt_create: Set(now),
+                    gmt_modified: Set(now),
+                    ..Default::default()
+                })
+                .exec(db)
+                .await
+                .map(|_| ())
+            }
+        };
+
+        match inserted {
+            Ok(()) => Ok(self.capacity(scope, 0, 0, usage)),
+            // a concurrent publish created the row first, the unique key refused this one
+            Err(err) => self.find(db, scope).await?.ok_or_else(|| err.into()),
+        }
+    }
+
+    // A quota or max_size of 0 falls back to the defaults
+    fn capacity(
+        &self,
+        scope: &CapacityScope<'_>,
+        quota: u32,
+        max_size: u32,
+        usage: u32,
+    ) -> Capacity {
+        Capacity {
+            quota: Some(quota).filter(|e| *e > 0).unwrap_or(match scope {
+                CapacityScope::Tenant(_) => self.default_tenant_quota,
+                CapacityScope::Group(_) => self.default_group_quota,
+            }),
+            max_size: Some(max_size)
+                .filter(|e| *e > 0)
+                .unwrap_or(self.default_max_size),
+            usage,
+        }
+    }
+}
+
+async fn count_usage(db: &DatabaseConnection, scope: &CapacityScope<'_>) -> anyhow::Result<u32> {
+    let select = match *scope {
+        CapacityScope::Tenant(tenant) => {
+            config_info::Entity::find().filter(config_info::Column::TenantId.eq(tenant))
+        }
+        CapacityScope::Group(group) => config_info::Entity::find()
+            .filter(config_info::Column::TenantId.eq(""))
+            .filter(config_info::Column::GroupId.eq(group)),
+    };
+
+    Ok(select.count(db).await? as u32)
+}
+
+fn quota_exceeded(scope: &CapacityScope<'_>, capacity: &Capacity) -> anyhow::Error {
+    BusinessError::CapacityExceeded(format!(
+        "{} reached its quota of {} configs",
+        scope.name(),
+        capacity.quota
+    ))
+    .into()
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::BTreeMap,
+    net::UdpSocket,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::Utc;
+use config::Config;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    model::cluster::{format_address, Member, NodeState, DEFAULT_SERVER_PORT},
+    service::{
+        self,
+        cluster_event::{ClusterEventLog, ClusterEventType},
+        member_lookup::{self, LookupType, MemberLookup},
+    },
+};
+
+const IDLE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupHealth {
+    pub lookup_type: String,
+    pub healthy: bool,
+    pub fail_count: u32,
+    pub last_success_time: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ServerMemberManager {
+    app_config: Config,
+    local_address: String,
+    server_list: RwLock<BTreeMap<String, Member>>,
+    lookup: RwLock<Arc<dyn MemberLookup>>,
+    lookup_health: RwLock<LookupHealth>,
+    events: ClusterEventLog,
+}
+
+impl ServerMemberManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let local_address = local_address(app_config);
+        let lookup_type = member_lookup::default_lookup_type(app_config)?;
+        let lookup = member_lookup::create_lookup(lookup_type, app_config, &local_address)?;
+
+        let mut server_list = BTreeMap::new();
+        let mut local_member = Member::from_address(&local_address);
+
+        local_member.state = NodeState::Starting;
+        server_list.insert(local_address.clone(), local_member);
+
+        Ok(Self {
+            app_config: app_config.clone(),
+            local_address,
+            server_list: RwLock::new(server_list),
+            lookup: RwLock::new(lookup),
+            lookup_health: RwLock::new(LookupHealth {
+                lookup_type: lookup_type.name().to_string(),
+                ..Default::default()
+            }),
+            events: ClusterEventLog::new(app_config),
+        })
+    }
+
+    pub async fn start(self: &Arc<Self>) {
+        self.refresh().await;
+
+        self.update_self_state(NodeState::Up);
+
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match manager.current_lookup().refresh_interval() {
+                    Some(interval) => {
+                        tokio::time::sleep(interval).await;
+
+                        if manager.current_lookup().is_modified().await {
+                            manager.refresh().await;
+                        }
+                    }
+                    None => tokio::time::sleep(IDLE_REFRESH_INTERVAL).await,
+                }
+            }
+        });
+    }
+
+    pub async fn refresh(&self) {
+        let lookup = self.current_lookup();
+        let result = lookup.lookup().await;
+
+        // the lookup may have been switched while this one was running
+        if lookup.lookup_type() != self.current_lookup().lookup_type() {
+            return;
+        }
+
+        let event = {
+            let mut health = self.lookup_health.write().unwrap();
+
+            match &result {
+                Ok(_) => {
+                    let event = (health.fail_count > 0).then(|| {
+                        (
+                            ClusterEventType::LookupRecovery,
+                            format!("{} lookup", health.lookup_type),
+                        )
+                    });
+
+                    health.healthy = true;
+                    health.fail_count = 0;
+                    health.last_success_time = Some(Utc::now().timestamp_millis());
+                    health.last_error = None;
+
+                    event
+                }
+                Err(err) => {
+                    tracing::warn!("member lookup {} failed: {}", health.lookup_type, err);
+
+                    // only the first failure in a row, a lookup down for long would flood the log
+                    let event = (health.fail_count == 0).then(|| {
+                        (
+                            ClusterEventType::LookupFailure,
+                            format!("{} lookup: {}", health.lookup_type, err),
+                        )
+                    });
+
+                    health.healthy = false;
+                    health.fail_count += 1;
+                    health.last_error = Some(err.to_string());
+
+                    event
+                }
+            }
+        };
+
+        // recording writes the event file, it happens once the locks are released
+        if let Some((event_type, detail)) = event {
+            self.events.record(event_type, &self.local_address, &detail);
+        }
+
+        if let Ok(members) = result {
+            self.member_change(members);
+        }
+    }
+
+    pub async fn switch_lookup(&self, lookup_type: LookupType) -> anyhow::Result<()> {
+        if self.current_lookup().lookup_type() == lookup_type {
+            return Ok(());
+        }
+
+        let lookup =
+            member_lookup::create_lookup(lookup_type, &self.app_config, &self.local_address)?;
+
+        *self.lookup.write().unwrap() = lookup;
+        *self.lookup_health.write().unwrap() = LookupHealth {
+            lookup_type: lookup_type.name().to_string(),
+            ..Default::default()
+        };
+
+        tracing::info!("member lookup switched to {}", lookup_type.name());
+
+        self.events.record(
+            ClusterEventType::LookupSwitch,
+            &self.local_address,
+            lookup_type.name(),
+        );
+
+        self.refresh().await;
+
+        Ok(())
+    }
+
+    // Recreates the file lookup with a reloaded nacos.member.list, the other lookups don't read
+    // it. The caller refreshes the members once it returns true
+    pub fn apply_member_list(&self, app_config: &Config) -> anyhow::Result<bool> {
+        if self.current_lookup().lookup_type() != LookupType::File {
+            return Ok(false);
+        }
+
+        let lookup =
+            member_lookup::create_lookup(LookupType::File, app_config, &self.local_address)?;
+
+        *self.lookup.write().unwrap() = lookup;
+
+        tracing::info!("member list reloaded");
+
+        Ok(true)
+    }
+
+    pub fn current_lookup(&self) -> Arc<dyn MemberLookup> {
+        self.lookup.read().unwrap().clone()
+    }
+
+    pub fn lookup_health(&self) -> LookupHealth {
+        self.lookup_health.read().unwrap().clone()
+    }
+
+    pub fn is_standalone(&self) -> bool {
+        self.current_lookup().lookup_type() == LookupType::Standalone
+    }
+
+    pub fn all_members(&self) -> Vec<Member> {
+        self.server_list.read().unwrap().values().cloned().collect()
+    }
+
+    // Address and base url, like http://10.0.0.2:8848/nacos, of every member but this node
+    pub fn peer_urls(&self, context_path: &str) -> Vec<(String, String)> {
+        let scheme = if service::tls::is_enabled(&self.app_config) {
+            "https"
+        } else {
+            "http"
+        };
+
+        self.all_members()
+            .into_iter()
+            .filter(|member| member.address != self.local_address)
+            .map(|member| {
+                let url = format!("{}://{}{}", scheme, member.address, context_path);
+
+                (member.address, url)
+            })
+            .collect()
+    }
+
+    pub fn update_self_state(&self, state: NodeState) {
+        let detail = match self
+            .server_list
+            .write()
+            .unwrap()
+            .get_mut(&self.local_address)
+        {
+            Some(member) if member.state != state => {
+                let detail = format!("{:?} -> {:?}", member.state, state).to_uppercase();
+
+                member.state = state;
+
+                detail
+            }
+            _ => return,
+        };
+
+        self.events
+            .record(ClusterEventType::StateChange, &self.local_address, &detail);
+    }
+
+    pub fn events(&self) -> &ClusterEventLog {
+        &self.events
+    }
+
+    pub fn get_self(&self) -> Member {
+        self.server_list
+            .read()
+            .unwrap()
+            .get(&self.local_address)
+            .cloned()
+            .unwrap_or_else(|| Member::from_address(&self.local_address))
+    }
+
+    fn member_change(&self, members: Vec<Member>) {
+        let mut server_list = self.server_list.write().unwrap();
+        let mut new_list = BTreeMap::new();
+        let mut events = Vec::new();
+
+        for mut member in members {
+            if let Some(old) = server_list.get(&member.address) {
+                member.state = old.state.clone();
+                member.fail_access_cnt = old.fail_access_cnt;
+                member.extend_info.extend(old.extend_info.clone());
+            } else {
+                tracing::info!("member join: {}", member.address);
+
+                events.push((ClusterEventType::MemberJoin, member.address.clone()));
+            }
+
+            new_list.insert(member.address.clone(), member);
+        }
+
+        if !new_list.contains_key(&self.local_address) {
+            if let Some(local_member) = server_list.get(&self.local_address) {
+                new_list.insert(self.local_address.clone(), local_member.clone());
+            }
+        }
+
+        server_list
+            .keys()
+            .filter(|address| !new_list.contains_key(*address))
+            .for_each(|address| {
+                tracing::info!("member leave: {}", address);
+
+                events.push((ClusterEventType::MemberLeave, address.clone()));
+            });
+
+        *server_list = new_list;
+
+        drop(server_list);
+
+        for (event_type, address) in events {
+            self.events.record(event_type, &address, "");
+        }
+    }
+}
+
+fn local_address(app_config: &Config) -> String {
+    let port = app_config
+        .get_int("server.port")
+        .unwrap_or(DEFAULT_SERVER_PORT as i64);
+
+    let ip = app_config
+        .get_string("nacos.inetutils.ip-address")
+        .ok()
+        .filter(|ip| !ip.is_empty())
+        .unwrap_or_else(|| {
+            // connecting a udp socket only selects the outbound interface, nothing is sent
+            UdpSocket::bind("0.0.0.0:0")
+                .and_then(|socket| {
+                    socket.connect("8.8.8.8:80")?;
+                    socket.local_addr()
+                })
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or(String::from("127.0.0.1"))
+        });
+
+    format_address(&ip, port as i32)
+}
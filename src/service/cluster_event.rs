@@ -0,0 +1,195 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use config::Config;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::common::Page;
+
+pub const CLUSTER_EVENTS_CAPACITY: &str = "nacos.core.cluster.events.capacity";
+pub const CLUSTER_EVENTS_FILE: &str = "nacos.core.cluster.events.file";
+
+const DEFAULT_CLUSTER_EVENTS_CAPACITY: i64 = 1000;
+const DEFAULT_CLUSTER_EVENTS_FILE: &str = "data/cluster-events.log";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClusterEventType {
+    MemberJoin,
+    MemberLeave,
+    StateChange,
+    LookupSwitch,
+    LookupFailure,
+    LookupRecovery,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterEvent {
+    pub id: u64,
+    pub event_type: ClusterEventType,
+    pub member: String,
+    pub detail: String,
+    pub time: i64,
+}
+
+#[derive(Debug, Default)]
+struct ClusterEventState {
+    next_id: u64,
+    events: VecDeque<ClusterEvent>,
+    file_lines: usize,
+}
+
+// Latest cluster lifecycle events seen by this node. They are appended to a file and read back
+// on start, so a timeline survives restarts. The file is rewritten with the kept events once it
+// holds twice the capacity
+#[derive(Debug)]
+pub struct ClusterEventLog {
+    capacity: usize,
+    path: Option<PathBuf>,
+    state: Mutex<ClusterEventState>,
+}
+
+impl ClusterEventLog {
+    pub fn new(app_config: &Config) -> Self {
+        let capacity = app_config
+            .get_int(CLUSTER_EVENTS_CAPACITY)
+            .unwrap_or(DEFAULT_CLUSTER_EVENTS_CAPACITY)
+            .max(0) as usize;
+        // an empty file keeps the events in memory only
+        let path = Some(
+            app_config
+                .get_string(CLUSTER_EVENTS_FILE)
+                .unwrap_or(DEFAULT_CLUSTER_EVENTS_FILE.to_string()),
+        )
+        .filter(|e| capacity > 0 && !e.is_empty())
+        .map(PathBuf::from);
+
+        let mut state = ClusterEventState::default();
+
+        if let Some(content) = path.as_ref().and_then(|path| fs::read_to_string(path).ok()) {
+            let events: Vec<ClusterEvent> = content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect();
+
+            state.next_id = events.iter().map(|e| e.id).max().unwrap_or_default();
+            state.file_lines = events.len();
+            state.events = events.into_iter().rev().take(capacity).rev().collect();
+        }
+
+        ClusterEventLog {
+            capacity,
+            path,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn record(&self, event_type: ClusterEventType, member: &str, detail: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        tracing::info!("cluster event {:?} {} {}", event_type, member, detail);
+
+        let mut state = self.state.lock().unwrap();
+
+        state.next_id += 1;
+
+        let event = ClusterEvent {
+            id: state.next_id,
+            event_type,
+            member: member.to_string(),
+            detail: detail.to_string(),
+            time: chrono::Utc::now().timestamp_millis(),
+        };
+
+        if state.events.len() >= self.capacity {
+            state.events.pop_front();
+        }
+
+        state.events.push_back(event.clone());
+
+        if let Some(path) = &self.path {
+            let result = if state.file_lines >= self.capacity * 2 {
+                rewrite(path, &state.events).map(|_| state.events.len())
+            } else {
+                append(path, &event).map(|_| state.file_lines + 1)
+            };
+
+            match result {
+                Ok(file_lines) => state.file_lines = file_lines,
+                Err(err) => {
+                    tracing::warn!("write cluster event {} failed: {}", path.display(), err)
+                }
+            }
+        }
+    }
+
+    // Newest first, empty filters match everything
+    pub fn search(
+        &self,
+        event_type: Option<ClusterEventType>,
+        member: &str,
+        page_no: u64,
+        page_size: u64,
+    ) -> Page<ClusterEvent> {
+        let state = self.state.lock().unwrap();
+        let matched: Vec<&ClusterEvent> = state
+            .events
+            .iter()
+            .rev()
+            .filter(|e| event_type.is_none_or(|event_type| e.event_type == event_type))
+            .filter(|e| member.is_empty() || e.member == member)
+            .collect();
+
+        if matched.is_empty() {
+            return Page::<ClusterEvent>::default();
+        }
+
+        let page_items = matched
+            .iter()
+            .skip(((page_no - 1) * page_size) as usize)
+            .take(page_size as usize)
+            .map(|e| (*e).clone())
+            .collect();
+
+        Page::<ClusterEvent>::new(matched.len() as u64, page_no, page_size, page_items)
+    }
+}
+
+fn rewrite(path: &PathBuf, events: &VecDeque<ClusterEvent>) -> anyhow::Result<()> {
+    let mut content = String::new();
+
+    for event in events {
+        content.push_str(&serde_json::to_string(event)?);
+        content.push('\n');
+    }
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn append(path: &PathBuf, event: &ClusterEvent) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+
+    Ok(())
+}
@@ -5,13 +5,19 @@ use sea_orm::*;
 use crate::{
     entity::{config_info, config_tags_relation, his_config_info},
     model::{
-        common::Page,
-        config::{ConfigAllInfo, ConfigInfo, ConfigInfoStateWrapper},
+        common::{BusinessError, Page},
+        config::{ConfigAllInfo, ConfigChange, ConfigInfo, ConfigInfoStateWrapper, ConfigSortBy},
     },
+    service::{capacity::CapacityPolicy, crypto::ContentCipher, ip_filter::split_list},
 };
 
+// op_type of the history entry keeping the content an update or a rollback replaced
+pub const OP_TYPE_UPDATE: &str = "U";
+pub const OP_TYPE_ROLLBACK: &str = "R";
+
 pub async fn search_page(
     db: &DatabaseConnection,
+    cipher: &ContentCipher,
     page_no: u64,
     page_size: u64,
     tenant: &str,
@@ -21,39 +27,80 @@ pub async fn search_page(
     config_tags: &str,
     types: &str,
     content: &str,
+    sort_by: ConfigSortBy,
+    descending: bool,
 ) -> anyhow::Result<Page<ConfigInfo>> {
-    let mut count_select =
-        config_info::Entity::find().filter(config_info::Column::TenantId.eq(tenant));
-    let mut query_select =
-        config_info::Entity::find().filter(config_info::Column::TenantId.eq(tenant));
+    let mut select = config_info::Entity::find().filter(config_info::Column::TenantId.eq(tenant));
 
     if !data_id.is_empty() {
-        count_select = count_select.filter(config_info::Column::DataId.contains(data_id));
-        query_select = query_select.filter(config_info::Column::DataId.contains(data_id));
+        select = select.filter(config_info::Column::DataId.contains(data_id));
     }
     if !group.is_empty() {
-        count_select = count_select.filter(config_info::Column::GroupId.contains(group));
-        query_select = query_select.filter(config_info::Column::GroupId.contains(group));
+        select = select.filter(config_info::Column::GroupId.contains(group));
     }
     if !app_name.is_empty() {
-        count_select = count_select.filter(config_info::Column::AppName.contains(app_name));
-        query_select = query_select.filter(config_info::Column::AppName.contains(app_name));
+        select = select.filter(config_info::Column::AppName.contains(app_name));
     }
     if !content.is_empty() {
-        count_select = count_select.filter(config_info::Column::Content.contains(content));
-        query_select = query_select.filter(config_info::Column::Content.contains(content));
+        // the stored content is ciphertext, a LIKE on it never matches
+        if cipher.is_enabled() {
+            return Err(BusinessError::ParameterValidate(String::from(
+                "config_detail search is not supported while config encryption is enabled",
+            ))
+            .into());
+        }
+
+        select = select.filter(config_info::Column::Content.contains(content));
+    }
+
+    let types = split_list(types);
+
+    if !types.is_empty() {
+        select = select.filter(config_info::Column::Type.is_in(types));
+    }
+
+    let config_tags = split_list(config_tags);
+
+    if !config_tags.is_empty() {
+        select = select.filter(
+            config_info::Column::Id.in_subquery(
+                sea_query::Query::select()
+                    .column(config_tags_relation::Column::Id)
+                    .from(config_tags_relation::Entity)
+                    .and_where(config_tags_relation::Column::TagName.is_in(config_tags))
+                    .to_owned(),
+            ),
+        );
     }
 
-    let total_count = count_select.count(db).await?;
+    let total_count = select.clone().count(db).await?;
 
     if total_count > 0 {
-        let page_items = query_select
+        let order = if descending { Order::Desc } else { Order::Asc };
+        let column = match sort_by {
+            ConfigSortBy::Id => config_info::Column::Id,
+            ConfigSortBy::DataId => config_info::Column::DataId,
+            ConfigSortBy::Group => config_info::Column::GroupId,
+            ConfigSortBy::AppName => config_info::Column::AppName,
+            ConfigSortBy::ModifiedTime => config_info::Column::GmtModified,
+        };
+
+        // id breaks ties so that pages stay stable
+        let page_items = select
+            .order_by(column, order.clone())
+            .order_by(config_info::Column::Id, order)
             .paginate(db, page_size)
             .fetch_page(page_no - 1)
             .await?
-            .iter()
-            .map(|entity| ConfigInfo::from(entity.clone()))
-            .collect();
+            .into_iter()
+            .map(|entity| {
+                let mut config_info = ConfigInfo::from(entity);
+
+                config_info.content = cipher.decrypt(&config_info.content)?;
+
+                anyhow::Ok(config_info)
+            })
+            .collect::<anyhow::Result<_>>()?;
 
         return anyhow::Ok(Page::<ConfigInfo>::new(
             total_count,
@@ -68,6 +115,7 @@ pub async fn search_page(
 
 pub async fn find_all(
     db: &DatabaseConnection,
+    cipher: &ContentCipher,
     data_id: &str,
     group: &str,
     tenant: &str,
@@ -91,7 +139,7 @@ pub async fn find_all(
         .one(db)
         .await?;
 
-    let config_all_info = config_all_info_result
+    let mut config_all_info = config_all_info_result
         .map(|entity| {
             let mut m = ConfigAllInfo::from(entity.clone());
 
@@ -99,7 +147,14 @@ pub async fn find_all(
 
             m
         })
-        .unwrap();
+        .ok_or_else(|| {
+            BusinessError::ResourceNotFound(format!(
+                "config data not exist, dataId={}, group={}, tenant={}",
+                data_id, group, tenant
+            ))
+        })?;
+
+    config_all_info.content = cipher.decrypt(&config_all_info.content)?;
 
     Ok(config_all_info)
 }
@@ -129,8 +184,82 @@ pub async fn find_state(
     anyhow::Ok(result)
 }
 
+pub async fn find_md5(
+    db: &DatabaseConnection,
+    data_id: &str,
+    group: &str,
+    tenant: &str,
+) -> anyhow::Result<Option<String>> {
+    let md5 = config_info::Entity::find()
+        .filter(config_info::Column::DataId.eq(data_id))
+        .filter(config_info::Column::GroupId.eq(group))
+        .filter(config_info::Column::TenantId.eq(tenant))
+        .one(db)
+        .await?
+        .map(|entity| entity.md5.unwrap_or_default());
+
+    Ok(md5)
+}
+
+// Publishes through the namespace or group capacity, unlike create_or_update
+pub async fn publish(
+    db: &DatabaseConnection,
+    cipher: &ContentCipher,
+    capacity_policy: &CapacityPolicy,
+    change: &ConfigChange,
+) -> anyhow::Result<bool> {
+    publish_as(db, cipher, capacity_policy, change, OP_TYPE_UPDATE).await
+}
+
+// The history entry of the replaced content is recorded with op_type, like R for a rollback
+pub async fn publish_as(
+    db: &DatabaseConnection,
+    cipher: &ContentCipher,
+    capacity_policy: &CapacityPolicy,
+    change: &ConfigChange,
+    op_type: &str,
+) -> anyhow::Result<bool> {
+    let is_new = find_md5(db, &change.data_id, &change.group, &change.tenant)
+        .await?
+        .is_none();
+
+    // usage is taken before the write so concurrent publishes cannot overshoot the quota
+    capacity_policy.acquire(db, change, is_new).await?;
+
+    let result = create_or_update(
+        db,
+        cipher,
+        &change.data_id,
+        &change.group,
+        &change.tenant,
+        &change.content,
+        &change.tag,
+        &change.app_name,
+        &change.src_user,
+        &change.src_ip,
+        &change.config_tags,
+        &change.desc,
+        &change.r#use,
+        &change.effect,
+        &change.r#type,
+        &change.schema,
+        &change.encrypted_data_key,
+        op_type,
+    )
+    .await;
+
+    // a concurrent publish of the same config may have created it, only the creator keeps it
+    if is_new && !matches!(result, Ok(true)) {
+        capacity_policy.release(db, change).await;
+    }
+
+    result
+}
+
+// Returns whether the config was created
 pub async fn create_or_update(
     db: &DatabaseConnection,
+    cipher: &ContentCipher,
     data_id: &str,
     group: &str,
     tenant: &str,
@@ -146,6 +275,7 @@ pub async fn create_or_update(
     r#type: &str,
     schema: &str,
     encrypted_data_key: &str,
+    update_op_type: &str,
 ) -> anyhow::Result<bool> {
     let entity_option = config_info::Entity::find()
         .filter(config_info::Column::DataId.eq(data_id))
@@ -161,8 +291,13 @@ pub async fn create_or_update(
             let entity_c = entity.clone();
             let mut model: config_info::ActiveModel = entity.into();
 
-            model.content = Set(Some(content.to_string()));
-            model.md5 = Set(Some(md5_digest(content)));
+            let md5 = md5_digest(content);
+
+            // encryption uses a random nonce, so only unchanged md5 tells the content is the same
+            if entity_c.md5.as_deref() != Some(md5.as_str()) {
+                model.content = Set(Some(cipher.encrypt(content)?));
+                model.md5 = Set(Some(md5));
+            }
             model.src_user = Set(Some(src_user.to_string()));
             model.src_ip = Set(Some((src_ip.to_string())));
             model.app_name = Set(Some(app_name.to_string()));
@@ -189,7 +324,7 @@ pub async fn create_or_update(
                     gmt_modified: Set(entity_c.gmt_modified.unwrap()),
                     src_user: Set(Some(entity_c.src_user.unwrap_or_default())),
                     src_ip: Set(Some(entity_c.src_ip.unwrap_or_default())),
-                    op_type: Set(Some(String::from("U"))),
+                    op_type: Set(Some(update_op_type.to_string())),
                     tenant_id: Set(Some(entity_c.tenant_id.unwrap_or_default())),
                     encrypted_data_key: Set(entity_c.encrypted_data_key.unwrap_or_default()),
                     ..Default::default()
@@ -198,13 +333,13 @@ pub async fn create_or_update(
                 .await?;
             }
 
-            anyhow::Ok(true)
+            anyhow::Ok(false)
         }
         None => {
             let model = config_info::ActiveModel {
                 data_id: Set(data_id.to_string()),
                 group_id: Set(Some(group.to_string())),
-                content: Set(Some(content.to_string())),
+                content: Set(Some(cipher.encrypt(content)?)),
                 md5: Set(Some(md5_digest(content))),
                 gmt_create: Set(Some(now)),
                 gmt_modified: Set(Some(now)),
@@ -287,7 +422,7 @@ async fn insert_config_history_atomic(
     Ok(())
 }
 
-fn md5_digest(content: &str) -> String {
+pub fn md5_digest(content: &str) -> String {
     let mut md5 = Md5::new();
 
     md5.input_str(content);
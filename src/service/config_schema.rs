@@ -0,0 +1,133 @@
+use regex::Regex;
+use sea_orm::*;
+use serde_json::Value;
+
+use crate::{
+    entity::config_info,
+    model::config::{ConfigChange, SchemaViolation},
+    service::crypto::ContentCipher,
+};
+
+// Schemas are plain configs of this group so they get history and rollback like any other config
+pub const SCHEMA_GROUP: &str = "nacos.schema";
+// The dataId pattern a schema applies to, * matches any characters
+pub const DATA_ID_KEYWORD: &str = "x-nacos-data-id";
+
+// Check a change against the schemas of its namespace, or check the schema itself when it is one
+pub async fn validate(
+    db: &DatabaseConnection,
+    cipher: &ContentCipher,
+    change: &ConfigChange,
+) -> anyhow::Result<Vec<SchemaViolation>> {
+    if change.group == SCHEMA_GROUP {
+        return Ok(check_schema(&change.data_id, &change.content)
+            .err()
+            .into_iter()
+            .collect());
+    }
+
+    let schemas = config_info::Entity::find()
+        .filter(config_info::Column::GroupId.eq(SCHEMA_GROUP))
+        .filter(config_info::Column::TenantId.eq(change.tenant.as_str()))
+        .all(db)
+        .await?;
+    let mut violations = Vec::new();
+    let mut content = None;
+
+    for entity in schemas {
+        let schema = match serde_json::from_str::<Value>(
+            &cipher.decrypt(&entity.content.unwrap_or_default())?,
+        ) {
+            Ok(schema) => schema,
+            Err(err) => {
+                tracing::warn!("skip invalid schema {}: {}", entity.data_id, err);
+
+                continue;
+            }
+        };
+
+        if !schema
+            .get(DATA_ID_KEYWORD)
+            .and_then(Value::as_str)
+            .is_some_and(|pattern| matches_pattern(pattern, &change.data_id))
+        {
+            continue;
+        }
+
+        let validator = match jsonschema::validator_for(&schema) {
+            Ok(validator) => validator,
+            Err(err) => {
+                tracing::warn!("skip invalid schema {}: {}", entity.data_id, err);
+
+                continue;
+            }
+        };
+
+        if content.is_none() {
+            match serde_json::from_str::<Value>(&change.content) {
+                Ok(value) => content = Some(value),
+                Err(err) => {
+                    return Ok(vec![SchemaViolation {
+                        schema: entity.data_id,
+                        path: String::new(),
+                        message: format!("content is not valid json: {}", err),
+                    }]);
+                }
+            }
+        }
+
+        if let Some(content) = &content {
+            violations.extend(validator.iter_errors(content).map(|err| SchemaViolation {
+                schema: entity.data_id.clone(),
+                path: err.instance_path.to_string(),
+                message: err.to_string(),
+            }));
+        }
+    }
+
+    Ok(violations)
+}
+
+fn check_schema(data_id: &str, content: &str) -> Result<(), SchemaViolation> {
+    let violation = |message: String| SchemaViolation {
+        schema: data_id.to_string(),
+        path: String::new(),
+        message,
+    };
+    let schema = serde_json::from_str::<Value>(content)
+        .map_err(|err| violation(format!("schema is not valid json: {}", err)))?;
+
+    if schema
+        .get(DATA_ID_KEYWORD)
+        .and_then(Value::as_str)
+        .is_none()
+    {
+        return Err(violation(format!(
+            "schema has no '{}' dataId pattern",
+            DATA_ID_KEYWORD
+        )));
+    }
+
+    jsonschema::validator_for(&schema)
+        .map(|_| ())
+        .map_err(|err| violation(format!("invalid schema: {}", err)))
+}
+
+pub fn matches_pattern(pattern: &str, value: &str) -> bool {
+    pattern_regex(pattern).is_match(value)
+}
+
+// * matches any characters, everything else matches itself
+pub fn pattern_regex(pattern: &str) -> Regex {
+    let regex = format!(
+        "^{}$",
+        pattern
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*")
+    );
+
+    // escaped literals joined by .* always compile
+    Regex::new(&regex).unwrap()
+}
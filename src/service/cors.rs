@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use config::Config;
+
+use crate::{model::auth::ApiType, service::ip_filter::split_list};
+
+const DEFAULT_METHODS: &str = "GET,POST,PUT,DELETE,OPTIONS";
+const DEFAULT_HEADERS: &str = "*";
+const DEFAULT_MAX_AGE: i64 = 1800;
+
+#[derive(Clone, Debug, Default)]
+pub struct CorsPolicy {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub exposed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age: i64,
+}
+
+impl CorsPolicy {
+    // Each key of the api group falls back to the shared nacos.core.cors.<key>
+    fn new(app_config: &Config, api_type: ApiType) -> Self {
+        let get = |key: &str| {
+            app_config
+                .get_string(&format!("nacos.core.cors.{}.{}", api_type.key(), key))
+                .or_else(|_| app_config.get_string(&format!("nacos.core.cors.{}", key)))
+        };
+
+        CorsPolicy {
+            allowed_origins: split_list(&get("allowed-origins").unwrap_or_default()),
+            allowed_methods: split_list(
+                &get("allowed-methods").unwrap_or(DEFAULT_METHODS.to_string()),
+            ),
+            allowed_headers: split_list(
+                &get("allowed-headers").unwrap_or(DEFAULT_HEADERS.to_string()),
+            ),
+            exposed_headers: split_list(&get("exposed-headers").unwrap_or_default()),
+            allow_credentials: get("allow-credentials")
+                .ok()
+                .and_then(|e| e.parse().ok())
+                .unwrap_or(false),
+            max_age: get("max-age")
+                .ok()
+                .and_then(|e| e.parse().ok())
+                .unwrap_or(DEFAULT_MAX_AGE),
+        }
+    }
+
+    // Origins match exactly or through a leading wildcard like https://*.example.com
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins.iter().any(|allowed| {
+            if allowed == "*" {
+                return true;
+            }
+
+            match allowed.split_once('*') {
+                Some((prefix, suffix)) => {
+                    origin.len() > prefix.len() + suffix.len()
+                        && origin.starts_with(prefix)
+                        && origin.ends_with(suffix)
+                }
+                None => allowed.eq_ignore_ascii_case(origin),
+            }
+        })
+    }
+
+    pub fn allows_method(&self, method: &str) -> bool {
+        self.allowed_methods
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(method))
+    }
+
+    // A wildcard echoes whatever the preflight asked for
+    pub fn allow_headers_value(&self, requested: Option<&str>) -> Option<String> {
+        if self.allowed_headers.iter().any(|e| e == "*") {
+            return requested.map(String::from);
+        }
+
+        if self.allowed_headers.is_empty() {
+            return None;
+        }
+
+        Some(self.allowed_headers.join(","))
+    }
+
+    // Browsers reject a wildcard origin on credentialed requests, so echo it back instead
+    pub fn allow_origin_value(&self, origin: &str) -> String {
+        if !self.allow_credentials && self.allowed_origins.iter().any(|e| e == "*") {
+            return String::from("*");
+        }
+
+        origin.to_string()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CorsManager {
+    enabled: bool,
+    policies: HashMap<ApiType, CorsPolicy>,
+}
+
+impl CorsManager {
+    pub fn new(app_config: &Config) -> Self {
+        let enabled = app_config
+            .get_bool("nacos.core.cors.enabled")
+            .unwrap_or(false);
+        let policies = ApiType::ALL
+            .into_iter()
+            .map(|api_type| (api_type, CorsPolicy::new(app_config, api_type)))
+            .collect();
+
+        CorsManager { enabled, policies }
+    }
+
+    // None when cors is off or the api group allows no origin at all
+    pub fn policy(&self, api_type: ApiType) -> Option<&CorsPolicy> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.policies
+            .get(&api_type)
+            .filter(|policy| !policy.allowed_origins.is_empty())
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use config::Config;
+
+use crate::service::ip_filter::split_list;
+
+pub const ENCRYPTION_ENABLED: &str = "nacos.core.config.encryption.enabled";
+pub const ENCRYPTION_KEY_ID: &str = "nacos.core.config.encryption.key-id";
+pub const ENCRYPTION_KEYS: &str = "nacos.core.config.encryption.keys";
+
+// Stored content looks like enc:<key id>:<base64 of the provider output>, anything else is
+// plaintext written before encryption was turned on
+const ENCRYPTED_PREFIX: &str = "enc:";
+const NONCE_LENGTH: usize = 12;
+
+// Encrypts config content at rest, implemented by local keys and by key management services
+pub trait CipherProvider: Debug + Send + Sync {
+    fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>>;
+
+    fn decrypt(&self, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>>;
+}
+
+// AES-256-GCM with a local key, the random nonce is stored in front of the ciphertext
+pub struct AesGcmProvider {
+    cipher: Aes256Gcm,
+}
+
+impl Debug for AesGcmProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AesGcmProvider").finish_non_exhaustive()
+    }
+}
+
+impl AesGcmProvider {
+    pub fn new(key: &[u8]) -> anyhow::Result<Self> {
+        if key.len() != 32 {
+            anyhow::bail!("aes-gcm key must be 32 bytes, got {}", key.len());
+        }
+
+        Ok(AesGcmProvider {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+}
+
+impl CipherProvider for AesGcmProvider {
+    fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow::anyhow!("aes-gcm encryption failed"))?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if ciphertext.len() < NONCE_LENGTH {
+            anyhow::bail!("aes-gcm ciphertext too short");
+        }
+
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_LENGTH);
+
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("aes-gcm decryption failed"))
+    }
+}
+
+// Encrypts with the current key, earlier keys stay available to read content written with them.
+// The default one leaves content in plaintext and only reads plaintext
+#[derive(Debug, Default)]
+pub struct ContentCipher {
+    key_id: Option<String>,
+    providers: HashMap<String, Arc<dyn CipherProvider>>,
+}
+
+impl ContentCipher {
+    pub fn new(
+        key_id: &str,
+        providers: HashMap<String, Arc<dyn CipherProvider>>,
+    ) -> anyhow::Result<Self> {
+        if !providers.contains_key(key_id) {
+            anyhow::bail!("no encryption key with id {}", key_id);
+        }
+
+        Ok(ContentCipher {
+            key_id: Some(key_id.to_string()),
+            providers,
+        })
+    }
+
+    // Local keys are configured as comma separated id=base64 pairs
+    pub fn from_config(app_config: &Config) -> anyhow::Result<Self> {
+        if !app_config.get_bool(ENCRYPTION_ENABLED).unwrap_or(false) {
+            return Ok(Self::default());
+        }
+
+        let mut providers: HashMap<String, Arc<dyn CipherProvider>> = HashMap::new();
+
+        for pair in split_list(&app_config.get_string(ENCRYPTION_KEYS).unwrap_or_default()) {
+            let (id, key) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("invalid encryption key: {}", pair))?;
+
+            providers.insert(
+                id.trim().to_string(),
+                Arc::new(AesGcmProvider::new(&STANDARD.decode(key.trim())?)?),
+            );
+        }
+
+        Self::new(&app_config.get_string(ENCRYPTION_KEY_ID)?, providers)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.key_id.is_some()
+    }
+
+    pub fn encrypt(&self, content: &str) -> anyhow::Result<String> {
+        let Some(key_id) = &self.key_id else {
+            return Ok(content.to_string());
+        };
+        let ciphertext = self.providers[key_id].encrypt(content.as_bytes())?;
+
+        Ok(format!(
+            "{}{}:{}",
+            ENCRYPTED_PREFIX,
+            key_id,
+            STANDARD.encode(ciphertext)
+        ))
+    }
+
+    // Content written with a key that is not configured any more is an error, never returned
+    // as ciphertext
+    pub fn decrypt(&self, stored: &str) -> anyhow::Result<String> {
+        let Some((key_id, ciphertext)) = stored
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .and_then(|e| e.split_once(':'))
+        else {
+            return Ok(stored.to_string());
+        };
+        let provider = self
+            .providers
+            .get(key_id)
+            .ok_or_else(|| anyhow::anyhow!("no encryption key with id {}", key_id))?;
+
+        Ok(String::from_utf8(
+            provider.decrypt(&STANDARD.decode(ciphertext)?)?,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(key_id: &str, keys: &[(&str, u8)]) -> ContentCipher {
+        let providers = keys
+            .iter()
+            .map(|(id, byte)| {
+                let provider: Arc<dyn CipherProvider> =
+                    Arc::new(AesGcmProvider::new(&[*byte; 32]).unwrap());
+
+                (id.to_string(), provider)
+            })
+            .collect();
+
+        ContentCipher::new(key_id, providers).unwrap()
+    }
+
+    #[test]
+    fn encrypted_content_decrypts_to_the_original() {
+        let cipher = cipher("k1", &[("k1", 1)]);
+        let stored = cipher.encrypt("a=1").unwrap();
+
+        assert!(stored.starts_with("enc:k1:"));
+        assert_ne!(cipher.encrypt("a=1").unwrap(), stored);
+        assert_eq!(cipher.decrypt(&stored).unwrap(), "a=1");
+    }
+
+    #[test]
+    fn plaintext_is_read_as_is() {
+        assert_eq!(cipher("k1", &[("k1", 1)]).decrypt("a=1").unwrap(), "a=1");
+        assert_eq!(ContentCipher::default().decrypt("a=1").unwrap(), "a=1");
+        assert_eq!(ContentCipher::default().encrypt("a=1").unwrap(), "a=1");
+    }
+
+    #[test]
+    fn rotated_key_still_reads_content_of_the_old_one() {
+        let stored = cipher("k1", &[("k1", 1)]).encrypt("a=1").unwrap();
+        let rotated = cipher("k2", &[("k1", 1), ("k2", 2)]);
+
+        assert_eq!(rotated.decrypt(&stored).unwrap(), "a=1");
+        assert!(rotated.encrypt("a=1").unwrap().starts_with("enc:k2:"));
+    }
+
+    #[test]
+    fn content_of_an_unknown_key_is_an_error() {
+        let stored = cipher("k1", &[("k1", 1)]).encrypt("a=1").unwrap();
+
+        assert!(cipher("k2", &[("k2", 2)]).decrypt(&stored).is_err());
+        assert!(ContentCipher::default().decrypt(&stored).is_err());
+        // same id, different key material
+        assert!(cipher("k1", &[("k1", 3)]).decrypt(&stored).is_err());
+    }
+
+    #[test]
+    fn current_key_has_to_be_configured() {
+        assert!(ContentCipher::new("k1", HashMap::new()).is_err());
+        assert!(!ContentCipher::default().is_enabled());
+        assert!(cipher("k1", &[("k1", 1)]).is_enabled());
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use config::Config;
+use sea_orm::{ConnectOptions, DatabaseConnection};
+
+pub const USERNAME_FILE: &str = "db.secret.username-file";
+pub const PASSWORD_FILE: &str = "db.secret.password-file";
+pub const WATCH_INTERVAL: &str = "db.secret.watch-interval";
+
+const DEFAULT_WATCH_INTERVAL: u64 = 10000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+// Database username / password mounted as secret files, they override the ones in db.url
+#[derive(Clone, Debug)]
+pub struct CredentialFiles {
+    username_file: Option<PathBuf>,
+    password_file: Option<PathBuf>,
+    watch_interval: Duration,
+}
+
+impl CredentialFiles {
+    pub fn new(app_config: &Config) -> Option<Self> {
+        let username_file = app_config.get_string(USERNAME_FILE).ok().map(PathBuf::from);
+        let password_file = app_config.get_string(PASSWORD_FILE).ok().map(PathBuf::from);
+
+        if username_file.is_none() && password_file.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            username_file,
+            password_file,
+            watch_interval: Duration::from_millis(
+                app_config
+                    .get_int(WATCH_INTERVAL)
+                    .unwrap_or(DEFAULT_WATCH_INTERVAL as i64) as u64,
+            ),
+        })
+    }
+
+    pub fn read(&self) -> anyhow::Result<Credentials> {
+        Ok(Credentials {
+            username: read_secret(&self.username_file)?,
+            password: read_secret(&self.password_file)?,
+        })
+    }
+
+    fn modified_times(&self) -> Vec<Option<SystemTime>> {
+        [&self.username_file, &self.password_file]
+            .iter()
+            .map(|path| {
+                path.as_ref().and_then(|path| {
+                    std::fs::metadata(path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                })
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, opt: &mut ConnectOptions) -> anyhow::Result<()> {
+        let credentials = self.read()?;
+
+        opt.map_sqlx_mysql_opts(move |options| with_credentials(options, &credentials));
+
+        Ok(())
+    }
+
+    // Poll the secret files and switch the pool to the new credentials when they change,
+    // pooled connections keep the old ones until they are recycled
+    pub fn start_rotation(self, db: DatabaseConnection) {
+        tokio::spawn(async move {
+            let mut modified_times = self.modified_times();
+
+            loop {
+                tokio::time::sleep(self.watch_interval).await;
+
+                let current_modified_times = self.modified_times();
+
+                if current_modified_times == modified_times {
+                    continue;
+                }
+
+                modified_times = current_modified_times;
+
+                match self.read() {
+                    Ok(credentials) => {
+                        let pool = db.get_mysql_connection_pool();
+
+                        pool.set_connect_options(with_credentials(
+                            pool.connect_options().as_ref().clone(),
+                            &credentials,
+                        ));
+
+                        tracing::info!("database credentials rotated");
+                    }
+                    Err(err) => tracing::warn!("reload database credentials failed: {}", err),
+                }
+            }
+        });
+    }
+}
+
+fn read_secret(path: &Option<PathBuf>) -> anyhow::Result<Option<String>> {
+    match path {
+        Some(path) => std::fs::read_to_string(path)
+            .map(|content| Some(content.trim().to_string()))
+            .map_err(|e| anyhow::anyhow!("read {} failed: {}", path.display(), e)),
+        None => Ok(None),
+    }
+}
+
+fn with_credentials(
+    mut options: sqlx::mysql::MySqlConnectOptions,
+    credentials: &Credentials,
+) -> sqlx::mysql::MySqlConnectOptions {
+    if let Some(username) = &credentials.username {
+        options = options.username(username);
+    }
+
+    if let Some(password) = &credentials.password {
+        options = options.password(password);
+    }
+
+    options
+}
@@ -0,0 +1,87 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use config::Config;
+use sea_orm::DatabaseConnection;
+
+pub const SAMPLE_INTERVAL: &str = "db.pool.metrics.sample-interval";
+
+const DEFAULT_SAMPLE_INTERVAL: u64 = 10000;
+
+#[derive(Clone, Debug, Default)]
+pub struct PoolStats {
+    pub max_connections: u32,
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub acquire_seconds: f64,
+    pub acquire_failures: u64,
+}
+
+// Connection pool gauges, sqlx does not track waiters or acquire times so the acquire latency
+// is sampled by checking out a connection every interval
+#[derive(Debug)]
+pub struct PoolMonitor {
+    db: DatabaseConnection,
+    max_connections: u32,
+    sample_interval: Duration,
+    acquire_micros: AtomicU64,
+    acquire_failures: AtomicU64,
+}
+
+impl PoolMonitor {
+    pub fn new(app_config: &Config, max_connections: u32, db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            max_connections,
+            sample_interval: Duration::from_millis(
+                app_config
+                    .get_int(SAMPLE_INTERVAL)
+                    .unwrap_or(DEFAULT_SAMPLE_INTERVAL as i64) as u64,
+            ),
+            acquire_micros: AtomicU64::new(0),
+            acquire_failures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.sample_interval).await;
+
+                let start = Instant::now();
+
+                match self.db.get_mysql_connection_pool().acquire().await {
+                    Ok(_) => self
+                        .acquire_micros
+                        .store(start.elapsed().as_micros() as u64, Ordering::Relaxed),
+                    Err(err) => {
+                        self.acquire_failures.fetch_add(1, Ordering::Relaxed);
+
+                        tracing::warn!("database pool acquire failed: {}", err);
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let pool = self.db.get_mysql_connection_pool();
+        let size = pool.size();
+        let idle = pool.num_idle() as u32;
+
+        PoolStats {
+            max_connections: self.max_connections,
+            size,
+            idle,
+            in_use: size.saturating_sub(idle),
+            acquire_seconds: self.acquire_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            acquire_failures: self.acquire_failures.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{
+    model::config::ConfigChange,
+    service::{config::md5_digest, config_schema::matches_pattern},
+};
+
+const EVENT_CAPACITY: usize = 1024;
+
+// Typed events for processes embedding the server, subscribers that lag behind miss events
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "SCREAMING_SNAKE_CASE",
+    rename_all_fields = "camelCase"
+)]
+#[non_exhaustive]
+pub enum ServerEvent {
+    ConfigChanged {
+        data_id: String,
+        group: String,
+        tenant: String,
+        md5: String,
+    },
+}
+
+impl ServerEvent {
+    pub fn config_changed(change: &ConfigChange) -> Self {
+        ServerEvent::ConfigChanged {
+            data_id: change.data_id.clone(),
+            group: change.group.clone(),
+            tenant: change.tenant.clone(),
+            md5: md5_digest(&change.content),
+        }
+    }
+}
+
+// Config changes of one namespace, the group and dataId are patterns where * matches any
+// characters and empty matches everything
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConfigSubscription {
+    pub tenant: String,
+    pub group: String,
+    pub data_id: String,
+}
+
+impl ConfigSubscription {
+    pub fn matches(&self, event: &ServerEvent) -> bool {
+        let pattern_matches =
+            |pattern: &str, value: &str| pattern.is_empty() || matches_pattern(pattern, value);
+
+        match event {
+            ServerEvent::ConfigChanged {
+                data_id,
+                group,
+                tenant,
+                ..
+            } => {
+                self.tenant == *tenant
+                    && pattern_matches(&self.group, group)
+                    && pattern_matches(&self.data_id, data_id)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus {
+            sender: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+
+    // Sending only fails without subscribers, which is fine
+    pub fn publish(&self, event: ServerEvent) {
+        let _ = self.sender.send(event);
+    }
+}
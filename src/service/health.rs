@@ -0,0 +1,97 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use config::Config;
+use sea_orm::DatabaseConnection;
+
+use crate::{model::cluster::NodeState, service::cluster::ServerMemberManager};
+
+pub const CHECK_INTERVAL: &str = "nacos.core.self-health.check-interval";
+pub const ISOLATION_THRESHOLD: &str = "nacos.core.self-health.isolation-threshold";
+
+const DEFAULT_CHECK_INTERVAL: u64 = 5000;
+const DEFAULT_ISOLATION_THRESHOLD: u64 = 30000;
+
+// Mark the local member SUSPICIOUS when the database stays unreachable longer than the threshold,
+// and back UP once it responds again
+pub fn start_self_health_check(
+    app_config: &Config,
+    db: DatabaseConnection,
+    member_manager: Arc<ServerMemberManager>,
+) {
+    let check_interval = Duration::from_millis(
+        app_config
+            .get_int(CHECK_INTERVAL)
+            .unwrap_or(DEFAULT_CHECK_INTERVAL as i64) as u64,
+    );
+    let isolation_threshold = Duration::from_millis(
+        app_config
+            .get_int(ISOLATION_THRESHOLD)
+            .unwrap_or(DEFAULT_ISOLATION_THRESHOLD as i64) as u64,
+    );
+
+    tokio::spawn(async move {
+        let mut unhealthy_since: Option<Instant> = None;
+
+        loop {
+            tokio::time::sleep(check_interval).await;
+
+            let state = member_manager.get_self().state;
+
+            match db.ping().await {
+                Ok(()) => {
+                    unhealthy_since = None;
+
+                    if state == NodeState::Suspicious {
+                        tracing::info!("database is reachable again, node recovered");
+
+                        member_manager.update_self_state(NodeState::Up);
+                    }
+                }
+                Err(err) => {
+                    let since = *unhealthy_since.get_or_insert_with(Instant::now);
+
+                    tracing::warn!("database health check failed: {}", err);
+
+                    if state == NodeState::Up && since.elapsed() >= isolation_threshold {
+                        tracing::error!(
+                            "database unhealthy for {:?}, node isolated itself",
+                            since.elapsed()
+                        );
+
+                        member_manager.update_self_state(NodeState::Suspicious);
+                    }
+                }
+            }
+        }
+    });
+}
+
+pub fn is_healthy(member_manager: &ServerMemberManager) -> bool {
+    member_manager.get_self().state == NodeState::Up
+}
+
+// Writes are refused while the node isolated itself, and while maintenance drains it. A node
+// still starting is not healthy yet but takes writes
+pub fn refuses_writes(state: NodeState) -> bool {
+    matches!(
+        state,
+        NodeState::Suspicious | NodeState::Isolation | NodeState::Down
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_isolated_or_drained_nodes_refuse_writes() {
+        assert!(refuses_writes(NodeState::Suspicious));
+        assert!(refuses_writes(NodeState::Isolation));
+        assert!(refuses_writes(NodeState::Down));
+        assert!(!refuses_writes(NodeState::Up));
+        assert!(!refuses_writes(NodeState::Starting));
+    }
+}
@@ -3,13 +3,18 @@ use sea_orm::*;
 use crate::{
     entity::{config_info, his_config_info},
     model::{
-        common::Page,
-        config::{ConfigHistoryInfo, ConfigInfoWrapper},
+        common::{BusinessError, Page},
+        config::{
+            ConfigAllInfo, ConfigChange, ConfigHistoryInfo, ConfigInfoWrapper, HistoryAuditPage,
+            HistoryAuditQuery,
+        },
     },
+    service::{config, crypto::ContentCipher},
 };
 
 pub async fn search_page(
     db: &DatabaseConnection,
+    cipher: &ContentCipher,
     data_id: &str,
     group: &str,
     tenant: &str,
@@ -32,9 +37,9 @@ pub async fn search_page(
             .paginate(db, page_size)
             .fetch_page(page_no - 1)
             .await?
-            .iter()
-            .map(|entity| ConfigHistoryInfo::from(entity.clone()))
-            .collect();
+            .into_iter()
+            .map(|entity| decrypt(cipher, entity))
+            .collect::<anyhow::Result<_>>()?;
 
         return anyhow::Ok(Page::<ConfigHistoryInfo>::new(
             total_count,
@@ -49,6 +54,7 @@ pub async fn search_page(
 
 pub async fn get_by_id(
     db: &DatabaseConnection,
+    cipher: &ContentCipher,
     id: u64,
 ) -> anyhow::Result<Option<ConfigHistoryInfo>> {
     let config_history_info = his_config_info::Entity::find_by_id(id)
@@ -71,10 +77,65 @@ pub async fn get_by_id(
         ])
         .one(db)
         .await?
-        .map(|entity| ConfigHistoryInfo::from(entity));
+        .map(|entity| decrypt(cipher, entity))
+        .transpose()?;
 
     Ok(config_history_info)
 }
+// The change restoring the content of history entry nid. History does not keep the type,
+// description and the like, so those of the current config are kept, which is returned too
+pub async fn rollback_change(
+    db: &DatabaseConnection,
+    cipher: &ContentCipher,
+    nid: u64,
+    src_user: &str,
+    src_ip: &str,
+) -> anyhow::Result<(ConfigChange, Option<ConfigAllInfo>)> {
+    let history = get_by_id(db, cipher, nid).await?.ok_or_else(|| {
+        BusinessError::ResourceNotFound(format!("config history {} not exist", nid))
+    })?;
+    let current =
+        match config::find_md5(db, &history.data_id, &history.group, &history.tenant).await? {
+            Some(_) => Some(
+                config::find_all(
+                    db,
+                    cipher,
+                    &history.data_id,
+                    &history.group,
+                    &history.tenant,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+    let base = current.clone().unwrap_or_default();
+
+    let change = ConfigChange {
+        data_id: history.data_id,
+        group: history.group,
+        tenant: history.tenant,
+        content: history.content,
+        tag: String::new(),
+        app_name: history.app_name,
+        src_user: src_user.to_string(),
+        src_ip: src_ip.to_string(),
+        config_tags: base.config_tags,
+        desc: base.desc,
+        r#use: base.r#use,
+        effect: base.effect,
+        r#type: if base._type.is_empty() {
+            String::from("text")
+        } else {
+            base._type
+        },
+        schema: base.schema,
+        encrypted_data_key: history.encrypted_data_key,
+        op_type: config::OP_TYPE_ROLLBACK.to_string(),
+    };
+
+    Ok((change, current))
+}
+
 pub async fn get_config_list_by_namespace(
     db: &DatabaseConnection,
     namespace_id: &str,
@@ -100,3 +161,60 @@ pub async fn get_config_list_by_namespace(
 
     Ok(config_infos)
 }
+
+// History of all configs of a namespace, paged by nid so that deep pages stay cheap
+pub async fn search_audit(
+    db: &DatabaseConnection,
+    cipher: &ContentCipher,
+    query: &HistoryAuditQuery,
+) -> anyhow::Result<HistoryAuditPage> {
+    let mut select =
+        his_config_info::Entity::find().filter(his_config_info::Column::TenantId.eq(&query.tenant));
+
+    if let Some(start_time) = query.start_time {
+        select = select.filter(his_config_info::Column::GmtModified.gte(start_time));
+    }
+    if let Some(end_time) = query.end_time {
+        select = select.filter(his_config_info::Column::GmtModified.lt(end_time));
+    }
+    if !query.src_user.is_empty() {
+        select = select.filter(his_config_info::Column::SrcUser.eq(&query.src_user));
+    }
+    if !query.op_type.is_empty() {
+        select = select.filter(his_config_info::Column::OpType.eq(&query.op_type));
+    }
+    if let Some(cursor) = query.cursor {
+        select = select.filter(his_config_info::Column::Nid.lt(cursor));
+    }
+
+    let page_items: Vec<ConfigHistoryInfo> = select
+        .order_by_desc(his_config_info::Column::Nid)
+        .limit(query.page_size)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|entity| decrypt(cipher, entity))
+        .collect::<anyhow::Result<_>>()?;
+
+    let next_cursor = if page_items.len() as u64 == query.page_size {
+        page_items.last().map(|e| e.id)
+    } else {
+        None
+    };
+
+    Ok(HistoryAuditPage {
+        page_items,
+        next_cursor,
+    })
+}
+
+fn decrypt(
+    cipher: &ContentCipher,
+    entity: his_config_info::Model,
+) -> anyhow::Result<ConfigHistoryInfo> {
+    let mut config_history_info = ConfigHistoryInfo::from(entity);
+
+    config_history_info.content = cipher.decrypt(&config_history_info.content)?;
+
+    Ok(config_history_info)
+}
@@ -0,0 +1,81 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use bytes::Bytes;
+use config::Config;
+
+use crate::model::common::BusinessError;
+
+pub const IDEMPOTENCY_WINDOW: &str = "nacos.core.idempotency.window";
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const DEFAULT_IDEMPOTENCY_WINDOW: i64 = 300;
+
+#[derive(Clone, Debug)]
+pub struct IdempotentResult {
+    pub status: u16,
+    pub body: Bytes,
+}
+
+#[derive(Debug)]
+enum Entry {
+    InFlight,
+    Done(IdempotentResult),
+}
+
+// Results of write requests by idempotency key, a duplicate key within the window replays
+// the original result instead of applying the write again
+#[derive(Debug, Default)]
+pub struct IdempotencyManager {
+    window: i64,
+    entries: Mutex<HashMap<String, (Entry, i64)>>,
+}
+
+impl IdempotencyManager {
+    pub fn new(app_config: &Config) -> Self {
+        Self {
+            window: app_config
+                .get_int(IDEMPOTENCY_WINDOW)
+                .unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW),
+            ..Default::default()
+        }
+    }
+
+    // Reserve the key for a new request, or return the result recorded for it
+    pub fn begin(&self, key: &str) -> anyhow::Result<Option<IdempotentResult>> {
+        let now = chrono::Utc::now().timestamp();
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.retain(|_, (_, expire_time)| *expire_time > now);
+
+        match entries.get(key) {
+            Some((Entry::Done(result), _)) => Ok(Some(result.clone())),
+            Some((Entry::InFlight, _)) => Err(BusinessError::ResourceConflict(format!(
+                "request {} is still in progress",
+                key
+            ))
+            .into()),
+            None => {
+                entries.insert(key.to_string(), (Entry::InFlight, now + self.window));
+
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn complete(&self, key: &str, result: IdempotentResult) {
+        self.entries.lock().unwrap().insert(
+            key.to_string(),
+            (
+                Entry::Done(result),
+                chrono::Utc::now().timestamp() + self.window,
+            ),
+        );
+    }
+
+    // Failed requests release their key so that a retry applies the write again
+    pub fn abort(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{collections::HashMap, net::IpAddr, sync::RwLock};
+
+use config::Config;
+use ipnet::IpNet;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::model::auth::ApiType;
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IpRules {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    #[serde(skip)]
+    allow_nets: Vec<IpNet>,
+    #[serde(skip)]
+    deny_nets: Vec<IpNet>,
+}
+
+impl IpRules {
+    pub fn new(allow: &[String], deny: &[String]) -> anyhow::Result<Self> {
+        Ok(Self {
+            allow: allow.to_vec(),
+            deny: deny.to_vec(),
+            allow_nets: parse_nets(allow)?,
+            deny_nets: parse_nets(deny)?,
+        })
+    }
+
+    // Deny wins over allow, an empty allow list allows everything not denied
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        if self.deny_nets.iter().any(|net| net.contains(ip)) {
+            return false;
+        }
+
+        self.allow_nets.is_empty() || self.allow_nets.iter().any(|net| net.contains(ip))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct IpFilterManager {
+    rules: RwLock<HashMap<ApiType, IpRules>>,
+}
+
+impl IpFilterManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let manager = IpFilterManager::default();
+
+        manager.apply_config(app_config)?;
+
+        Ok(manager)
+    }
+
+    // Replaces the rules of every api type with the configured lists, nothing changes when
+    // one of them does not parse
+    pub fn apply_config(&self, app_config: &Config) -> anyhow::Result<()> {
+        let mut rules = Vec::new();
+
+        for api_type in ApiType::ALL {
+            let prefix = format!("nacos.core.ip-filter.{}", api_type.key());
+            let allow = split_list(
+                &app_config
+                    .get_string(&format!("{}.allow", prefix))
+                    .unwrap_or_default(),
+            );
+            let deny = split_list(
+                &app_config
+                    .get_string(&format!("{}.deny", prefix))
+                    .unwrap_or_default(),
+            );
+
+            rules.push((api_type, IpRules::new(&allow, &deny)?));
+        }
+
+        for (api_type, rules) in rules {
+            self.set_rules(api_type, rules);
+        }
+
+        Ok(())
+    }
+
+    pub fn rules(&self) -> HashMap<ApiType, IpRules> {
+        self.rules.read().unwrap().clone()
+    }
+
+    pub fn set_rules(&self, api_type: ApiType, rules: IpRules) {
+        self.rules.write().unwrap().insert(api_type, rules);
+    }
+
+    pub fn is_allowed(&self, api_type: ApiType, ip: &IpAddr) -> bool {
+        self.rules
+            .read()
+            .unwrap()
+            .get(&api_type)
+            .is_none_or(|rules| rules.is_allowed(ip))
+    }
+}
+
+pub fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|e| e.trim())
+        .filter(|e| !e.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_nets(values: &[String]) -> anyhow::Result<Vec<IpNet>> {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse::<IpNet>()
+                .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| anyhow::anyhow!("invalid ip or cidr: {}", value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &str, deny: &str) -> IpRules {
+        IpRules::new(&split_list(allow), &split_list(deny)).unwrap()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn cidr_and_single_addresses_match() {
+        let rules = rules("10.0.0.0/8, 192.168.1.7, fd00::/8", "");
+
+        assert!(rules.is_allowed(&ip("10.1.2.3")));
+        assert!(rules.is_allowed(&ip("192.168.1.7")));
+        assert!(rules.is_allowed(&ip("fd00::1")));
+        assert!(!rules.is_allowed(&ip("192.168.1.8")));
+        assert!(!rules.is_allowed(&ip("11.0.0.1")));
+        assert!(!rules.is_allowed(&ip("fe80::1")));
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let rules = rules("10.0.0.0/8", "10.0.1.0/24");
+
+        assert!(rules.is_allowed(&ip("10.0.0.1")));
+        assert!(!rules.is_allowed(&ip("10.0.1.1")));
+    }
+
+    #[test]
+    fn empty_allow_list_allows_everything_not_denied() {
+        let rules = rules("", "127.0.0.1");
+
+        assert!(rules.is_allowed(&ip("10.0.0.1")));
+        assert!(!rules.is_allowed(&ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        assert!(IpRules::new(&[String::from("10.0.0.0/33")], &[]).is_err());
+        assert!(IpRules::new(&[], &[String::from("localhost")]).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use std::{collections::HashSet, time::Duration};
+
+use config::Config;
+use ldap3::{dn_escape, ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use sea_orm::DatabaseConnection;
+
+use crate::service::{self, ip_filter::split_list};
+
+pub const AUTH_SYSTEM_TYPE: &str = "nacos.core.auth.system.type";
+
+const INVALID_CREDENTIALS: u32 = 49;
+const DEFAULT_TIMEOUT: i64 = 3000;
+const DEFAULT_FILTER_PREFIX: &str = "uid";
+const DEFAULT_GROUP_FILTER: &str = "(member={1})";
+const DEFAULT_GROUP_ATTRIBUTE: &str = "cn";
+
+// Authenticates console users against LDAP / Active Directory when nacos.core.auth.system.type
+// is ldap, directory groups can be mapped to local roles
+#[derive(Clone, Debug)]
+pub struct LdapAuthProvider {
+    url: String,
+    base_dc: String,
+    // cn={0},dc=example,dc=org binds users directly, without {0} it is the dn used to search them
+    user_dn: String,
+    password: String,
+    filter_prefix: String,
+    case_sensitive: bool,
+    starttls: bool,
+    timeout: Duration,
+    group_base: String,
+    group_filter: String,
+    group_attribute: String,
+    group_roles: Vec<(String, String)>,
+}
+
+impl LdapAuthProvider {
+    pub fn new(app_config: &Config) -> anyhow::Result<Option<Self>> {
+        let system_type = app_config.get_string(AUTH_SYSTEM_TYPE).unwrap_or_default();
+
+        if !system_type.eq_ignore_ascii_case("ldap") {
+            return Ok(None);
+        }
+
+        let get = |key: &str| {
+            app_config
+                .get_string(&format!("nacos.core.auth.ldap.{}", key))
+                .unwrap_or_default()
+        };
+        let base_dc = get("basedc");
+        let group_roles = split_list(&get("group.role-mapping"))
+            .iter()
+            .map(|mapping| {
+                mapping
+                    .rsplit_once('=')
+                    .map(|(group, role)| (group.trim().to_string(), role.trim().to_string()))
+                    .ok_or_else(|| anyhow::anyhow!("invalid ldap group mapping: {}", mapping))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let provider = LdapAuthProvider {
+            url: app_config.get_string("nacos.core.auth.ldap.url")?,
+            group_base: Some(get("group.base"))
+                .filter(|e| !e.is_empty())
+                .unwrap_or(base_dc.clone()),
+            base_dc,
+            user_dn: Some(get("userdn"))
+                .filter(|e| !e.is_empty())
+                .unwrap_or_else(|| get("userDn")),
+            password: get("password"),
+            filter_prefix: Some(get("filter.prefix"))
+                .filter(|e| !e.is_empty())
+                .unwrap_or(DEFAULT_FILTER_PREFIX.to_string()),
+            case_sensitive: app_config
+                .get_bool("nacos.core.auth.ldap.case.sensitive")
+                .unwrap_or(true),
+            starttls: app_config
+                .get_bool("nacos.core.auth.ldap.starttls")
+                .unwrap_or(false),
+            timeout: Duration::from_millis(
+                app_config
+                    .get_int("nacos.core.auth.ldap.timeout")
+                    .unwrap_or(DEFAULT_TIMEOUT) as u64,
+            ),
+            group_filter: Some(get("group.filter"))
+                .filter(|e| !e.is_empty())
+                .unwrap_or(DEFAULT_GROUP_FILTER.to_string()),
+            group_attribute: Some(get("group.attribute"))
+                .filter(|e| !e.is_empty())
+                .unwrap_or(DEFAULT_GROUP_ATTRIBUTE.to_string()),
+            group_roles,
+        };
+
+        Ok(Some(provider))
+    }
+
+    // The local username of the account, usernames are compared lower case unless case sensitive
+    pub fn local_username(&self, username: &str) -> String {
+        if self.case_sensitive {
+            username.to_string()
+        } else {
+            username.to_lowercase()
+        }
+    }
+
+    // Some with the directory groups of the user when the directory accepts the password
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        // an empty password is an anonymous bind which most directories accept
+        if username.is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ldap = self.connect().await?;
+        let search_bind = !self.user_dn.contains("{0}");
+
+        if search_bind && !self.user_dn.is_empty() {
+            ldap.simple_bind(&self.user_dn, &self.password)
+                .await?
+                .success()?;
+        }
+
+        let user_dn = if search_bind {
+            match self.find_user_dn(&mut ldap, username).await? {
+                Some(user_dn) => user_dn,
+                None => return Ok(None),
+            }
+        } else {
+            self.user_dn.replace("{0}", &dn_escape(username))
+        };
+
+        let result = ldap.simple_bind(&user_dn, password).await?;
+
+        if result.rc == INVALID_CREDENTIALS {
+            return Ok(None);
+        }
+
+        result.success()?;
+
+        let groups = if self.group_roles.is_empty() {
+            Vec::new()
+        } else {
+            if search_bind && !self.user_dn.is_empty() {
+                ldap.simple_bind(&self.user_dn, &self.password)
+                    .await?
+                    .success()?;
+            }
+
+            self.find_groups(&mut ldap, username, &user_dn).await?
+        };
+
+        let _ = ldap.unbind().await;
+
+        Ok(Some(groups))
+    }
+
+    // Grant the mapped roles of the groups and revoke mapped roles the user is no longer in the
+    // groups of, roles without mapping are managed locally and left alone
+    pub async fn sync_roles(
+        &self,
+        db: &DatabaseConnection,
+        username: &str,
+        groups: &[String],
+    ) -> anyhow::Result<()> {
+        if self.group_roles.is_empty() {
+            return Ok(());
+        }
+
+        let current: HashSet<String> = service::role::find_by_username(db, username)
+            .await?
+            .into_iter()
+            .map(|role| role.role)
+            .collect();
+        let granted: HashSet<&str> = self
+            .group_roles
+            .iter()
+            .filter(|(group, _)| groups.iter().any(|e| e.eq_ignore_ascii_case(group)))
+            .map(|(_, role)| role.as_str())
+            .collect();
+
+        for role in self.group_roles.iter().map(|(_, role)| role.as_str()) {
+            if granted.contains(role) && !current.contains(role) {
+                service::role::create(db, role, username).await?;
+            } else if !granted.contains(role) && current.contains(role) {
+                service::role::delete(db, role, username).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn connect(&self) -> anyhow::Result<Ldap> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(self.timeout)
+            .set_starttls(self.starttls);
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.url).await?;
+
+        ldap3::drive!(conn);
+
+        ldap.with_timeout(self.timeout);
+
+        Ok(ldap)
+    }
+
+    async fn find_user_dn(
+        &self,
+        ldap: &mut Ldap,
+        username: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let filter = format!("({}={})", self.filter_prefix, ldap_escape(username));
+        let (entries, _) = ldap
+            .search(&self.base_dc, Scope::Subtree, &filter, vec!["dn"])
+            .await?
+            .success()?;
+
+        Ok(entries
+            .into_iter()
+            .next()
+            .map(|entry| SearchEntry::construct(entry).dn))
+    }
+
+    async fn find_groups(
+        &self,
+        ldap: &mut Ldap,
+        username: &str,
+        user_dn: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let filter = self
+            .group_filter
+            .replace("{0}", &ldap_escape(username))
+            .replace("{1}", &ldap_escape(user_dn));
+        let (entries, _) = ldap
+            .search(
+                &self.group_base,
+                Scope::Subtree,
+                &filter,
+                vec![self.group_attribute.as_str()],
+            )
+            .await?
+            .success()?;
+
+        Ok(entries
+            .into_iter()
+            .flat_map(|entry| {
+                SearchEntry::construct(entry)
+                    .attrs
+                    .remove(&self.group_attribute)
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
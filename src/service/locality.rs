@@ -0,0 +1,104 @@
+use std::{fmt, net::IpAddr, sync::RwLock};
+
+use config::Config;
+use ipnet::IpNet;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::service::ip_filter::split_list;
+
+pub const LOCALITY_RULES: &str = "nacos.core.locality.rules";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Locality {
+    pub region: String,
+    pub zone: String,
+}
+
+impl fmt::Display for Locality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.region, self.zone)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalityRule {
+    pub cidr: String,
+    pub locality: Locality,
+    #[serde(skip)]
+    net: Option<IpNet>,
+}
+
+impl LocalityRule {
+    // Parse a cidr=region/zone rule, the zone is optional
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let (cidr, labels) = value
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("invalid locality rule: {}", value))?;
+        let cidr = cidr.trim();
+        let net = cidr
+            .parse::<IpNet>()
+            .or_else(|_| cidr.parse::<IpAddr>().map(IpNet::from))
+            .map_err(|_| anyhow::anyhow!("invalid ip or cidr: {}", cidr))?;
+        let (region, zone) = labels.split_once('/').unwrap_or((labels, ""));
+
+        if region.trim().is_empty() {
+            return Err(anyhow::anyhow!("locality rule without region: {}", value));
+        }
+
+        Ok(Self {
+            cidr: cidr.to_string(),
+            locality: Locality {
+                region: region.trim().to_string(),
+                zone: zone.trim().to_string(),
+            },
+            net: Some(net),
+        })
+    }
+}
+
+// Static CIDR to region / zone labels of clients, the most specific network wins
+#[derive(Debug, Default)]
+pub struct LocalityManager {
+    rules: RwLock<Vec<LocalityRule>>,
+}
+
+impl LocalityManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let manager = LocalityManager::default();
+
+        manager.set_rules(parse_rules(
+            &app_config.get_string(LOCALITY_RULES).unwrap_or_default(),
+        )?);
+
+        Ok(manager)
+    }
+
+    pub fn rules(&self) -> Vec<LocalityRule> {
+        self.rules.read().unwrap().clone()
+    }
+
+    pub fn set_rules(&self, mut rules: Vec<LocalityRule>) {
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.net.map_or(0, |net| net.prefix_len())));
+
+        *self.rules.write().unwrap() = rules;
+    }
+
+    pub fn resolve(&self, ip: &IpAddr) -> Option<Locality> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .find(|rule| rule.net.is_some_and(|net| net.contains(ip)))
+            .map(|rule| rule.locality.clone())
+    }
+}
+
+pub fn parse_rules(value: &str) -> anyhow::Result<Vec<LocalityRule>> {
+    split_list(value)
+        .iter()
+        .map(|rule| LocalityRule::parse(rule))
+        .collect()
+}
@@ -0,0 +1,128 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use config::Config;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{model::cluster::NodeState, service::cluster::ServerMemberManager};
+
+pub const MAINTENANCE_PATH: &str = "/v1/core/maintenance";
+pub const DRAIN_TIMEOUT: &str = "nacos.core.maintenance.drain-timeout";
+
+const DEFAULT_DRAIN_TIMEOUT: u64 = 30;
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceStatus {
+    pub maintenance: bool,
+    pub state: NodeState,
+    pub since: Option<i64>,
+    pub in_flight: usize,
+    pub safe_to_restart: bool,
+}
+
+#[derive(Debug)]
+pub struct MaintenanceManager {
+    drain_timeout: Duration,
+    in_flight: AtomicUsize,
+    // start of the maintenance and the state of the node before it
+    entered: RwLock<Option<(i64, NodeState)>>,
+}
+
+// Counts a request as in flight until it is dropped
+pub struct InFlightGuard(Arc<MaintenanceManager>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl MaintenanceManager {
+    pub fn new(app_config: &Config) -> Self {
+        MaintenanceManager {
+            drain_timeout: Duration::from_secs(
+                app_config
+                    .get_int(DRAIN_TIMEOUT)
+                    .unwrap_or(DEFAULT_DRAIN_TIMEOUT as i64) as u64,
+            ),
+            in_flight: AtomicUsize::new(0),
+            entered: RwLock::new(None),
+        }
+    }
+
+    pub fn track(self: &Arc<Self>) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+
+        InFlightGuard(self.clone())
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    // The node reports DOWN so readiness fails and writes are refused, then waits for the
+    // requests already running to finish
+    pub async fn enter(
+        &self,
+        member_manager: &ServerMemberManager,
+        timeout: Duration,
+    ) -> MaintenanceStatus {
+        self.entered.write().unwrap().get_or_insert_with(|| {
+            (
+                chrono::Utc::now().timestamp_millis(),
+                member_manager.get_self().state,
+            )
+        });
+
+        member_manager.update_self_state(NodeState::Down);
+
+        let start = Instant::now();
+
+        while self.others_in_flight() > 0 && start.elapsed() < timeout {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        self.status(member_manager)
+    }
+
+    // Restores the state from before the maintenance, so a node that isolated itself stays
+    // isolated until the health check sees the database again
+    pub fn leave(&self, member_manager: &ServerMemberManager) -> MaintenanceStatus {
+        if let Some((_, state)) = self.entered.write().unwrap().take() {
+            member_manager.update_self_state(state);
+        }
+
+        self.status(member_manager)
+    }
+
+    pub fn status(&self, member_manager: &ServerMemberManager) -> MaintenanceStatus {
+        let since = self
+            .entered
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(since, _)| *since);
+        let in_flight = self.others_in_flight();
+
+        MaintenanceStatus {
+            maintenance: since.is_some(),
+            state: member_manager.get_self().state,
+            since,
+            in_flight,
+            safe_to_restart: since.is_some() && in_flight == 0,
+        }
+    }
+
+    // Requests in flight besides the maintenance request asking
+    fn others_in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst).saturating_sub(1)
+    }
+}
@@ -0,0 +1,207 @@
+use config::Config;
+use regex::{Regex, RegexBuilder};
+use sea_orm::*;
+
+use crate::{entity::permissions, service::ip_filter::split_list};
+
+pub const MASK_PATTERNS: &str = "nacos.core.config.mask.patterns";
+pub const UNMASK_ACTION: &str = "unmask";
+
+const DEFAULT_MASK_PATTERNS: &str = "*password*,*secret*";
+const MASK: &str = "******";
+
+// Masks the values of sensitive keys in config content shown on the console, matching
+// key=value and key: value lines of properties, yaml and pretty printed json. The lines of a
+// yaml block scalar, like key: | followed by indented lines, are masked too
+#[derive(Debug)]
+pub struct MaskManager {
+    patterns: Vec<Regex>,
+    line: Regex,
+}
+
+impl MaskManager {
+    pub fn new(app_config: &Config) -> anyhow::Result<Self> {
+        let patterns = split_list(
+            &app_config
+                .get_string(MASK_PATTERNS)
+                .unwrap_or(DEFAULT_MASK_PATTERNS.to_string()),
+        )
+        .iter()
+        .map(|pattern| {
+            RegexBuilder::new(&format!(
+                "^{}$",
+                regex::escape(pattern)
+                    .replace(r"\*", ".*")
+                    .replace(r"\?", ".")
+            ))
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| anyhow::anyhow!("invalid mask pattern {}: {}", pattern, e))
+        })
+        .collect::<anyhow::Result<Vec<Regex>>>()?;
+
+        Ok(Self {
+            patterns,
+            line: Regex::new(r#"^(\s*-?\s*["']?)([\w.\-]+)(["']?\s*[:=]\s*)(.*?)(,?\s*)$"#)?,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    pub fn mask(&self, content: &str) -> String {
+        if !self.is_enabled() {
+            return content.to_string();
+        }
+
+        // indentation of the masked key whose block scalar is being read
+        let mut block_indent: Option<usize> = None;
+
+        content
+            .split('\n')
+            .map(|line| {
+                let indent = line.len() - line.trim_start().len();
+
+                if let Some(key_indent) = block_indent {
+                    if line.trim().is_empty() {
+                        return line.to_string();
+                    }
+                    if indent > key_indent {
+                        return format!("{}{}", &line[..indent], MASK);
+                    }
+
+                    block_indent = None;
+                }
+
+                match self.line.captures(line) {
+                    Some(caps)
+                        if !caps[4].is_empty()
+                            && !caps[4].starts_with('{')
+                            && !caps[4].starts_with('[')
+                            && self.patterns.iter().any(|e| e.is_match(&caps[2])) =>
+                    {
+                        // the indicator, like | or >-, is kept and the lines below are masked
+                        if caps[4].starts_with('|') || caps[4].starts_with('>') {
+                            block_indent = Some(indent);
+
+                            return line.to_string();
+                        }
+
+                        let value = if caps[4].starts_with('"') {
+                            format!("\"{}\"", MASK)
+                        } else {
+                            MASK.to_string()
+                        };
+
+                        format!("{}{}{}{}{}", &caps[1], &caps[2], &caps[3], value, &caps[5])
+                    }
+                    _ => line.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+// Whether one of the roles is granted the unmask action on the namespace, resources are
+// formatted as namespace:group:resource
+pub async fn can_unmask(
+    db: &DatabaseConnection,
+    roles: &[String],
+    namespace: &str,
+) -> anyhow::Result<bool> {
+    if roles.is_empty() {
+        return Ok(false);
+    }
+
+    let namespace = if namespace.is_empty() {
+        "public"
+    } else {
+        namespace
+    };
+
+    let granted = permissions::Entity::find()
+        .filter(permissions::Column::Role.is_in(roles.to_vec()))
+        .filter(permissions::Column::Action.eq(UNMASK_ACTION))
+        .all(db)
+        .await?
+        .iter()
+        .any(|permission| {
+            let resource_namespace = permission.resource.split(':').next().unwrap_or_default();
+
+            resource_namespace == "*" || resource_namespace == namespace
+        });
+
+    Ok(granted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(patterns: &str) -> MaskManager {
+        let app_config = Config::builder()
+            .set_override(MASK_PATTERNS, patterns)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        MaskManager::new(&app_config).unwrap()
+    }
+
+    #[test]
+    fn properties_and_yaml_values_are_masked() {
+        let manager = manager(DEFAULT_MASK_PATTERNS);
+
+        assert_eq!(
+            manager.mask("db.password=123456\ndb.user=nacos"),
+            "db.password=******\ndb.user=nacos"
+        );
+        assert_eq!(
+            manager.mask("db:\n  Secret-Key: abc\n  - password: 'x'"),
+            "db:\n  Secret-Key: ******\n  - password: ******"
+        );
+    }
+
+    #[test]
+    fn json_values_keep_their_quotes() {
+        let manager = manager(DEFAULT_MASK_PATTERNS);
+
+        assert_eq!(
+            manager.mask("{\n  \"password\": \"123\",\n  \"name\": \"a\"\n}"),
+            "{\n  \"password\": \"******\",\n  \"name\": \"a\"\n}"
+        );
+    }
+
+    #[test]
+    fn yaml_block_scalars_are_masked() {
+        let manager = manager(DEFAULT_MASK_PATTERNS);
+        let content = "tls:\n  secret: |\n    line1\n\n    line2\n  name: a\nother: b";
+
+        assert_eq!(
+            manager.mask(content),
+            "tls:\n  secret: |\n    ******\n\n    ******\n  name: a\nother: b"
+        );
+        assert_eq!(
+            manager.mask("password: >-\n  folded\nuser: x"),
+            "password: >-\n  ******\nuser: x"
+        );
+    }
+
+    #[test]
+    fn nested_values_and_other_keys_are_kept() {
+        let manager = manager(DEFAULT_MASK_PATTERNS);
+        let content = "password:\n  value: a\nsecrets: [a, b]\nhost: localhost";
+
+        assert_eq!(manager.mask(content), content);
+    }
+
+    #[test]
+    fn empty_patterns_disable_masking() {
+        let manager = manager("");
+
+        assert!(!manager.is_enabled());
+        assert_eq!(manager.mask("password=1"), "password=1");
+    }
+}
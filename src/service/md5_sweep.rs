@@ -0,0 +1,215 @@
+use std::{
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use config::Config;
+use sea_orm::{sea_query::Expr, *};
+
+use crate::{
+    entity::config_info,
+    service::{
+        config::md5_digest,
+        crypto::ContentCipher,
+        event::{EventBus, ServerEvent},
+    },
+};
+
+pub const MD5_SWEEP_INTERVAL: &str = "nacos.core.config.md5-sweep.interval";
+pub const MD5_SWEEP_REPAIR: &str = "nacos.core.config.md5-sweep.repair";
+
+const DEFAULT_INTERVAL: u64 = 3_600_000;
+const BATCH_SIZE: u64 = 500;
+
+#[derive(Clone, Debug, Default)]
+pub struct Md5SweepStats {
+    pub runs: u64,
+    pub mismatches: u64,
+    pub repaired: u64,
+    pub undecryptable: u64,
+    pub last_checked: u64,
+    pub last_mismatches: u64,
+    pub last_run_time: i64,
+}
+
+// Recomputes the md5 of every stored config content. Clients compare md5 to detect changes,
+// so a stale md5 column hides a content from them until the next publish. Mismatches are
+// repaired unless turned off, and counted for the metrics either way
+#[derive(Debug)]
+pub struct Md5Sweeper {
+    db: DatabaseConnection,
+    cipher: Arc<ContentCipher>,
+    event_bus: Arc<EventBus>,
+    interval: u64,
+    repair: bool,
+    runs: AtomicU64,
+    mismatches: AtomicU64,
+    repaired: AtomicU64,
+    undecryptable: AtomicU64,
+    last_checked: AtomicU64,
+    last_mismatches: AtomicU64,
+    last_run_time: AtomicI64,
+}
+
+impl Md5Sweeper {
+    pub fn new(
+        app_config: &Config,
+        db: DatabaseConnection,
+        cipher: Arc<ContentCipher>,
+        event_bus: Arc<EventBus>,
+    ) -> Self {
+        Md5Sweeper {
+            db,
+            cipher,
+            event_bus,
+            interval: app_config
+                .get_int(MD5_SWEEP_INTERVAL)
+                .map_or(DEFAULT_INTERVAL, |e| e.max(0) as u64),
+            repair: app_config.get_bool(MD5_SWEEP_REPAIR).unwrap_or(true),
+            runs: AtomicU64::new(0),
+            mismatches: AtomicU64::new(0),
+            repaired: AtomicU64::new(0),
+            undecryptable: AtomicU64::new(0),
+            last_checked: AtomicU64::new(0),
+            last_mismatches: AtomicU64::new(0),
+            last_run_time: AtomicI64::new(0),
+        }
+    }
+
+    // An interval of 0 turns the sweep off
+    pub fn start(self: Arc<Self>) {
+        if self.interval == 0 {
+            return;
+        }
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(self.interval)).await;
+
+                if let Err(err) = self.sweep().await {
+                    tracing::warn!("config md5 sweep failed: {}", err);
+                }
+            }
+        });
+    }
+
+    pub fn stats(&self) -> Md5SweepStats {
+        Md5SweepStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            mismatches: self.mismatches.load(Ordering::Relaxed),
+            repaired: self.repaired.load(Ordering::Relaxed),
+            undecryptable: self.undecryptable.load(Ordering::Relaxed),
+            last_checked: self.last_checked.load(Ordering::Relaxed),
+            last_mismatches: self.last_mismatches.load(Ordering::Relaxed),
+            last_run_time: self.last_run_time.load(Ordering::Relaxed),
+        }
+    }
+
+    // Walks config_info in id order a batch at a time, so a large table is not loaded at once
+    pub async fn sweep(&self) -> anyhow::Result<()> {
+        let mut last_id = 0i64;
+        let mut checked = 0u64;
+        let mut mismatches = 0u64;
+
+        loop {
+            let batch = config_info::Entity::find()
+                .filter(config_info::Column::Id.gt(last_id))
+                .order_by_asc(config_info::Column::Id)
+                .limit(BATCH_SIZE)
+                .all(&self.db)
+                .await?;
+
+            let Some(last) = batch.last() else {
+                break;
+            };
+
+            last_id = last.id;
+
+            for entity in batch {
+                checked += 1;
+
+                let stored = entity.content.clone().unwrap_or_default();
+                // a content that cannot be decrypted says nothing about its md5
+                let content = match self.cipher.decrypt(&stored) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        self.undecryptable.fetch_add(1, Ordering::Relaxed);
+
+                        tracing::warn!("config {} skipped by md5 sweep: {}", entity.id, err);
+
+                        continue;
+                    }
+                };
+                let md5 = md5_digest(&content);
+
+                if entity.md5.as_deref() == Some(md5.as_str()) {
+                    continue;
+                }
+
+                mismatches += 1;
+                self.mismatches.fetch_add(1, Ordering::Relaxed);
+
+                tracing::error!(
+                    "config {} {} {} has md5 {} but its content hashes to {}",
+                    entity.tenant_id.clone().unwrap_or_default(),
+                    entity.group_id.clone().unwrap_or_default(),
+                    entity.data_id,
+                    entity.md5.clone().unwrap_or_default(),
+                    md5
+                );
+
+                if self.repair {
+                    self.repair(entity, stored, md5).await?;
+                }
+            }
+        }
+
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.last_checked.store(checked, Ordering::Relaxed);
+        self.last_mismatches.store(mismatches, Ordering::Relaxed);
+        self.last_run_time
+            .store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+
+        tracing::info!(
+            "config md5 sweep checked {} configs, {} mismatched",
+            checked,
+            mismatches
+        );
+
+        Ok(())
+    }
+
+    // Only while the content is still the one hashed, a publish meanwhile wrote a correct md5
+    async fn repair(
+        &self,
+        entity: config_info::Model,
+        stored: String,
+        md5: String,
+    ) -> anyhow::Result<()> {
+        let updated = config_info::Entity::update_many()
+            .col_expr(config_info::Column::Md5, Expr::value(md5.clone()))
+            .filter(config_info::Column::Id.eq(entity.id))
+            .filter(config_info::Column::Content.eq(stored))
+            .exec(&self.db)
+            .await?;
+
+        if updated.rows_affected == 0 {
+            return Ok(());
+        }
+
+        self.repaired.fetch_add(1, Ordering::Relaxed);
+
+        // watchers still hold the stale md5 and have to fetch the content again
+        self.event_bus.publish(ServerEvent::ConfigChanged {
+            data_id: entity.data_id,
+            group: entity.group_id.unwrap_or_default(),
+            tenant: entity.tenant_id.unwrap_or_default(),
+            md5,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use config::Config;
+use hickory_resolver::TokioAsyncResolver;
+use serde::{Deserialize, Serialize};
+
+use crate::model::cluster::{format_address, Member, DEFAULT_SERVER_PORT};
+
+pub const LOOKUP_TYPE: &str = "nacos.core.member.lookup.type";
+pub const MEMBER_LIST: &str = "nacos.member.list";
+pub const CLUSTER_CONF_PATH: &str = "nacos.core.member.lookup.file.path";
+pub const CLUSTER_CONF_WATCH_INTERVAL: &str = "nacos.core.member.lookup.file.watch-interval";
+pub const DNS_NAME: &str = "nacos.core.member.lookup.dns.name";
+pub const DNS_RECORD_TYPE: &str = "nacos.core.member.lookup.dns.record-type";
+pub const DNS_REFRESH_INTERVAL: &str = "nacos.core.member.lookup.dns.refresh-interval";
+pub const KUBERNETES_SERVICE: &str = "nacos.core.member.lookup.kubernetes.service";
+pub const KUBERNETES_NAMESPACE: &str = "nacos.core.member.lookup.kubernetes.namespace";
+pub const KUBERNETES_REFRESH_INTERVAL: &str =
+    "nacos.core.member.lookup.kubernetes.refresh-interval";
+
+const DEFAULT_CLUSTER_CONF_PATH: &str = "conf/cluster.conf";
+const DEFAULT_CLUSTER_CONF_WATCH_INTERVAL: u64 = 2000;
+const DEFAULT_DNS_REFRESH_INTERVAL: u64 = 5000;
+const DEFAULT_KUBERNETES_REFRESH_INTERVAL: u64 = 5000;
+const KUBERNETES_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+const KUBERNETES_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Cloud provider address servers (the address-server lookup of Nacos) are not supported yet, the
+// same deployments can use a DNS name or the Kubernetes endpoints instead
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LookupType {
+    Standalone,
+    File,
+    Dns,
+    Kubernetes,
+}
+
+impl LookupType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "standalone" => Some(LookupType::Standalone),
+            "file" => Some(LookupType::File),
+            "dns" => Some(LookupType::Dns),
+            "kubernetes" => Some(LookupType::Kubernetes),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LookupType::Standalone => "standalone",
+            LookupType::File => "file",
+            LookupType::Dns => "dns",
+            LookupType::Kubernetes => "kubernetes",
+        }
+    }
+}
+
+#[async_trait]
+pub trait MemberLookup: Debug + Send + Sync {
+    fn lookup_type(&self) -> LookupType;
+
+    // Lookups without a refresh interval are only consulted on start and on switch
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    // Checked before each periodic refresh so unchanged sources are not reapplied
+    async fn is_modified(&self) -> bool {
+        true
+    }
+
+    async fn lookup(&self) -> anyhow::Result<Vec<Member>>;
+}
+
+#[derive(Debug)]
+pub struct StandaloneMemberLookup {
+    local_address: String,
+}
+
+#[async_trait]
+impl MemberLookup for StandaloneMemberLookup {
+    fn lookup_type(&self) -> LookupType {
+        LookupType::Standalone
+    }
+
+    async fn lookup(&self) -> anyhow::Result<Vec<Member>> {
+        Ok(vec![Member::from_address(&self.local_address)])
+    }
+}
+
+#[derive(Debug)]
+pub struct FileConfigMemberLookup {
+    member_list: Option<String>,
+    path: PathBuf,
+    watch_interval: Duration,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl FileConfigMemberLookup {
+    async fn modified_time(&self) -> Option<SystemTime> {
+        tokio::fs::metadata(&self.path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[async_trait]
+impl MemberLookup for FileConfigMemberLookup {
+    fn lookup_type(&self) -> LookupType {
+        LookupType::File
+    }
+
+    // nacos.member.list takes priority and is applied again by a config reload, only
+    // cluster.conf is watched
+    fn refresh_interval(&self) -> Option<Duration> {
+        match self.member_list {
+            Some(_) => None,
+            None => Some(self.watch_interval),
+        }
+    }
+
+    async fn is_modified(&self) -> bool {
+        let modified_time = self.modified_time().await;
+        let modified = modified_time != *self.last_modified.lock().unwrap();
+
+        if modified {
+            tracing::info!("{} changed, reloading cluster members", self.path.display());
+        }
+
+        modified
+    }
+
+    async fn lookup(&self) -> anyhow::Result<Vec<Member>> {
+        let addresses: Vec<String> = match &self.member_list {
+            Some(member_list) => member_list.split(',').map(String::from).collect(),
+            None => {
+                let modified_time = self.modified_time().await;
+                let content = tokio::fs::read_to_string(&self.path)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("read {} failed: {}", self.path.display(), e));
+
+                *self.last_modified.lock().unwrap() = modified_time;
+
+                content?
+                    .lines()
+                    .map(|line| line.split('#').next().unwrap_or_default().to_string())
+                    .collect()
+            }
+        };
+
+        let members: Vec<Member> = addresses
+            .iter()
+            .map(|address| address.trim())
+            .filter(|address| !address.is_empty())
+            .map(Member::from_address)
+            .collect();
+
+        if members.is_empty() {
+            return Err(anyhow::anyhow!("no member found in cluster config"));
+        }
+
+        Ok(members)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DnsRecordType {
+    Srv,
+    A,
+}
+
+#[derive(Debug)]
+pub struct DnsMemberLookup {
+    name: String,
+    record_type: DnsRecordType,
+    port: i32,
+    refresh_interval: Duration,
+    resolver: TokioAsyncResolver,
+}
+
+#[async_trait]
+impl MemberLookup for DnsMemberLookup {
+    fn lookup_type(&self) -> LookupType {
+        LookupType::Dns
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(self.refresh_interval)
+    }
+
+    async fn lookup(&self) -> anyhow::Result<Vec<Member>> {
+        let mut addresses: Vec<String> = match self.record_type {
+            DnsRecordType::Srv => self
+                .resolver
+                .srv_lookup(self.name.as_str())
+                .await?
+                .iter()
+                .map(|srv| {
+                    format!(
+                        "{}:{}",
+                        srv.target().to_utf8().trim_end_matches('.'),
+                        srv.port()
+                    )
+                })
+                .collect(),
+            DnsRecordType::A => self
+                .resolver
+                .lookup_ip(self.name.as_str())
+                .await?
+                .iter()
+                .map(|ip| format_address(&ip.to_string(), self.port))
+                .collect(),
+        };
+
+        addresses.sort();
+        addresses.dedup();
+
+        if addresses.is_empty() {
+            return Err(anyhow::anyhow!("no record found for {}", self.name));
+        }
+
+        Ok(addresses.iter().map(|e| Member::from_address(e)).collect())
+    }
+}
+
+// Reads the endpoints of a service from the API server with the service account of the pod
+#[derive(Debug)]
+pub struct KubernetesMemberLookup {
+    url: String,
+    token_path: PathBuf,
+    port: i32,
+    refresh_interval: Duration,
+    agent: ureq::Agent,
+}
+
+#[async_trait]
+impl MemberLookup for KubernetesMemberLookup {
+    fn lookup_type(&self) -> LookupType {
+        LookupType::Kubernetes
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(self.refresh_interval)
+    }
+
+    async fn lookup(&self) -> anyhow::Result<Vec<Member>> {
+        // the token is read on each lookup, kubelet rotates it
+        let token = tokio::fs::read_to_string(&self.token_path)
+            .await
+            .map_err(|e| anyhow::anyhow!("read {} failed: {}", self.token_path.display(), e))?;
+        let request = self
+            .agent
+            .get(&self.url)
+            .set("Authorization", &format!("Bearer {}", token.trim()));
+        let endpoints: serde_json::Value =
+            tokio::task::spawn_blocking(move || -> anyhow::Result<serde_json::Value> {
+                Ok(request.call()?.into_json()?)
+            })
+            .await??;
+
+        // pods not ready yet are members too, they have to find each other to become ready
+        let mut addresses: Vec<String> = endpoints["subsets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|subset| {
+                ["addresses", "notReadyAddresses"]
+                    .into_iter()
+                    .filter_map(|key| subset[key].as_array())
+                    .flatten()
+            })
+            .filter_map(|address| address["ip"].as_str())
+            .map(|ip| format_address(ip, self.port))
+            .collect();
+
+        addresses.sort();
+        addresses.dedup();
+
+        if addresses.is_empty() {
+            return Err(anyhow::anyhow!("no address found in {}", self.url));
+        }
+
+        Ok(addresses.iter().map(|e| Member::from_address(e)).collect())
+    }
+}
+
+fn create_kubernetes_lookup(app_config: &Config) -> anyhow::Result<KubernetesMemberLookup> {
+    let service = app_config.get_string(KUBERNETES_SERVICE).map_err(|_| {
+        anyhow::anyhow!(
+            "{} is required for kubernetes member lookup",
+            KUBERNETES_SERVICE
+        )
+    })?;
+    let account_dir = PathBuf::from(KUBERNETES_ACCOUNT_DIR);
+    // the namespace of the pod when not configured
+    let namespace = match app_config.get_string(KUBERNETES_NAMESPACE) {
+        Ok(namespace) => namespace,
+        Err(_) => std::fs::read_to_string(account_dir.join("namespace"))
+            .map(|e| e.trim().to_string())
+            .map_err(|e| anyhow::anyhow!("read the namespace of the pod failed: {}", e))?,
+    };
+    let host = std::env::var("KUBERNETES_SERVICE_HOST")
+        .map_err(|_| anyhow::anyhow!("kubernetes member lookup only runs inside a pod"))?;
+    let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or(String::from("443"));
+    let ca = std::fs::read(account_dir.join("ca.crt"))
+        .map_err(|e| anyhow::anyhow!("read the cluster ca failed: {}", e))?;
+    let connector = native_tls::TlsConnector::builder()
+        .add_root_certificate(native_tls::Certificate::from_pem(&ca)?)
+        .build()?;
+
+    Ok(KubernetesMemberLookup {
+        url: format!(
+            "https://{}/api/v1/namespaces/{}/endpoints/{}",
+            format_address(&host, port.parse().unwrap_or(443)),
+            namespace,
+            service
+        ),
+        token_path: account_dir.join("token"),
+        port: app_config
+            .get_int("server.port")
+            .unwrap_or(DEFAULT_SERVER_PORT as i64) as i32,
+        refresh_interval: Duration::from_millis(
+            app_config
+                .get_int(KUBERNETES_REFRESH_INTERVAL)
+                .unwrap_or(DEFAULT_KUBERNETES_REFRESH_INTERVAL as i64) as u64,
+        ),
+        agent: ureq::AgentBuilder::new()
+            .tls_connector(Arc::new(connector))
+            .timeout(KUBERNETES_TIMEOUT)
+            .build(),
+    })
+}
+
+pub fn default_lookup_type(app_config: &Config) -> anyhow::Result<LookupType> {
+    if app_config.get_bool("nacos.standalone").unwrap_or(true) {
+        return Ok(LookupType::Standalone);
+    }
+
+    match app_config.get_string(LOOKUP_TYPE) {
+        Ok(name) => LookupType::from_name(&name)
+            .ok_or_else(|| anyhow::anyhow!("unknown member lookup type: {}", name)),
+        Err(_) => Ok(LookupType::File),
+    }
+}
+
+pub fn create_lookup(
+    lookup_type: LookupType,
+    app_config: &Config,
+    local_address: &str,
+) -> anyhow::Result<Arc<dyn MemberLookup>> {
+    let lookup: Arc<dyn MemberLookup> = match lookup_type {
+        LookupType::Standalone => Arc::new(StandaloneMemberLookup {
+            local_address: local_address.to_string(),
+        }),
+        LookupType::File => Arc::new(FileConfigMemberLookup {
+            member_list: app_config
+                .get_string(MEMBER_LIST)
+                .ok()
+                .filter(|e| !e.trim().is_empty()),
+            path: PathBuf::from(
+                app_config
+                    .get_string(CLUSTER_CONF_PATH)
+                    .unwrap_or(DEFAULT_CLUSTER_CONF_PATH.to_string()),
+            ),
+            watch_interval: Duration::from_millis(
+                app_config
+                    .get_int(CLUSTER_CONF_WATCH_INTERVAL)
+                    .unwrap_or(DEFAULT_CLUSTER_CONF_WATCH_INTERVAL as i64) as u64,
+            ),
+            last_modified: Mutex::new(None),
+        }),
+        LookupType::Dns => {
+            let name = app_config
+                .get_string(DNS_NAME)
+                .map_err(|_| anyhow::anyhow!("{} is required for dns member lookup", DNS_NAME))?;
+            let record_type = match app_config
+                .get_string(DNS_RECORD_TYPE)
+                .unwrap_or("srv".to_string())
+                .to_lowercase()
+                .as_str()
+            {
+                "srv" => DnsRecordType::Srv,
+                "a" => DnsRecordType::A,
+                other => return Err(anyhow::anyhow!("unknown dns record type: {}", other)),
+            };
+
+            Arc::new(DnsMemberLookup {
+                name,
+                record_type,
+                port: app_config
+                    .get_int("server.port")
+                    .unwrap_or(DEFAULT_SERVER_PORT as i64) as i32,
+                refresh_interval: Duration::from_millis(
+                    app_config
+                        .get_int(DNS_REFRESH_INTERVAL)
+                        .unwrap_or(DEFAULT_DNS_REFRESH_INTERVAL as i64) as u64,
+                ),
+                resolver: TokioAsyncResolver::tokio_from_system_conf()?,
+            })
+        }
+        LookupType::Kubernetes => Arc::new(create_kubernetes_lookup(app_config)?),
+    };
+
+    Ok(lookup)
+}
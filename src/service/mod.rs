@@ -1,7 +1,37 @@
+pub mod access_log;
+pub mod activity;
+pub mod app_config;
+pub mod approval;
+pub mod audit;
 pub mod auth;
+pub mod capacity;
+pub mod cluster;
+pub mod cluster_event;
 pub mod config;
+pub mod config_schema;
+pub mod cors;
+pub mod crypto;
+pub mod db_credential;
+pub mod db_pool;
+pub mod event;
+pub mod health;
 pub mod history;
+pub mod idempotency;
+pub mod ip_filter;
+pub mod ldap;
+pub mod locality;
+pub mod maintenance;
+pub mod mask;
+pub mod md5_sweep;
+pub mod member_lookup;
 pub mod namespace;
 pub mod permission;
+pub mod preference;
+pub mod reload;
 pub mod role;
+pub mod sync;
+pub mod tls;
 pub mod user;
+pub mod watch;
+pub mod write_lock;
+pub mod write_quota;
@@ -1,10 +1,22 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
+use config::Config;
+use crypto::{hmac::Hmac, mac::Mac, sha2::Sha256, util::fixed_time_eq};
 use sea_orm::*;
 
 use crate::{
-    entity::{config_info, tenant_info},
-    model::naming::Namespace,
+    entity::{
+        config_info, config_info_beta, config_info_tag, config_tags_relation, permissions,
+        tenant_capacity, tenant_info,
+    },
+    model::{
+        common::BusinessError,
+        naming::{Namespace, NamespaceDeleteReport},
+    },
+    service::{
+        auth::AuthManager,
+        event::{EventBus, ServerEvent},
+    },
 };
 
 #[derive(Debug, FromQueryResult)]
@@ -17,14 +29,17 @@ const DEFAULT_NAMESPACE: &'static str = "public";
 const DEFAULT_CREATE_SOURCE: &'static str = "nacos";
 const DEFAULT_KP: &'static str = "1";
 
+pub const DELETE_CONFIRM_TIMEOUT: &str = "nacos.core.namespace.delete.confirm-timeout";
+
+const DEFAULT_DELETE_CONFIRM_TIMEOUT: i64 = 300;
+
 // Find all namespaces
 
-pub async fn find_all(db: &DatabaseConnection) -> Vec<Namespace> {
+pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Namespace>> {
     let tenant_infos: Vec<tenant_info::Model> = tenant_info::Entity::find()
         .filter(tenant_info::Column::Kp.eq(DEFAULT_KP))
         .all(db)
-        .await
-        .unwrap();
+        .await?;
 
     let mut tenant_ids: Vec<String> = Vec::new();
     let mut namespaces: Vec<Namespace> = tenant_infos
@@ -48,8 +63,7 @@ pub async fn find_all(db: &DatabaseConnection) -> Vec<Namespace> {
         .group_by(config_info::Column::TenantId)
         .into_model::<SelectResult>()
         .all(db)
-        .await
-        .unwrap()
+        .await?
         .iter()
         .map(|x| (x.tenant_id.clone().unwrap_or_default(), x.count))
         .collect::<HashMap<String, i32>>();
@@ -60,13 +74,13 @@ pub async fn find_all(db: &DatabaseConnection) -> Vec<Namespace> {
         }
     });
 
-    namespaces
+    Ok(namespaces)
 }
 
 pub async fn get_by_namespace_id(
     db: &DatabaseConnection,
     namespace_id: String,
-) -> Option<Namespace> {
+) -> anyhow::Result<Option<Namespace>> {
     let mut namspace: Namespace;
 
     if namespace_id.is_empty() || namespace_id.eq(DEFAULT_NAMESPACE) {
@@ -75,11 +89,10 @@ pub async fn get_by_namespace_id(
         let tenant_info_option = tenant_info::Entity::find()
             .filter(tenant_info::Column::TenantId.eq(namespace_id))
             .one(db)
-            .await
-            .unwrap();
+            .await?;
 
         if tenant_info_option.is_none() {
-            return None;
+            return Ok(None);
         }
 
         let tenant_info = tenant_info_option.unwrap();
@@ -95,14 +108,13 @@ pub async fn get_by_namespace_id(
         .group_by(config_info::Column::TenantId)
         .into_model::<SelectResult>()
         .one(db)
-        .await
-        .unwrap();
+        .await?;
 
     if config_info.is_some() {
         namspace.config_count = config_info.unwrap().count;
     }
 
-    return Some(namspace);
+    return Ok(Some(namspace));
 }
 
 pub async fn create(
@@ -131,12 +143,16 @@ pub async fn create(
     return true;
 }
 
-pub async fn get_count_by_tenant_id(db: &DatabaseConnection, namespace_id: String) -> u64 {
-    return tenant_info::Entity::find()
+pub async fn get_count_by_tenant_id(
+    db: &DatabaseConnection,
+    namespace_id: String,
+) -> anyhow::Result<u64> {
+    let count = tenant_info::Entity::find()
         .filter(tenant_info::Column::TenantId.eq(namespace_id))
         .count(db)
-        .await
-        .unwrap();
+        .await?;
+
+    Ok(count)
 }
 
 pub async fn update(
@@ -144,15 +160,14 @@ pub async fn update(
     namespace: String,
     namespace_show_name: String,
     namespace_desc: String,
-) -> bool {
+) -> anyhow::Result<bool> {
     let entity_option = tenant_info::Entity::find()
         .filter(tenant_info::Column::TenantId.eq(namespace))
         .one(db)
-        .await
-        .unwrap();
+        .await?;
 
     if entity_option.is_none() {
-        return false;
+        return Ok(false);
     }
 
     let mut entity: tenant_info::ActiveModel = entity_option.unwrap().into();
@@ -163,25 +178,260 @@ pub async fn update(
     if entity.is_changed() {
         entity.gmt_modified = Set(chrono::Utc::now().timestamp_millis());
 
-        let res = entity.update(db).await;
+        entity.update(db).await?;
+    }
+
+    return Ok(true);
+}
 
-        if res.is_err() {
-            return false;
+pub async fn delete_report(
+    db: &DatabaseConnection,
+    namespace_id: &str,
+) -> anyhow::Result<NamespaceDeleteReport> {
+    check_deletable(db, namespace_id).await?;
+
+    let config_count = config_info::Entity::find()
+        .filter(config_info::Column::TenantId.eq(namespace_id))
+        .count(db)
+        .await?;
+    let beta_count = config_info_beta::Entity::find()
+        .filter(config_info_beta::Column::TenantId.eq(namespace_id))
+        .count(db)
+        .await?;
+    let tag_count = config_info_tag::Entity::find()
+        .filter(config_info_tag::Column::TenantId.eq(namespace_id))
+        .count(db)
+        .await?;
+    let permission_count = find_permission_resources(db, namespace_id).await?.len() as u64;
+
+    Ok(NamespaceDeleteReport {
+        namespace_id: namespace_id.to_string(),
+        config_count,
+        beta_count,
+        tag_count,
+        permission_count,
+        ..Default::default()
+    })
+}
+
+// Delete the namespace together with its configs and the permissions granted on it, watchers of
+// the deleted configs are notified once the deletion is committed
+pub async fn delete(
+    db: &DatabaseConnection,
+    event_bus: &EventBus,
+    namespace_id: &str,
+) -> anyhow::Result<()> {
+    check_deletable(db, namespace_id).await?;
+
+    let resources = find_permission_resources(db, namespace_id).await?;
+    let txn = db.begin().await?;
+
+    let deleted: Vec<(String, Option<String>)> = config_info::Entity::find()
+        .select_only()
+        .column(config_info::Column::DataId)
+        .column(config_info::Column::GroupId)
+        .filter(config_info::Column::TenantId.eq(namespace_id))
+        .into_tuple()
+        .all(&txn)
+        .await?;
+
+    config_info::Entity::delete_many()
+        .filter(config_info::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+    config_info_beta::Entity::delete_many()
+        .filter(config_info_beta::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+    config_info_tag::Entity::delete_many()
+        .filter(config_info_tag::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+    config_tags_relation::Entity::delete_many()
+        .filter(config_tags_relation::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+
+    if !resources.is_empty() {
+        permissions::Entity::delete_many()
+            .filter(permissions::Column::Resource.is_in(resources))
+            .exec(&txn)
+            .await?;
+    }
+
+    tenant_capacity::Entity::delete_many()
+        .filter(tenant_capacity::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+    tenant_info::Entity::delete_many()
+        .filter(tenant_info::Column::TenantId.eq(namespace_id))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    // a deleted config has an empty md5
+    for (data_id, group) in deleted {
+        event_bus.publish(ServerEvent::ConfigChanged {
+            data_id,
+            group: group.unwrap_or_default(),
+            tenant: namespace_id.to_string(),
+            md5: String::new(),
+        });
+    }
+
+    Ok(())
+}
+
+async fn check_deletable(db: &DatabaseConnection, namespace_id: &str) -> anyhow::Result<()> {
+    if namespace_id.is_empty() || namespace_id.eq(DEFAULT_NAMESPACE) {
+        return Err(BusinessError::ParameterValidate(String::from(
+            "the default namespace can not be deleted",
+        ))
+        .into());
+    }
+
+    if get_count_by_tenant_id(db, namespace_id.to_string()).await? == 0 {
+        return Err(BusinessError::NamespaceNotExist(namespace_id.to_string()).into());
+    }
+
+    Ok(())
+}
+
+// Permission resources are formatted as namespace:group:resource
+async fn find_permission_resources(
+    db: &DatabaseConnection,
+    namespace_id: &str,
+) -> anyhow::Result<Vec<String>> {
+    let prefix = format!("{}:", namespace_id);
+    let mut resources: Vec<String> = permissions::Entity::find()
+        .filter(permissions::Column::Resource.starts_with(&prefix))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|permission| permission.resource)
+        .filter(|resource| resource.starts_with(&prefix))
+        .collect();
+
+    resources.sort();
+    resources.dedup();
+
+    Ok(resources)
+}
+
+// Tokens that confirm a namespace deletion after its cascade report was reviewed. A token is
+// the expire time signed together with the namespace id by the token secret of the cluster, so
+// any member can check it
+#[derive(Debug)]
+pub struct DeleteConfirmationManager {
+    timeout: i64,
+    auth_manager: Arc<AuthManager>,
+}
+
+impl DeleteConfirmationManager {
+    pub fn new(app_config: &Config, auth_manager: Arc<AuthManager>) -> Self {
+        Self {
+            timeout: app_config
+                .get_int(DELETE_CONFIRM_TIMEOUT)
+                .unwrap_or(DEFAULT_DELETE_CONFIRM_TIMEOUT),
+            auth_manager,
         }
     }
 
-    return true;
+    pub fn issue(&self, report: &mut NamespaceDeleteReport) {
+        let expire_time = chrono::Utc::now().timestamp() + self.timeout;
+
+        report.confirm_token = format!(
+            "{}.{}",
+            expire_time,
+            self.sign(&report.namespace_id, expire_time)
+        );
+        report.expire_time = expire_time;
+    }
+
+    pub fn confirm(&self, namespace_id: &str, token: &str) -> bool {
+        let Some((expire_time, signature)) = token
+            .split_once('.')
+            .and_then(|(expire_time, signature)| Some((expire_time.parse().ok()?, signature)))
+        else {
+            return false;
+        };
+
+        expire_time > chrono::Utc::now().timestamp()
+            && fixed_time_eq(
+                self.sign(namespace_id, expire_time).as_bytes(),
+                signature.as_bytes(),
+            )
+    }
+
+    fn sign(&self, namespace_id: &str, expire_time: i64) -> String {
+        let mut hmac = Hmac::new(Sha256::new(), self.auth_manager.secret_key().as_bytes());
+
+        hmac.input(format!("{}\u{0}{}", namespace_id, expire_time).as_bytes());
+
+        hmac.result()
+            .code()
+            .iter()
+            .map(|e| format!("{:02x}", e))
+            .collect()
+    }
 }
 
-pub async fn delete(db: &DatabaseConnection, namespace_id: String) -> bool {
-    let res = tenant_info::Entity::delete_many()
-        .filter(tenant_info::Column::TenantId.eq(namespace_id))
-        .exec(db)
-        .await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::auth::{generate_secret_key, TOKEN_SECRET_KEY};
 
-    if res.is_err() {
-        return false;
+    fn manager(secret_key: &str) -> DeleteConfirmationManager {
+        let app_config = Config::builder()
+            .set_override(TOKEN_SECRET_KEY, secret_key)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        DeleteConfirmationManager::new(
+            &app_config,
+            Arc::new(AuthManager::new(&app_config).unwrap()),
+        )
     }
 
-    return true;
+    fn issue(manager: &DeleteConfirmationManager, namespace_id: &str) -> String {
+        let mut report = NamespaceDeleteReport {
+            namespace_id: namespace_id.to_string(),
+            ..Default::default()
+        };
+
+        manager.issue(&mut report);
+
+        report.confirm_token
+    }
+
+    #[test]
+    fn token_confirms_its_namespace_on_any_member() {
+        let secret_key = generate_secret_key();
+        let token = issue(&manager(&secret_key), "dev");
+
+        assert!(manager(&secret_key).confirm("dev", &token));
+        assert!(!manager(&secret_key).confirm("prod", &token));
+        assert!(!manager(&generate_secret_key()).confirm("dev", &token));
+    }
+
+    #[test]
+    fn tampered_or_expired_token_is_rejected() {
+        let manager = manager(&generate_secret_key());
+        let token = issue(&manager, "dev");
+        let (expire_time, signature) = token.split_once('.').unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        assert!(!manager.confirm(
+            "dev",
+            &format!("{}.{}", expire_time.parse::<i64>().unwrap() + 60, signature)
+        ));
+        assert!(!manager.confirm(
+            "dev",
+            &format!("{}.{}", now - 1, manager.sign("dev", now - 1))
+        ));
+        assert!(!manager.confirm("dev", "garbage"));
+        assert!(!manager.confirm("dev", ""));
+    }
 }
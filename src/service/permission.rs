@@ -1,3 +1,4 @@
+use regex::Regex;
 use sea_orm::*;
 
 use crate::{entity::permissions, model::auth::PermissionInfo, model::common::Page};
@@ -73,3 +74,43 @@ pub async fn delete(
 
     anyhow::Ok(())
 }
+
+pub async fn find_by_roles(
+    db: &DatabaseConnection,
+    roles: &[String],
+) -> anyhow::Result<Vec<PermissionInfo>> {
+    if roles.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Role.is_in(roles.to_vec()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(PermissionInfo::from)
+        .collect();
+
+    Ok(permissions)
+}
+
+// Same rules as Nacos: * in a granted resource matches anything and a granted rw covers r and w
+pub fn is_granted(permissions: &[PermissionInfo], resource: &str, action: &str) -> bool {
+    !action.is_empty()
+        && permissions.iter().any(|permission| {
+            permission.action.contains(action) && resource_matches(&permission.resource, resource)
+        })
+}
+
+fn resource_matches(pattern: &str, resource: &str) -> bool {
+    let regex = format!(
+        "^{}$",
+        pattern
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*")
+    );
+
+    Regex::new(&regex).is_ok_and(|regex| regex.is_match(resource))
+}
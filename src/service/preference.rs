@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use sea_orm::{sea_query::OnConflict, *};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{entity::user_preference, model::common::BusinessError};
+
+const MAX_SAVED_FILTERS: usize = 50;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedFilter {
+    pub name: String,
+    // the console page the filter belongs to, like config or history
+    pub page: String,
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPreference {
+    pub default_namespace: Option<String>,
+    pub page_size: Option<u64>,
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
+}
+
+// Preferences are kept as json in the user_preference table, one row per user
+pub async fn find(db: &DatabaseConnection, username: &str) -> anyhow::Result<UserPreference> {
+    let entity = user_preference::Entity::find_by_id(username)
+        .one(db)
+        .await?;
+
+    Ok(match entity {
+        Some(entity) => serde_json::from_str(&entity.preference)?,
+        None => UserPreference::default(),
+    })
+}
+
+pub async fn save(
+    db: &DatabaseConnection,
+    username: &str,
+    preference: &UserPreference,
+) -> anyhow::Result<bool> {
+    if preference.saved_filters.len() > MAX_SAVED_FILTERS {
+        return Err(BusinessError::ParameterValidate(format!(
+            "at most {} saved filters are allowed",
+            MAX_SAVED_FILTERS
+        ))
+        .into());
+    }
+
+    if preference
+        .saved_filters
+        .iter()
+        .any(|filter| filter.name.trim().is_empty())
+    {
+        return Err(BusinessError::ParameterValidate(String::from(
+            "saved filter name is required",
+        ))
+        .into());
+    }
+
+    let entity = user_preference::ActiveModel {
+        username: Set(username.to_string()),
+        preference: Set(serde_json::to_string(preference)?),
+        gmt_modified: Set(chrono::Utc::now().timestamp_millis()),
+    };
+
+    user_preference::Entity::insert(entity)
+        .on_conflict(
+            OnConflict::column(user_preference::Column::Username)
+                .update_columns([
+                    user_preference::Column::Preference,
+                    user_preference::Column::GmtModified,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+    Ok(true)
+}
@@ -0,0 +1,206 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use config::Config;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    model::auth::ApiType,
+    service::{
+        self,
+        auth::{AuthManager, AUTH_ENABLED},
+        cluster::ServerMemberManager,
+        ip_filter::IpFilterManager,
+        member_lookup::MEMBER_LIST,
+        write_quota::{WriteQuotaManager, NAMESPACE_DEFAULT_LIMIT, USER_DEFAULT_LIMIT},
+    },
+};
+
+pub const LOG_LEVEL: &str = "nacos.core.log.level";
+pub const RELOAD_WATCH_INTERVAL: &str = "nacos.core.config.reload.watch-interval";
+
+const DEFAULT_WATCH_INTERVAL: u64 = 5000;
+
+// Replaces the log filter of the process with directives like info or batata=debug,info,
+// implemented by the binary which owns the tracing subscriber
+pub trait LogLevelReloader: Debug + Send + Sync {
+    fn reload(&self, level: &str) -> anyhow::Result<()>;
+}
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadReport {
+    // settings whose value changed and got applied: log-level, auth, quota, ip-filter,
+    // member-list
+    pub applied: Vec<String>,
+    pub reload_time: i64,
+}
+
+// Managers holding the settings a reload applies
+#[derive(Debug)]
+pub struct ReloadTargets {
+    pub auth_manager: Arc<AuthManager>,
+    pub write_quota_manager: Arc<WriteQuotaManager>,
+    pub ip_filter_manager: Arc<IpFilterManager>,
+    pub member_manager: Arc<ServerMemberManager>,
+}
+
+// Reads the config file again, with the same environment variables and arguments, and applies
+// the settings that can change at runtime. Everything else, including the settings read from
+// AppState::app_config on each request, keeps its startup value until a restart
+#[derive(Debug)]
+pub struct ConfigReloader {
+    config_file: String,
+    args: Vec<String>,
+    current: Mutex<Config>,
+    last_modified: Mutex<Option<SystemTime>>,
+    log_level_reloader: Option<Arc<dyn LogLevelReloader>>,
+    targets: ReloadTargets,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config_file: &str,
+        args: Vec<String>,
+        app_config: &Config,
+        log_level_reloader: Option<Arc<dyn LogLevelReloader>>,
+        targets: ReloadTargets,
+    ) -> anyhow::Result<Self> {
+        // the subscriber starts with its own default, the configured level applies from here
+        if let (Some(log_level_reloader), Ok(level)) =
+            (&log_level_reloader, app_config.get_string(LOG_LEVEL))
+        {
+            log_level_reloader.reload(&level)?;
+        }
+
+        Ok(ConfigReloader {
+            config_file: config_file.to_string(),
+            args,
+            current: Mutex::new(app_config.clone()),
+            last_modified: Mutex::new(modified_time(config_file)),
+            log_level_reloader,
+            targets,
+        })
+    }
+
+    // A setting is only applied when its value in the file changed, so runtime changes made
+    // through the admin API survive reloads triggered by other settings
+    pub fn reload(&self) -> anyhow::Result<ReloadReport> {
+        let app_config = service::app_config::load(&self.config_file, self.args.iter().cloned())?;
+        let mut current = self.current.lock().unwrap();
+        let changed = |keys: &[String]| {
+            keys.iter()
+                .any(|key| current.get_string(key).ok() != app_config.get_string(key).ok())
+        };
+        let mut applied = Vec::new();
+
+        if changed(&[LOG_LEVEL.to_string()]) {
+            if let Some(log_level_reloader) = &self.log_level_reloader {
+                log_level_reloader.reload(
+                    &app_config
+                        .get_string(LOG_LEVEL)
+                        .unwrap_or(String::from("info")),
+                )?;
+
+                applied.push(String::from("log-level"));
+            }
+        }
+
+        if changed(&[AUTH_ENABLED.to_string()]) {
+            let enabled = app_config.get_bool(AUTH_ENABLED).unwrap_or(true);
+
+            for api_type in ApiType::ALL {
+                self.targets.auth_manager.set_enabled(api_type, enabled);
+            }
+
+            applied.push(String::from("auth"));
+        }
+
+        if changed(&[
+            NAMESPACE_DEFAULT_LIMIT.to_string(),
+            USER_DEFAULT_LIMIT.to_string(),
+        ]) {
+            self.targets.write_quota_manager.apply_defaults(&app_config);
+
+            applied.push(String::from("quota"));
+        }
+
+        let ip_filter_keys: Vec<String> = ApiType::ALL
+            .iter()
+            .flat_map(|api_type| {
+                ["allow", "deny"]
+                    .map(|list| format!("nacos.core.ip-filter.{}.{}", api_type.key(), list))
+            })
+            .collect();
+
+        if changed(&ip_filter_keys) {
+            self.targets.ip_filter_manager.apply_config(&app_config)?;
+
+            applied.push(String::from("ip-filter"));
+        }
+
+        if changed(&[MEMBER_LIST.to_string()])
+            && self.targets.member_manager.apply_member_list(&app_config)?
+        {
+            let member_manager = self.targets.member_manager.clone();
+
+            tokio::spawn(async move { member_manager.refresh().await });
+
+            applied.push(String::from("member-list"));
+        }
+
+        *current = app_config;
+
+        tracing::info!(
+            "config {} reloaded, applied {:?}",
+            self.config_file,
+            applied
+        );
+
+        Ok(ReloadReport {
+            applied,
+            reload_time: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
+    // Polls the modification time of the config file, an interval of 0 turns watching off
+    pub fn start(self: Arc<Self>, app_config: &Config) {
+        let interval = app_config
+            .get_int(RELOAD_WATCH_INTERVAL)
+            .map_or(DEFAULT_WATCH_INTERVAL, |e| e.max(0) as u64);
+
+        if interval == 0 {
+            return;
+        }
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(interval)).await;
+
+                let modified_time = modified_time(&self.config_file);
+
+                if modified_time == *self.last_modified.lock().unwrap() {
+                    continue;
+                }
+
+                *self.last_modified.lock().unwrap() = modified_time;
+
+                if let Err(err) = self.reload() {
+                    tracing::warn!("reload config {} failed: {}", self.config_file, err);
+                }
+            }
+        });
+    }
+}
+
+// config::File::with_name also accepts the path without its extension
+fn modified_time(config_file: &str) -> Option<SystemTime> {
+    std::fs::metadata(config_file)
+        .or_else(|_| std::fs::metadata(format!("{}.yml", config_file)))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,421 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::{
+    model::{common::BusinessError, config::ConfigChange},
+    service::{
+        self, approval::ApprovalManager, capacity::CapacityPolicy, config_schema::matches_pattern,
+        crypto::ContentCipher, event::EventBus, event::ServerEvent, write_lock::WriteLockManager,
+    },
+};
+
+pub const MIN_INTERVAL_SECONDS: u64 = 5;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+const REMOTE_PAGE_SIZE: u64 = 100;
+
+// Counts of the last run of a task
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub created: u64,
+    pub updated: u64,
+    pub unchanged: u64,
+    // the local copy was modified after the remote one and is kept
+    pub conflicts: u64,
+    pub failed: u64,
+}
+
+// Pulls the configs of a namespace of another Batata or Nacos cluster into a local namespace.
+// Two way sync is a task on each side, contents that are equal on both sides are not written
+// again so the two tasks do not bounce changes between each other
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTask {
+    pub id: u64,
+    // base url including the context path, like http://10.0.0.1:8848/nacos
+    pub remote_server: String,
+    pub remote_namespace: String,
+    pub namespace: String,
+    // * matches any characters, empty matches everything
+    pub group: String,
+    pub data_id: String,
+    pub interval_seconds: u64,
+    pub operator: String,
+    pub create_time: i64,
+    pub last_sync_time: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+    #[serde(skip)]
+    pub username: Option<String>,
+    #[serde(skip)]
+    pub password: Option<String>,
+}
+
+impl SyncTask {
+    fn is_due(&self, now: i64) -> bool {
+        self.last_sync_time.is_none_or(|last_sync_time| {
+            now - last_sync_time >= (self.interval_seconds * 1000) as i64
+        })
+    }
+
+    fn matches(&self, group: &str, data_id: &str) -> bool {
+        (self.group.is_empty() || matches_pattern(&self.group, group))
+            && (self.data_id.is_empty() || matches_pattern(&self.data_id, data_id))
+    }
+}
+
+// What a run needs to write configs the way a publish does
+#[derive(Clone, Debug)]
+pub struct SyncContext {
+    pub db: DatabaseConnection,
+    pub content_cipher: Arc<ContentCipher>,
+    pub capacity_policy: Arc<CapacityPolicy>,
+    pub event_bus: Arc<EventBus>,
+    pub write_lock_manager: Arc<WriteLockManager>,
+    pub approval_manager: Arc<ApprovalManager>,
+}
+
+// Tasks only live on the node they were created on, so a cluster does not run them twice
+#[derive(Debug, Default)]
+pub struct SyncManager {
+    next_id: AtomicU64,
+    tasks: RwLock<BTreeMap<u64, SyncTask>>,
+    running: Mutex<HashSet<u64>>,
+}
+
+impl SyncManager {
+    pub fn tasks(&self) -> Vec<SyncTask> {
+        self.tasks.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn create(&self, mut task: SyncTask) -> SyncTask {
+        task.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        task.remote_server = task.remote_server.trim_end_matches('/').to_string();
+        task.interval_seconds = task.interval_seconds.max(MIN_INTERVAL_SECONDS);
+        task.create_time = chrono::Utc::now().timestamp_millis();
+
+        self.tasks.write().unwrap().insert(task.id, task.clone());
+
+        task
+    }
+
+    pub fn delete(&self, id: u64) -> bool {
+        self.tasks.write().unwrap().remove(&id).is_some()
+    }
+
+    pub fn start(self: Arc<Self>, context: SyncContext) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+
+                let now = chrono::Utc::now().timestamp_millis();
+                let due: Vec<u64> = self
+                    .tasks
+                    .read()
+                    .unwrap()
+                    .values()
+                    .filter(|task| task.is_due(now))
+                    .map(|task| task.id)
+                    .collect();
+
+                for id in due {
+                    let manager = self.clone();
+                    let context = context.clone();
+
+                    tokio::spawn(async move {
+                        if let Err(err) = manager.run(id, &context).await {
+                            tracing::warn!("config sync task {} failed: {}", id, err);
+                        }
+                    });
+                }
+            }
+        });
+    }
+
+    // Runs a task now, a task that is already running is not started a second time
+    pub async fn run(&self, id: u64, context: &SyncContext) -> anyhow::Result<SyncReport> {
+        let task = self
+            .tasks
+            .read()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| {
+                BusinessError::ResourceNotFound(format!("sync task {} not exist", id))
+            })?;
+
+        if !self.running.lock().unwrap().insert(id) {
+            return Err(BusinessError::ResourceConflict(format!(
+                "sync task {} is already running",
+                id
+            ))
+            .into());
+        }
+
+        let result = sync(&task, context).await;
+
+        self.running.lock().unwrap().remove(&id);
+
+        // the task may have been deleted meanwhile
+        if let Some(task) = self.tasks.write().unwrap().get_mut(&id) {
+            task.last_sync_time = Some(chrono::Utc::now().timestamp_millis());
+
+            match &result {
+                Ok(report) => {
+                    task.last_error = None;
+                    task.last_report = Some(report.clone());
+                }
+                Err(err) => task.last_error = Some(err.to_string()),
+            }
+        }
+
+        result
+    }
+}
+
+struct Remote {
+    server: String,
+    access_token: Option<String>,
+}
+
+impl Remote {
+    fn connect(task: &SyncTask) -> anyhow::Result<Self> {
+        let mut remote = Remote {
+            server: task.remote_server.clone(),
+            access_token: None,
+        };
+
+        // without credentials the remote has to run with auth disabled
+        if let (Some(username), Some(password)) = (&task.username, &task.password) {
+            let login: Value = ureq::post(&format!("{}/v1/auth/users/login", remote.server))
+                .timeout(REMOTE_TIMEOUT)
+                .send_form(&[("username", username), ("password", password)])?
+                .into_json()?;
+
+            remote.access_token = login["accessToken"].as_str().map(String::from);
+        }
+
+        Ok(remote)
+    }
+
+    fn get(&self, path: &str, query: &[(&str, &str)]) -> anyhow::Result<Value> {
+        let req = ureq::get(&format!("{}{}", self.server, path))
+            .timeout(REMOTE_TIMEOUT)
+            .query_pairs(query.to_vec());
+        let req = match &self.access_token {
+            Some(access_token) => req.set("accessToken", access_token),
+            None => req,
+        };
+
+        Ok(req.call()?.into_json()?)
+    }
+
+    // dataId, group and md5 of every config of the namespace
+    fn list(&self, namespace: &str) -> anyhow::Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut page_no = 1u64;
+
+        loop {
+            let page = self.get(
+                "/v1/cs/configs",
+                &[
+                    ("search", "blur"),
+                    ("tenant", namespace),
+                    ("dataId", ""),
+                    ("group", ""),
+                    ("pageNo", &page_no.to_string()),
+                    ("pageSize", &REMOTE_PAGE_SIZE.to_string()),
+                ],
+            )?;
+
+            items.extend(page["pageItems"].as_array().cloned().unwrap_or_default());
+
+            if page_no >= page["pagesAvailable"].as_u64().unwrap_or_default() {
+                return Ok(items);
+            }
+
+            page_no += 1;
+        }
+    }
+}
+
+async fn sync(task: &SyncTask, context: &SyncContext) -> anyhow::Result<SyncReport> {
+    // a sync would write around the review, and every run would submit the same changes again
+    if context.approval_manager.is_protected(&task.namespace) {
+        return Err(protected_namespace(&task.namespace).into());
+    }
+
+    let (remote, items) = {
+        let task = task.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let remote = Remote::connect(&task)?;
+            let items = remote.list(&task.remote_namespace)?;
+
+            anyhow::Ok((Arc::new(remote), items))
+        })
+        .await??
+    };
+    let mut report = SyncReport::default();
+
+    for item in items {
+        let data_id = item["dataId"].as_str().unwrap_or_default().to_string();
+        let group = item["group"].as_str().unwrap_or_default().to_string();
+
+        if data_id.is_empty() || !task.matches(&group, &data_id) {
+            continue;
+        }
+
+        match sync_config(task, context, remote.clone(), &item, &data_id, &group).await {
+            Ok(outcome) => match outcome {
+                SyncOutcome::Created => report.created += 1,
+                SyncOutcome::Updated => report.updated += 1,
+                SyncOutcome::Unchanged => report.unchanged += 1,
+                SyncOutcome::Conflict => report.conflicts += 1,
+            },
+            Err(err) => {
+                report.failed += 1;
+
+                tracing::warn!(
+                    "sync config {} {} from {} failed: {}",
+                    group,
+                    data_id,
+                    task.remote_server,
+                    err
+                );
+            }
+        }
+    }
+
+    tracing::info!("config sync task {} done: {:?}", task.id, report);
+
+    Ok(report)
+}
+
+enum SyncOutcome {
+    Created,
+    Updated,
+    Unchanged,
+    Conflict,
+}
+
+async fn sync_config(
+    task: &SyncTask,
+    context: &SyncContext,
+    remote: Arc<Remote>,
+    item: &Value,
+    data_id: &str,
+    group: &str,
+) -> anyhow::Result<SyncOutcome> {
+    let local_md5 = service::config::find_md5(&context.db, data_id, group, &task.namespace).await?;
+
+    if local_md5.as_deref() == item["md5"].as_str() {
+        return Ok(SyncOutcome::Unchanged);
+    }
+
+    let remote_config = {
+        let query = [
+            ("show", String::from("all")),
+            ("dataId", data_id.to_string()),
+            ("group", group.to_string()),
+            ("tenant", task.remote_namespace.clone()),
+        ];
+
+        tokio::task::spawn_blocking(move || {
+            let query: Vec<(&str, &str)> = query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+            remote.get("/v1/cs/configs", &query)
+        })
+        .await??
+    };
+
+    if local_md5.is_some() {
+        let local_config = service::config::find_all(
+            &context.db,
+            &context.content_cipher,
+            data_id,
+            group,
+            &task.namespace,
+        )
+        .await?;
+
+        if local_config.modify_time * 1000 >= to_millis(remote_config["modifyTime"].as_i64()) {
+            return Ok(SyncOutcome::Conflict);
+        }
+    }
+
+    context.write_lock_manager.check(&task.namespace, group)?;
+
+    let text = |key: &str| remote_config[key].as_str().unwrap_or_default().to_string();
+    let change = ConfigChange {
+        data_id: data_id.to_string(),
+        group: group.to_string(),
+        tenant: task.namespace.clone(),
+        content: text("content"),
+        app_name: text("appName"),
+        src_user: task.operator.clone(),
+        src_ip: remote_host(&task.remote_server).to_string(),
+        config_tags: text("configTags"),
+        desc: text("desc"),
+        r#use: text("use"),
+        effect: text("effect"),
+        r#type: text("type"),
+        schema: text("schema"),
+        encrypted_data_key: text("encryptedDataKey"),
+        ..Default::default()
+    };
+
+    service::config::publish(
+        &context.db,
+        &context.content_cipher,
+        &context.capacity_policy,
+        &change,
+    )
+    .await?;
+
+    context
+        .event_bus
+        .publish(ServerEvent::config_changed(&change));
+
+    Ok(if local_md5.is_some() {
+        SyncOutcome::Updated
+    } else {
+        SyncOutcome::Created
+    })
+}
+
+pub fn protected_namespace(namespace: &str) -> BusinessError {
+    BusinessError::ParameterValidate(format!(
+        "namespace '{}' is protected, its changes need approval and cannot be synced",
+        namespace
+    ))
+}
+
+// host:port of the remote, the history keeps it as the source address of the change
+fn remote_host(server: &str) -> &str {
+    let server = server.split_once("://").map_or(server, |(_, rest)| rest);
+
+    server.split('/').next().unwrap_or_default()
+}
+
+// Nacos answers the modify time in milliseconds and Batata in seconds, seconds stay far below
+// 10^12 for the foreseeable future
+fn to_millis(time: Option<i64>) -> i64 {
+    match time.unwrap_or_default() {
+        time if time < 1_000_000_000_000 => time * 1000,
+        time => time,
+    }
+}
@@ -0,0 +1,52 @@
+use config::Config;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslVerifyMode};
+
+pub fn is_enabled(app_config: &Config) -> bool {
+    app_config.get_bool("server.ssl.enabled").unwrap_or(false)
+}
+
+// Serving https needs server.ssl.certificate (PEM chain) and server.ssl.certificate-private-key
+pub fn acceptor(app_config: &Config) -> anyhow::Result<Option<SslAcceptorBuilder>> {
+    if !is_enabled(app_config) {
+        return Ok(None);
+    }
+
+    let certificate = app_config.get_string("server.ssl.certificate")?;
+    let private_key = app_config.get_string("server.ssl.certificate-private-key")?;
+
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+
+    builder.set_certificate_chain_file(&certificate)?;
+    builder.set_private_key_file(&private_key, SslFiletype::PEM)?;
+    builder.check_private_key()?;
+
+    // same values as spring boot, want asks for a client certificate and need requires one
+    let client_auth = app_config
+        .get_string("server.ssl.client-auth")
+        .unwrap_or("none".to_string());
+    let verify_mode = match client_auth.as_str() {
+        "none" => None,
+        "want" => Some(SslVerifyMode::PEER),
+        "need" => Some(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT),
+        other => anyhow::bail!(
+            "unknown server.ssl.client-auth '{}', expected none, want or need",
+            other
+        ),
+    };
+
+    if let Some(verify_mode) = verify_mode {
+        builder.set_ca_file(app_config.get_string("server.ssl.trust-certificate")?)?;
+        builder.set_verify(verify_mode);
+    }
+
+    Ok(Some(builder))
+}
+
+// Plain http port answering every request with a redirect to the https port
+pub fn http_redirect_port(app_config: &Config) -> Option<u16> {
+    app_config
+        .get_int("server.ssl.http-redirect-port")
+        .ok()
+        .and_then(|e| u16::try_from(e).ok())
+        .filter(|e| *e > 0)
+}
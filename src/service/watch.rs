@@ -0,0 +1,451 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use regex::Regex;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+
+use crate::{
+    model::common::BusinessError,
+    service::{
+        config_schema::pattern_regex,
+        event::{ConfigSubscription, EventBus, ServerEvent},
+    },
+};
+
+// Events a watcher may fall behind by before it misses some, like a lagging bus subscriber
+const WATCHER_CAPACITY: usize = 256;
+const MAX_SUBSCRIPTIONS: usize = 100;
+// Cannot appear in a group, joins it with the dataId in the index keys
+const SEPARATOR: char = '\u{0}';
+
+#[derive(Debug)]
+enum Pattern {
+    Any,
+    Exact(String),
+    Glob(Regex),
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Self {
+        if pattern.is_empty() {
+            return Pattern::Any;
+        }
+
+        if !pattern.contains('*') {
+            return Pattern::Exact(pattern.to_string());
+        }
+
+        Pattern::Glob(pattern_regex(pattern))
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Pattern::Any => true,
+            Pattern::Exact(pattern) => pattern == value,
+            Pattern::Glob(regex) => regex.is_match(value),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    watcher_id: u64,
+    subscription: ConfigSubscription,
+    group: Pattern,
+    data_id: Pattern,
+}
+
+// Subscriptions are kept at the node of the literal prefix of their patterns, group then
+// dataId, so a change only visits the subscriptions along the path of its own key
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, TrieNode>,
+    entries: Vec<IndexEntry>,
+}
+
+impl TrieNode {
+    fn insert(&mut self, prefix: &str, entry: IndexEntry) {
+        let mut node = self;
+
+        for c in prefix.chars() {
+            node = node.children.entry(c).or_default();
+        }
+
+        node.entries.push(entry);
+    }
+
+    // Returns whether the node became empty so the parent can drop it
+    fn remove(&mut self, prefix: &str, watcher_id: u64, subscription: &ConfigSubscription) -> bool {
+        let mut chars = prefix.chars();
+
+        match chars.next() {
+            Some(c) => {
+                if let Some(child) = self.children.get_mut(&c) {
+                    if child.remove(chars.as_str(), watcher_id, subscription) {
+                        self.children.remove(&c);
+                    }
+                }
+            }
+            None => self
+                .entries
+                .retain(|e| e.watcher_id != watcher_id || e.subscription != *subscription),
+        }
+
+        self.children.is_empty() && self.entries.is_empty()
+    }
+
+    fn collect(&self, key: &str, group: &str, data_id: &str, watchers: &mut HashSet<u64>) {
+        let mut node = self;
+        let mut add = |node: &TrieNode| {
+            watchers.extend(
+                node.entries
+                    .iter()
+                    .filter(|e| e.group.matches(group) && e.data_id.matches(data_id))
+                    .map(|e| e.watcher_id),
+            )
+        };
+
+        add(node);
+
+        for c in key.chars() {
+            match node.children.get(&c) {
+                Some(child) => {
+                    node = child;
+                    add(node);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct WatchIndex {
+    namespaces: HashMap<String, TrieNode>,
+    watchers: HashMap<u64, (mpsc::Sender<ServerEvent>, Vec<ConfigSubscription>)>,
+}
+
+// Routes config changes to the watch streams and WebSockets of this node whose subscriptions
+// match them, instead of every connection checking every change against all its patterns
+#[derive(Debug, Default)]
+pub struct WatchRegistry {
+    next_id: AtomicU64,
+    index: RwLock<WatchIndex>,
+}
+
+impl WatchRegistry {
+    pub fn start(self: Arc<Self>, event_bus: &EventBus) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => self.dispatch(&event),
+                    Err(RecvError::Lagged(count)) => {
+                        tracing::warn!("config watchers missed {} events", count)
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    pub fn watch(self: &Arc<Self>) -> Watcher {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel(WATCHER_CAPACITY);
+
+        self.index
+            .write()
+            .unwrap()
+            .watchers
+            .insert(id, (sender, Vec::new()));
+
+        Watcher {
+            id,
+            registry: self.clone(),
+            receiver,
+        }
+    }
+
+    pub fn watcher_count(&self) -> usize {
+        self.index.read().unwrap().watchers.len()
+    }
+
+    fn subscribe(&self, watcher_id: u64, subscription: ConfigSubscription) -> anyhow::Result<()> {
+        let mut index = self.index.write().unwrap();
+
+        match index.watchers.get_mut(&watcher_id) {
+            Some((_, subscriptions)) if !subscriptions.contains(&subscription) => {
+                if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+                    return Err(BusinessError::ParameterValidate(format!(
+                        "at most {} subscriptions are allowed",
+                        MAX_SUBSCRIPTIONS
+                    ))
+                    .into());
+                }
+
+                subscriptions.push(subscription.clone())
+            }
+            _ => return Ok(()),
+        }
+
+        let entry = IndexEntry {
+            watcher_id,
+            group: Pattern::new(&subscription.group),
+            data_id: Pattern::new(&subscription.data_id),
+            subscription,
+        };
+
+        index
+            .namespaces
+            .entry(entry.subscription.tenant.clone())
+            .or_default()
+            .insert(&literal_prefix(&entry.subscription), entry);
+
+        Ok(())
+    }
+
+    fn unsubscribe(&self, watcher_id: u64, subscription: &ConfigSubscription) {
+        let mut index = self.index.write().unwrap();
+
+        if let Some((_, subscriptions)) = index.watchers.get_mut(&watcher_id) {
+            subscriptions.retain(|e| e != subscription);
+        }
+
+        remove_entry(&mut index, watcher_id, subscription);
+    }
+
+    fn unregister(&self, watcher_id: u64) {
+        let mut index = self.index.write().unwrap();
+
+        if let Some((_, subscriptions)) = index.watchers.remove(&watcher_id) {
+            for subscription in &subscriptions {
+                remove_entry(&mut index, watcher_id, subscription);
+            }
+        }
+    }
+
+    fn dispatch(&self, event: &ServerEvent) {
+        let index = self.index.read().unwrap();
+        let mut watchers = HashSet::new();
+
+        match event {
+            ServerEvent::ConfigChanged {
+                data_id,
+                group,
+                tenant,
+                ..
+            } => {
+                if let Some(root) = index.namespaces.get(tenant) {
+                    let key = format!("{}{}{}", group, SEPARATOR, data_id);
+
+                    root.collect(&key, group, data_id, &mut watchers);
+                }
+            }
+        }
+
+        for watcher_id in watchers {
+            if let Some((sender, _)) = index.watchers.get(&watcher_id) {
+                // a full channel is a slow client, it misses the event rather than blocking
+                let _ = sender.try_send(event.clone());
+            }
+        }
+    }
+}
+
+// Receives the changes matching its subscriptions until dropped
+#[derive(Debug)]
+pub struct Watcher {
+    id: u64,
+    registry: Arc<WatchRegistry>,
+    receiver: mpsc::Receiver<ServerEvent>,
+}
+
+impl Watcher {
+    // Subscribing again to a subscription of the watcher changes nothing
+    pub fn subscribe(&self, subscription: ConfigSubscription) -> anyhow::Result<()> {
+        self.registry.subscribe(self.id, subscription)
+    }
+
+    pub fn unsubscribe(&self, subscription: &ConfigSubscription) {
+        self.registry.unsubscribe(self.id, subscription);
+    }
+
+    pub async fn recv(&mut self) -> Option<ServerEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.registry.unregister(self.id);
+    }
+}
+
+fn remove_entry(index: &mut WatchIndex, watcher_id: u64, subscription: &ConfigSubscription) {
+    let prefix = literal_prefix(subscription);
+
+    if let Some(root) = index.namespaces.get_mut(&subscription.tenant) {
+        if root.remove(&prefix, watcher_id, subscription) {
+            index.namespaces.remove(&subscription.tenant);
+        }
+    }
+}
+
+// The part of group and dataId before the first wildcard, an empty group matches any group
+fn literal_prefix(subscription: &ConfigSubscription) -> String {
+    let group = &subscription.group;
+    let data_id = &subscription.data_id;
+
+    match group.find('*') {
+        Some(end) => group[..end].to_string(),
+        None if group.is_empty() => String::new(),
+        None => format!(
+            "{}{}{}",
+            group,
+            SEPARATOR,
+            &data_id[..data_id.find('*').unwrap_or(data_id.len())]
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(tenant: &str, group: &str, data_id: &str) -> ConfigSubscription {
+        ConfigSubscription {
+            tenant: tenant.to_string(),
+            group: group.to_string(),
+            data_id: data_id.to_string(),
+        }
+    }
+
+    fn changed(tenant: &str, group: &str, data_id: &str) -> ServerEvent {
+        ServerEvent::ConfigChanged {
+            data_id: data_id.to_string(),
+            group: group.to_string(),
+            tenant: tenant.to_string(),
+            md5: String::new(),
+        }
+    }
+
+    // Whether the watcher got an event from dispatching the change
+    fn notified(registry: &WatchRegistry, watcher: &mut Watcher, event: ServerEvent) -> bool {
+        registry.dispatch(&event);
+
+        watcher.receiver.try_recv().is_ok()
+    }
+
+    #[test]
+    fn exact_subscription_matches_only_its_config() {
+        let registry = Arc::new(WatchRegistry::default());
+        let mut watcher = registry.watch();
+
+        watcher.subscribe(subscription("ns", "g", "app")).unwrap();
+
+        assert!(notified(&registry, &mut watcher, changed("ns", "g", "app")));
+        assert!(!notified(
+            &registry,
+            &mut watcher,
+            changed("ns", "g", "app2")
+        ));
+        assert!(!notified(
+            &registry,
+            &mut watcher,
+            changed("ns", "g2", "app")
+        ));
+        assert!(!notified(&registry, &mut watcher, changed("", "g", "app")));
+    }
+
+    #[test]
+    fn glob_matches_below_its_literal_prefix() {
+        let registry = Arc::new(WatchRegistry::default());
+        let mut watcher = registry.watch();
+
+        watcher
+            .subscribe(subscription("ns", "g", "app-*.yml"))
+            .unwrap();
+
+        assert!(notified(
+            &registry,
+            &mut watcher,
+            changed("ns", "g", "app-a.yml")
+        ));
+        assert!(!notified(
+            &registry,
+            &mut watcher,
+            changed("ns", "g", "app-a.json")
+        ));
+        assert!(!notified(&registry, &mut watcher, changed("ns", "g", "ap")));
+    }
+
+    #[test]
+    fn empty_or_wildcard_group_matches_every_group() {
+        let registry = Arc::new(WatchRegistry::default());
+        let mut any = registry.watch();
+        let mut prefixed = registry.watch();
+
+        any.subscribe(subscription("ns", "", "app")).unwrap();
+        prefixed
+            .subscribe(subscription("ns", "team-*", ""))
+            .unwrap();
+
+        registry.dispatch(&changed("ns", "team-a", "app"));
+
+        assert!(any.receiver.try_recv().is_ok());
+        assert!(prefixed.receiver.try_recv().is_ok());
+        assert!(notified(&registry, &mut any, changed("ns", "other", "app")));
+        assert!(!notified(
+            &registry,
+            &mut prefixed,
+            changed("ns", "other", "app")
+        ));
+    }
+
+    #[test]
+    fn unsubscribe_and_drop_clean_up_the_index() {
+        let registry = Arc::new(WatchRegistry::default());
+        let mut watcher = registry.watch();
+        let kept = registry.watch();
+
+        watcher.subscribe(subscription("ns", "g", "app")).unwrap();
+        kept.subscribe(subscription("other", "g*", "")).unwrap();
+        watcher.unsubscribe(&subscription("ns", "g", "app"));
+
+        assert!(!notified(
+            &registry,
+            &mut watcher,
+            changed("ns", "g", "app")
+        ));
+        assert!(!registry.index.read().unwrap().namespaces.contains_key("ns"));
+
+        drop(kept);
+
+        let index = registry.index.read().unwrap();
+
+        assert!(index.namespaces.is_empty());
+        assert_eq!(index.watchers.len(), 1);
+    }
+
+    #[test]
+    fn subscriptions_are_limited_per_watcher() {
+        let registry = Arc::new(WatchRegistry::default());
+        let watcher = registry.watch();
+
+        for i in 0..MAX_SUBSCRIPTIONS {
+            watcher
+                .subscribe(subscription("ns", "g", &i.to_string()))
+                .unwrap();
+        }
+
+        // a subscription the watcher already has does not count
+        assert!(watcher.subscribe(subscription("ns", "g", "0")).is_ok());
+        assert!(watcher.subscribe(subscription("ns", "g", "new")).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{entity::write_lock, model::common::BusinessError};
+
+pub const ALL_GROUPS: &str = "*";
+
+const DEFAULT_NAMESPACE: &str = "public";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteLock {
+    pub namespace: String,
+    pub group: String,
+    pub reason: String,
+    pub operator: String,
+    pub create_time: i64,
+    pub expire_time: Option<i64>,
+}
+
+impl WriteLock {
+    fn is_active(&self, now: i64) -> bool {
+        self.expire_time.is_none_or(|expire_time| expire_time > now)
+    }
+
+    fn to_error(&self) -> anyhow::Error {
+        let until = match self.expire_time {
+            Some(expire_time) => chrono::DateTime::from_timestamp_millis(expire_time)
+                .map(|e| e.to_rfc3339())
+                .unwrap_or_default(),
+            None => String::from("it is removed"),
+        };
+
+        BusinessError::ResourceLocked(format!(
+            "namespace '{}' group '{}' is write locked by {} until {}: {}",
+            self.namespace, self.group, self.operator, until, self.reason
+        ))
+        .into()
+    }
+}
+
+impl From<write_lock::Model> for WriteLock {
+    fn from(value: write_lock::Model) -> Self {
+        Self {
+            namespace: value.namespace,
+            group: value.group_id,
+            reason: value.reason,
+            operator: value.operator,
+            create_time: value.create_time,
+            expire_time: value.expire_time,
+        }
+    }
+}
+
+// Change freeze flags on a namespace or on a single group of it, checked by every write. Locks
+// are stored in the write_lock table, every member keeps a copy that is read again when a
+// member passes a change on and periodically, in case a change did not reach it
+#[derive(Debug, Default)]
+pub struct WriteLockManager {
+    locks: RwLock<BTreeMap<(String, String), WriteLock>>,
+}
+
+impl WriteLockManager {
+    // Replaces the locks with the stored ones
+    pub async fn load(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let locks = write_lock::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(WriteLock::from)
+            .filter(|lock| lock.is_active(now))
+            .map(|lock| ((lock.namespace.clone(), lock.group.clone()), lock))
+            .collect();
+
+        *self.locks.write().unwrap() = locks;
+
+        Ok(())
+    }
+
+    pub fn start(self: Arc<Self>, db: DatabaseConnection) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REFRESH_INTERVAL).await;
+
+                if let Err(err) = self.load(&db).await {
+                    tracing::warn!("refresh write locks failed: {}", err);
+                }
+            }
+        });
+    }
+
+    pub fn locks(&self) -> Vec<WriteLock> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut locks = self.locks.write().unwrap();
+
+        locks.retain(|_, lock| lock.is_active(now));
+
+        locks.values().cloned().collect()
+    }
+
+    pub fn lock(
+        &self,
+        namespace: &str,
+        group: &str,
+        reason: &str,
+        operator: &str,
+        expire_seconds: Option<i64>,
+    ) -> WriteLock {
+        let now = chrono::Utc::now().timestamp_millis();
+        let namespace = normalize_namespace(namespace);
+        let group = if group.is_empty() { ALL_GROUPS } else { group };
+        let lock = WriteLock {
+            namespace: namespace.to_string(),
+            group: group.to_string(),
+            reason: reason.to_string(),
+            operator: operator.to_string(),
+            create_time: now,
+            expire_time: expire_seconds.map(|seconds| now + seconds * 1000),
+        };
+
+        self.locks
+            .write()
+            .unwrap()
+            .insert((lock.namespace.clone(), lock.group.clone()), lock.clone());
+
+        lock
+    }
+
+    pub fn unlock(&self, namespace: &str, group: &str) -> bool {
+        let group = if group.is_empty() { ALL_GROUPS } else { group };
+
+        self.locks
+            .write()
+            .unwrap()
+            .remove(&(
+                normalize_namespace(namespace).to_string(),
+                group.to_string(),
+            ))
+            .is_some()
+    }
+
+    // Writes to a group are blocked by a lock on the group or on its whole namespace
+    pub fn check(&self, namespace: &str, group: &str) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let namespace = normalize_namespace(namespace);
+        let locks = self.locks.read().unwrap();
+
+        for group in [group, ALL_GROUPS] {
+            if let Some(lock) = locks.get(&(namespace.to_string(), group.to_string())) {
+                if lock.is_active(now) {
+                    return Err(lock.to_error());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Writes to the namespace itself are blocked by any lock inside it
+    pub fn check_namespace(&self, namespace: &str) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let namespace = normalize_namespace(namespace);
+
+        match self
+            .locks
+            .read()
+            .unwrap()
+            .values()
+            .find(|lock| lock.namespace == namespace && lock.is_active(now))
+        {
+            Some(lock) => Err(lock.to_error()),
+            None => Ok(()),
+        }
+    }
+}
+
+pub async fn save(db: &DatabaseConnection, lock: &WriteLock) -> anyhow::Result<()> {
+    let entity = write_lock::ActiveModel {
+        namespace: Set(lock.namespace.clone()),
+        group_id: Set(lock.group.clone()),
+        reason: Set(lock.reason.clone()),
+        operator: Set(lock.operator.clone()),
+        create_time: Set(lock.create_time),
+        expire_time: Set(lock.expire_time),
+    };
+
+    write_lock::Entity::insert(entity)
+        .on_conflict(
+            OnConflict::columns([write_lock::Column::Namespace, write_lock::Column::GroupId])
+                .update_columns([
+                    write_lock::Column::Reason,
+                    write_lock::Column::Operator,
+                    write_lock::Column::CreateTime,
+                    write_lock::Column::ExpireTime,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete(db: &DatabaseConnection, namespace: &str, group: &str) -> anyhow::Result<bool> {
+    let group = if group.is_empty() { ALL_GROUPS } else { group };
+    let result = write_lock::Entity::delete_many()
+        .filter(write_lock::Column::Namespace.eq(normalize_namespace(namespace)))
+        .filter(write_lock::Column::GroupId.eq(group))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected > 0)
+}
+
+fn normalize_namespace(namespace: &str) -> &str {
+    if namespace.is_empty() {
+        DEFAULT_NAMESPACE
+    } else {
+        namespace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_lock_blocks_only_its_group() {
+        let manager = WriteLockManager::default();
+
+        manager.lock("dev", "DEFAULT_GROUP", "release", "admin", None);
+
+        assert!(manager.check("dev", "DEFAULT_GROUP").is_err());
+        assert!(manager.check("dev", "OTHER_GROUP").is_ok());
+        assert!(manager.check("prod", "DEFAULT_GROUP").is_ok());
+        // any lock inside the namespace blocks writes to the namespace itself
+        assert!(manager.check_namespace("dev").is_err());
+        assert!(manager.check_namespace("prod").is_ok());
+    }
+
+    #[test]
+    fn namespace_lock_blocks_every_group() {
+        let manager = WriteLockManager::default();
+
+        manager.lock("", "", "freeze", "admin", None);
+
+        assert!(manager.check("public", "DEFAULT_GROUP").is_err());
+        assert!(manager.check("", "OTHER_GROUP").is_err());
+        assert!(manager.unlock("public", ALL_GROUPS));
+        assert!(manager.check("", "OTHER_GROUP").is_ok());
+        assert!(!manager.unlock("public", ALL_GROUPS));
+    }
+
+    #[test]
+    fn expired_locks_are_ignored() {
+        let manager = WriteLockManager::default();
+
+        manager.lock("dev", "", "short", "admin", Some(-1));
+
+        assert!(manager.check("dev", "DEFAULT_GROUP").is_ok());
+        assert!(manager.check_namespace("dev").is_ok());
+        assert!(manager.locks().is_empty());
+    }
+}
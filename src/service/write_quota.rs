@@ -0,0 +1,350 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use config::Config;
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::entity::write_quota;
+
+pub const NAMESPACE_DEFAULT_LIMIT: &str = "nacos.core.quota.namespace.default-limit";
+pub const USER_DEFAULT_LIMIT: &str = "nacos.core.quota.user.default-limit";
+pub const DEFAULT_TARGET: &str = "*";
+
+const WINDOW_SECONDS: i64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaScope {
+    Namespace,
+    User,
+}
+
+impl QuotaScope {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "namespace" => Some(QuotaScope::Namespace),
+            "user" => Some(QuotaScope::User),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaScope::Namespace => "namespace",
+            QuotaScope::User => "user",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaRule {
+    pub scope: QuotaScope,
+    pub target: String,
+    pub limit_per_minute: u32,
+}
+
+#[derive(Debug, Default)]
+struct QuotaState {
+    // the defaults of the config file, a stored rule for the same target overrides them
+    defaults: HashMap<(QuotaScope, String), u32>,
+    stored: HashMap<(QuotaScope, String), u32>,
+    rules: HashMap<(QuotaScope, String), u32>,
+    counters: HashMap<(QuotaScope, String), (i64, u32)>,
+}
+
+impl QuotaState {
+    fn merge(&mut self) {
+        self.rules = self.defaults.clone();
+        self.rules
+            .extend(self.stored.iter().map(|(key, limit)| (key.clone(), *limit)));
+    }
+}
+
+// Fixed one minute window write limits per namespace and per user, a limit of 0 means unlimited.
+// Rules set through the admin API are stored in the write_quota table so every member applies
+// them. Writes are counted by each member on its own, a cluster of n members lets up to n times
+// the limit through when clients spread their writes
+#[derive(Debug, Default)]
+pub struct WriteQuotaManager {
+    state: Mutex<QuotaState>,
+}
+
+impl WriteQuotaManager {
+    pub fn new(app_config: &Config) -> Self {
+        let manager = WriteQuotaManager::default();
+
+        manager.apply_defaults(app_config);
+
+        manager
+    }
+
+    // Sets the default rules from the config, a default no longer configured is removed
+    pub fn apply_defaults(&self, app_config: &Config) {
+        let mut state = self.state.lock().unwrap();
+
+        state.defaults = [
+            (QuotaScope::Namespace, NAMESPACE_DEFAULT_LIMIT),
+            (QuotaScope::User, USER_DEFAULT_LIMIT),
+        ]
+        .into_iter()
+        .filter_map(|(scope, key)| {
+            app_config
+                .get_int(key)
+                .ok()
+                .map(|limit| ((scope, DEFAULT_TARGET.to_string()), limit.max(0) as u32))
+        })
+        .collect();
+        state.merge();
+    }
+
+    // Replaces the rules set through the admin API with the stored ones
+    pub async fn load(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let stored = write_quota::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|row| {
+                QuotaScope::from_name(&row.scope)
+                    .map(|scope| ((scope, row.target), row.limit_per_minute))
+            })
+            .collect();
+        let mut state = self.state.lock().unwrap();
+
+        state.stored = stored;
+        state.merge();
+
+        Ok(())
+    }
+
+    pub fn rules(&self) -> Vec<QuotaRule> {
+        self.state
+            .lock()
+            .unwrap()
+            .rules
+            .iter()
+            .map(|((scope, target), limit)| QuotaRule {
+                scope: *scope,
+                target: target.clone(),
+                limit_per_minute: *limit,
+            })
+            .collect()
+    }
+
+    pub fn set_rule(&self, scope: QuotaScope, target: &str, limit_per_minute: u32) {
+        let mut state = self.state.lock().unwrap();
+
+        state
+            .stored
+            .insert((scope, target.to_string()), limit_per_minute);
+        state.merge();
+    }
+
+    pub fn remove_rule(&self, scope: QuotaScope, target: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let removed = state.stored.remove(&(scope, target.to_string())).is_some();
+
+        state.merge();
+
+        removed
+    }
+
+    // Count one write, returns the seconds to wait when a quota is exhausted
+    pub fn try_acquire(&self, namespace: &str, username: &str) -> Result<(), i64> {
+        self.acquire(namespace, username, true)
+    }
+
+    // Like try_acquire without counting the write
+    pub fn check(&self, namespace: &str, username: &str) -> Result<(), i64> {
+        self.acquire(namespace, username, false)
+    }
+
+    fn acquire(&self, namespace: &str, username: &str, count: bool) -> Result<(), i64> {
+        let now = chrono::Utc::now().timestamp();
+        let window = now / WINDOW_SECONDS;
+        let retry_after = WINDOW_SECONDS - now % WINDOW_SECONDS;
+        let mut state = self.state.lock().unwrap();
+
+        let keys: Vec<((QuotaScope, String), u32)> = [
+            (QuotaScope::Namespace, namespace),
+            (QuotaScope::User, username),
+        ]
+        .iter()
+        .filter_map(|(scope, target)| {
+            state
+                .rules
+                .get(&(*scope, target.to_string()))
+                .or_else(|| state.rules.get(&(*scope, DEFAULT_TARGET.to_string())))
+                .filter(|limit| **limit > 0)
+                .map(|limit| ((*scope, target.to_string()), *limit))
+        })
+        .collect();
+
+        let exhausted = keys
+            .iter()
+            .any(|(key, limit)| match state.counters.get(key) {
+                Some((counter_window, count)) => *counter_window == window && count >= limit,
+                None => false,
+            });
+
+        if exhausted {
+            return Err(retry_after);
+        }
+
+        if !count {
+            return Ok(());
+        }
+
+        for (key, _) in keys {
+            let counter = state.counters.entry(key).or_insert((window, 0));
+
+            if counter.0 != window {
+                *counter = (window, 0);
+            }
+
+            counter.1 += 1;
+        }
+
+        state
+            .counters
+            .retain(|_, (counter_window, _)| *counter_window == window);
+
+        Ok(())
+    }
+}
+
+pub async fn save_rule(
+    db: &DatabaseConnection,
+    scope: QuotaScope,
+    target: &str,
+    limit_per_minute: u32,
+) -> anyhow::Result<()> {
+    let entity = write_quota::ActiveModel {
+        scope: Set(scope.name().to_string()),
+        target: Set(target.to_string()),
+        limit_per_minute: Set(limit_per_minute),
+        gmt_modified: Set(chrono::Utc::now().timestamp_millis()),
+    };
+
+    write_quota::Entity::insert(entity)
+        .on_conflict(
+            OnConflict::columns([write_quota::Column::Scope, write_quota::Column::Target])
+                .update_columns([
+                    write_quota::Column::LimitPerMinute,
+                    write_quota::Column::GmtModified,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_rule(
+    db: &DatabaseConnection,
+    scope: QuotaScope,
+    target: &str,
+) -> anyhow::Result<bool> {
+    let result = write_quota::Entity::delete_many()
+        .filter(write_quota::Column::Scope.eq(scope.name()))
+        .filter(write_quota::Column::Target.eq(target))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespace_and_user_limits_apply_together() {
+        let manager = WriteQuotaManager::default();
+
+        manager.set_rule(QuotaScope::Namespace, "dev", 2);
+        manager.set_rule(QuotaScope::User, "alice", 3);
+
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "bob").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_err());
+        // the rejected write did not count for alice
+        assert!(manager.try_acquire("prod", "alice").is_ok());
+        assert!(manager.try_acquire("prod", "alice").is_ok());
+        assert!(manager.try_acquire("prod", "alice").is_err());
+        assert!(manager.try_acquire("prod", "bob").is_ok());
+    }
+
+    #[test]
+    fn specific_rule_overrides_default_and_zero_is_unlimited() {
+        let manager = WriteQuotaManager::default();
+
+        manager.set_rule(QuotaScope::Namespace, DEFAULT_TARGET, 1);
+        manager.set_rule(QuotaScope::Namespace, "batch", 0);
+
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_err());
+
+        for _ in 0..10 {
+            assert!(manager.try_acquire("batch", "alice").is_ok());
+        }
+    }
+
+    #[test]
+    fn check_does_not_count() {
+        let manager = WriteQuotaManager::default();
+
+        manager.set_rule(QuotaScope::User, "alice", 1);
+
+        assert!(manager.check("dev", "alice").is_ok());
+        assert!(manager.check("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+
+        let retry_after = manager.check("dev", "alice").unwrap_err();
+
+        assert!(retry_after > 0 && retry_after <= WINDOW_SECONDS);
+    }
+
+    #[test]
+    fn counters_of_a_past_window_are_reset() {
+        let manager = WriteQuotaManager::default();
+        let window = chrono::Utc::now().timestamp() / WINDOW_SECONDS;
+
+        manager.set_rule(QuotaScope::User, "alice", 1);
+        manager
+            .state
+            .lock()
+            .unwrap()
+            .counters
+            .insert((QuotaScope::User, String::from("alice")), (window - 1, 1));
+
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_err());
+    }
+
+    #[test]
+    fn stored_rule_overrides_config_default() {
+        let app_config = Config::builder()
+            .set_override(NAMESPACE_DEFAULT_LIMIT, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        let manager = WriteQuotaManager::new(&app_config);
+
+        manager.set_rule(QuotaScope::Namespace, DEFAULT_TARGET, 2);
+
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_ok());
+        assert!(manager.try_acquire("dev", "alice").is_err());
+
+        // the config default applies again once the stored rule is removed
+        assert!(manager.remove_rule(QuotaScope::Namespace, DEFAULT_TARGET));
+        assert!(manager.try_acquire("prod", "alice").is_ok());
+        assert!(manager.try_acquire("prod", "alice").is_err());
+    }
+}